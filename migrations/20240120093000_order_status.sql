@@ -0,0 +1,5 @@
+-- order lifecycle status (ordered -> cooking -> ready -> served, or cancelled before served)
+
+CREATE TYPE order_status AS ENUM ('ordered', 'cooking', 'ready', 'served', 'cancelled');
+
+ALTER TABLE orders ADD COLUMN status order_status NOT NULL DEFAULT 'ordered';
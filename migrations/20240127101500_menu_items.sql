@@ -0,0 +1,10 @@
+-- menu catalog (orders must reference an active menu item by name). price is stored in smallest currency unit
+
+CREATE TABLE menu_items(
+    menu_item_id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL UNIQUE,
+    category VARCHAR(64) NOT NULL,
+    price INTEGER NOT NULL CHECK(price >= 0),
+    prep_time_minutes INTEGER NOT NULL CHECK(prep_time_minutes >= 0),
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
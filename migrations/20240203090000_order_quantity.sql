@@ -0,0 +1,3 @@
+-- number of identical items in an order (upper bound is validated by application)
+
+ALTER TABLE orders ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1 CHECK(quantity > 0);
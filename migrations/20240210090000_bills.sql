@@ -0,0 +1,21 @@
+-- bills created by table checkout. Amounts are stored in smallest currency unit
+
+CREATE TABLE bills(
+    bill_id SERIAL PRIMARY KEY,
+    table_id SMALLINT NOT NULL,
+    subtotal INTEGER NOT NULL CHECK(subtotal >= 0),
+    service_charge INTEGER NOT NULL CHECK(service_charge >= 0),
+    tax INTEGER NOT NULL CHECK(tax >= 0),
+    total INTEGER NOT NULL CHECK(total >= 0),
+    creation_time TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE bill_items(
+    bill_id INTEGER NOT NULL REFERENCES bills(bill_id) ON DELETE CASCADE,
+    order_id INTEGER NOT NULL,
+    item_name VARCHAR(255) NOT NULL,
+    quantity INTEGER NOT NULL,
+    unit_price INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY(bill_id, order_id)
+);
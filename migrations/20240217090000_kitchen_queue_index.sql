@@ -0,0 +1,5 @@
+-- indexes for kitchen queue (open orders of all tables sorted by estimated arrival time, filtered by menu category)
+
+CREATE INDEX orders_kitchen_queue_idx ON orders(estimated_arrival_time, creation_time) WHERE status IN ('ordered', 'cooking', 'ready');
+
+CREATE INDEX menu_items_category_idx ON menu_items(category);
@@ -0,0 +1,4 @@
+-- acknowledgement of an order by a device (e.g., kitchen has seen the order). Only the first acknowledgement is kept
+
+ALTER TABLE orders ADD COLUMN acknowledged_by VARCHAR(64);
+ALTER TABLE orders ADD COLUMN acknowledgement_time TIMESTAMPTZ;
@@ -0,0 +1,15 @@
+-- table registry (replaces MAX_TABLES setting). Tables 1..100 and tables of existing orders are registered,
+-- so that orders of existing deployments keep their tables
+
+CREATE TABLE restaurant_tables(
+    table_id SMALLSERIAL PRIMARY KEY,
+    label VARCHAR(64) NOT NULL,
+    capacity SMALLINT NOT NULL CHECK(capacity > 0),
+    section VARCHAR(64) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+INSERT INTO restaurant_tables(table_id, label, capacity, section)
+SELECT id, 'Table ' || id, 4, 'main' FROM (SELECT generate_series(1, 100)::SMALLINT AS id UNION SELECT table_id FROM orders) AS ids;
+
+SELECT setval(pg_get_serial_sequence('restaurant_tables', 'table_id'), (SELECT MAX(table_id) FROM restaurant_tables));
@@ -0,0 +1,13 @@
+-- idempotency keys of order creation requests. The response of the first request is stored (status_code and response_body
+-- are NULL while it is processed) and replayed for retries with the same key
+
+CREATE TABLE idempotency_keys(
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    request_fingerprint TEXT NOT NULL,
+    status_code SMALLINT,
+    response_body TEXT,
+    creation_time TIMESTAMPTZ NOT NULL
+);
+
+-- expired keys are removed by creation time
+CREATE INDEX idempotency_keys_creation_time_idx ON idempotency_keys(creation_time);
@@ -0,0 +1,18 @@
+-- version of each table's orders (used as ETag). It is increased by triggers whenever orders of the table are changed
+-- (including orders moved from or to the table) or its seating session is closed, so every code path changes the version
+
+ALTER TABLE restaurant_tables ADD COLUMN orders_version BIGINT NOT NULL DEFAULT 0;
+
+CREATE FUNCTION bump_orders_version() RETURNS TRIGGER AS $$
+BEGIN
+    -- OLD is NULL for INSERT and NEW is NULL for DELETE
+    UPDATE restaurant_tables SET orders_version = orders_version + 1 WHERE table_id IN (OLD.table_id, NEW.table_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER orders_version_trigger AFTER INSERT OR UPDATE OR DELETE ON orders
+    FOR EACH ROW EXECUTE FUNCTION bump_orders_version();
+
+CREATE TRIGGER table_sessions_version_trigger AFTER UPDATE ON table_sessions
+    FOR EACH ROW EXECUTE FUNCTION bump_orders_version();
@@ -0,0 +1,6 @@
+-- soft deletion of orders. Removed (voided) orders are kept for loss tracking and order history,
+-- and every other query excludes them (deleted_at IS NULL)
+
+ALTER TABLE orders ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE orders ADD COLUMN deleted_by VARCHAR(64);
+ALTER TABLE orders ADD COLUMN reason TEXT;
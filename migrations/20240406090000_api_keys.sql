@@ -0,0 +1,10 @@
+-- API keys of clients (devices, integrations). Only SHA-256 hash of each key is stored,
+-- and revoked keys are kept for reference
+
+CREATE TABLE api_keys(
+    api_key_id SERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    creation_time TIMESTAMPTZ NOT NULL,
+    revocation_time TIMESTAMPTZ
+);
@@ -0,0 +1,5 @@
+-- staff roles of API keys (what the client is allowed to do). Existing keys become waiter keys
+
+CREATE TYPE staff_role AS ENUM ('waiter', 'kitchen', 'cashier', 'manager', 'admin');
+
+ALTER TABLE api_keys ADD COLUMN role staff_role NOT NULL DEFAULT 'waiter';
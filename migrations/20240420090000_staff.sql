@@ -0,0 +1,14 @@
+-- staff accounts logging in from devices with PIN (JWT login). Only salted PBKDF2 hash of each PIN is stored,
+-- and orders record the staff who created them
+
+CREATE TABLE staff(
+    staff_id VARCHAR(64) PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    role staff_role NOT NULL,
+    pin_hash CHAR(64) NOT NULL,
+    pin_salt CHAR(32) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    creation_time TIMESTAMPTZ NOT NULL
+);
+
+ALTER TABLE orders ADD COLUMN created_by VARCHAR(64);
@@ -0,0 +1,2 @@
+-- ETag of the stored response, which is replayed with the response body
+ALTER TABLE idempotency_keys ADD COLUMN etag TEXT;
@@ -0,0 +1,5 @@
+-- menu item and its price at the time the order was added. Bills are priced from this snapshot, so later menu changes
+-- (price update, rename or removal) don't change what the party pays. Orders added before this migration have no snapshot
+
+ALTER TABLE orders ADD COLUMN menu_item_id INTEGER;
+ALTER TABLE orders ADD COLUMN unit_price INTEGER;
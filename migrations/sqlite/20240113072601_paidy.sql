@@ -0,0 +1,10 @@
+-- Add migration script here (sqlite version of ../20240113072601_paidy.sql)
+
+CREATE TABLE orders(   
+    order_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id SMALLINT NOT NULL,
+    item_name VARCHAR(255) NOT NULL CHECK(length(item_name) <= 255),
+    note VARCHAR(255) CHECK(length(note) <= 255),
+    creation_time TIMESTAMPTZ NOT NULL,
+    estimated_arrival_time TIMESTAMPTZ NOT NULL
+);
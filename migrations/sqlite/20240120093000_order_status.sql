@@ -0,0 +1,3 @@
+-- order lifecycle status (sqlite version of ../20240120093000_order_status.sql)
+
+ALTER TABLE orders ADD COLUMN status TEXT NOT NULL DEFAULT 'ordered' CHECK(status IN ('ordered', 'cooking', 'ready', 'served', 'cancelled'));
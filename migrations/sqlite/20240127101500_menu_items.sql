@@ -0,0 +1,10 @@
+-- menu catalog (sqlite version of ../20240127101500_menu_items.sql)
+
+CREATE TABLE menu_items(
+    menu_item_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(255) NOT NULL UNIQUE CHECK(length(name) <= 255),
+    category VARCHAR(64) NOT NULL CHECK(length(category) <= 64),
+    price INTEGER NOT NULL CHECK(price >= 0),
+    prep_time_minutes INTEGER NOT NULL CHECK(prep_time_minutes >= 0),
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
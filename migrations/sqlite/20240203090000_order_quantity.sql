@@ -0,0 +1,3 @@
+-- number of identical items in an order (sqlite version of ../20240203090000_order_quantity.sql)
+
+ALTER TABLE orders ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1 CHECK(quantity > 0);
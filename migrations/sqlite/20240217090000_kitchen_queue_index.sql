@@ -0,0 +1,5 @@
+-- indexes for kitchen queue (sqlite version of ../20240217090000_kitchen_queue_index.sql)
+
+CREATE INDEX orders_kitchen_queue_idx ON orders(estimated_arrival_time, creation_time) WHERE status IN ('ordered', 'cooking', 'ready');
+
+CREATE INDEX menu_items_category_idx ON menu_items(category);
@@ -0,0 +1,4 @@
+-- acknowledgement of an order by a device (sqlite version of ../20240224090000_order_acknowledgement.sql)
+
+ALTER TABLE orders ADD COLUMN acknowledged_by VARCHAR(64) CHECK(length(acknowledged_by) <= 64);
+ALTER TABLE orders ADD COLUMN acknowledgement_time TIMESTAMPTZ;
@@ -0,0 +1,13 @@
+-- table registry (sqlite version of ../20240302090000_restaurant_tables.sql)
+
+CREATE TABLE restaurant_tables(
+    table_id INTEGER PRIMARY KEY AUTOINCREMENT CHECK(table_id <= 32767),
+    label VARCHAR(64) NOT NULL CHECK(length(label) <= 64),
+    capacity SMALLINT NOT NULL CHECK(capacity > 0),
+    section VARCHAR(64) NOT NULL CHECK(length(section) <= 64),
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+WITH RECURSIVE ids(id) AS (SELECT 1 UNION ALL SELECT id + 1 FROM ids WHERE id < 100)
+INSERT INTO restaurant_tables(table_id, label, capacity, section)
+SELECT id, 'Table ' || id, 4, 'main' FROM (SELECT id FROM ids UNION SELECT table_id FROM orders);
@@ -0,0 +1,18 @@
+-- seating sessions (sqlite version of ../20240309090000_table_sessions.sql)
+
+CREATE TABLE table_sessions(
+    session_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_id SMALLINT NOT NULL,
+    guest_count SMALLINT CHECK(guest_count > 0),
+    open_time TIMESTAMPTZ NOT NULL,
+    close_time TIMESTAMPTZ
+);
+
+-- at most one open session per table
+CREATE UNIQUE INDEX table_sessions_open_idx ON table_sessions(table_id) WHERE close_time IS NULL;
+
+ALTER TABLE orders ADD COLUMN session_id INTEGER REFERENCES table_sessions(session_id);
+CREATE INDEX orders_session_idx ON orders(session_id);
+
+INSERT INTO table_sessions(table_id, open_time) SELECT table_id, MIN(creation_time) FROM orders GROUP BY table_id;
+UPDATE orders SET session_id = (SELECT s.session_id FROM table_sessions s WHERE s.table_id = orders.table_id);
@@ -0,0 +1,12 @@
+-- idempotency keys (sqlite version of ../20240316090000_idempotency_keys.sql)
+
+CREATE TABLE idempotency_keys(
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    request_fingerprint TEXT NOT NULL,
+    status_code SMALLINT,
+    response_body TEXT,
+    creation_time TIMESTAMPTZ NOT NULL
+);
+
+-- expired keys are removed by creation time
+CREATE INDEX idempotency_keys_creation_time_idx ON idempotency_keys(creation_time);
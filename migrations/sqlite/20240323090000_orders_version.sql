@@ -0,0 +1,23 @@
+-- version of each table's orders (sqlite version of ../20240323090000_orders_version.sql)
+
+ALTER TABLE restaurant_tables ADD COLUMN orders_version INTEGER NOT NULL DEFAULT 0;
+
+CREATE TRIGGER orders_insert_version_trigger AFTER INSERT ON orders
+BEGIN
+    UPDATE restaurant_tables SET orders_version = orders_version + 1 WHERE table_id = NEW.table_id;
+END;
+
+CREATE TRIGGER orders_update_version_trigger AFTER UPDATE ON orders
+BEGIN
+    UPDATE restaurant_tables SET orders_version = orders_version + 1 WHERE table_id IN (OLD.table_id, NEW.table_id);
+END;
+
+CREATE TRIGGER orders_delete_version_trigger AFTER DELETE ON orders
+BEGIN
+    UPDATE restaurant_tables SET orders_version = orders_version + 1 WHERE table_id = OLD.table_id;
+END;
+
+CREATE TRIGGER table_sessions_version_trigger AFTER UPDATE ON table_sessions
+BEGIN
+    UPDATE restaurant_tables SET orders_version = orders_version + 1 WHERE table_id = OLD.table_id;
+END;
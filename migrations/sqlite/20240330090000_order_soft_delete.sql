@@ -0,0 +1,5 @@
+-- soft deletion of orders (sqlite version of ../20240330090000_order_soft_delete.sql)
+
+ALTER TABLE orders ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE orders ADD COLUMN deleted_by VARCHAR(64) CHECK(length(deleted_by) <= 64);
+ALTER TABLE orders ADD COLUMN reason TEXT;
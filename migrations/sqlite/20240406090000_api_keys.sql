@@ -0,0 +1,9 @@
+-- API keys of clients (sqlite version of ../20240406090000_api_keys.sql)
+
+CREATE TABLE api_keys(
+    api_key_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(64) NOT NULL CHECK(length(name) <= 64),
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    creation_time TIMESTAMPTZ NOT NULL,
+    revocation_time TIMESTAMPTZ
+);
@@ -0,0 +1,3 @@
+-- staff roles of API keys (sqlite version of ../20240413090000_api_key_roles.sql)
+
+ALTER TABLE api_keys ADD COLUMN role TEXT NOT NULL DEFAULT 'waiter' CHECK(role IN ('waiter', 'kitchen', 'cashier', 'manager', 'admin'));
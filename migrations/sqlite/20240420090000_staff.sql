@@ -0,0 +1,13 @@
+-- staff accounts and order creators (sqlite version of ../20240420090000_staff.sql)
+
+CREATE TABLE staff(
+    staff_id VARCHAR(64) PRIMARY KEY CHECK(length(staff_id) <= 64),
+    name VARCHAR(64) NOT NULL CHECK(length(name) <= 64),
+    role TEXT NOT NULL CHECK(role IN ('waiter', 'kitchen', 'cashier', 'manager', 'admin')),
+    pin_hash CHAR(64) NOT NULL,
+    pin_salt CHAR(32) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    creation_time TIMESTAMPTZ NOT NULL
+);
+
+ALTER TABLE orders ADD COLUMN created_by VARCHAR(64);
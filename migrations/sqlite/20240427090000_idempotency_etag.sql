@@ -0,0 +1,2 @@
+-- ETag of the stored response (sqlite version of ../20240427090000_idempotency_etag.sql)
+ALTER TABLE idempotency_keys ADD COLUMN etag TEXT;
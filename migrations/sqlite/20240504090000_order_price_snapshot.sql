@@ -0,0 +1,4 @@
+-- menu item and price snapshot of orders (sqlite version of ../20240504090000_order_price_snapshot.sql)
+
+ALTER TABLE orders ADD COLUMN menu_item_id INTEGER;
+ALTER TABLE orders ADD COLUMN unit_price INTEGER;
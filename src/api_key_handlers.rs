@@ -0,0 +1,61 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use chrono::Utc;
+
+use crate::auth::{generate_api_key, hash_api_key};
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::model::{ApiKey, ApiKeyRequest, ApiKeysResponse};
+use crate::auth::{RequireRole, AdminAccess};
+use crate::context::ApiContext;
+
+
+/// maximum length of API key name (same as the column)
+const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+
+/// handler function for getting all API keys (including revoked keys). Keys themselves are never returned
+pub async fn handle_get_api_keys<D: StorageDAO>(State(context): State<ApiContext<D>>, _role: RequireRole<AdminAccess>) -> Response{
+    tracing::info!("[api key get all]");
+
+    context.dbo.get_api_keys()
+        .await
+        .map( |api_keys: Vec<ApiKey>| ApiKeysResponse::new(200, api_keys).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for issuing a new API key of staff role. The key is returned only in this response (only its hash is stored)
+pub async fn handle_issue_api_key<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<AdminAccess>,
+    WithRejection(Json(request), _): WithRejection<Json<ApiKeyRequest>, ApiError>) -> Response{
+
+    tracing::info!("[api key issue] name = {}, role = {}", request.name, request.role);
+
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LENGTH{
+        return ApiError::BadRequest(format!("API key name must be 1 to {MAX_API_KEY_NAME_LENGTH} characters")).into_response();
+    }
+
+    let key = generate_api_key();
+    context.dbo.add_api_key(name, request.role, &hash_api_key(&key), Utc::now())
+        .await
+        .map( |api_key: ApiKey| ApiKeysResponse::issued(api_key, key).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for revoking a specific API key. It returns the revoked key
+pub async fn handle_revoke_api_key<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<AdminAccess>,
+    WithRejection(Path(api_key_id), _): WithRejection<Path<i32>, ApiError>) -> Response{
+
+    tracing::info!("[api key revoke] api_key_id = {api_key_id}");
+
+    context.dbo.revoke_api_key(api_key_id, Utc::now())
+        .await
+        .map( |api_key: ApiKey| ApiKeysResponse::new(200, vec![api_key]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
@@ -0,0 +1,333 @@
+use std::marker::PhantomData;
+
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::{header, HeaderMap};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use pbkdf2::pbkdf2_hmac;
+
+use crate::context::ApiContext;
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::jwt::TokenType;
+use crate::model::Role;
+
+
+/// request header carrying API key of client (issued by admin, or ADMIN_API_KEY itself)
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// number of random bytes of issued key (the key is hex encoded, so it has twice as many characters)
+const API_KEY_BYTES: usize = 32;
+
+/// number of random bytes of PIN salt
+const PIN_SALT_BYTES: usize = 16;
+
+/// PBKDF2 rounds of PIN hash (PINs are short, so hashing is made slow)
+const PIN_HASH_ROUNDS: u32 = 100_000;
+
+
+/// helper function to hash API key (SHA-256, hex encoded). Only the hash is stored, so a leaked database doesn't leak usable keys
+pub fn hash_api_key(api_key: &str) -> String{
+    hex::encode(Sha256::digest(api_key.as_bytes()))
+}
+
+
+/// helper function to generate a new random API key
+pub fn generate_api_key() -> String{
+    let mut bytes = [0u8; API_KEY_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+
+/// helper function to generate random salt (hex) of PIN hash
+pub fn generate_pin_salt() -> String{
+    let mut bytes = [0u8; PIN_SALT_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+
+/// helper function to hash staff PIN with salt (PBKDF2-HMAC-SHA256, hex encoded)
+pub fn hash_pin(pin: &str, pin_salt: &str) -> String{
+    let mut hash = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(pin.as_bytes(), pin_salt.as_bytes(), PIN_HASH_ROUNDS, &mut hash);
+    hex::encode(hash)
+}
+
+
+/// helper function to read bearer token from `Authorization` header (None if the header is not sent or is not bearer)
+fn parse_bearer_token(headers: &HeaderMap) -> Option<&str>{
+    headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+
+/// helper function to read API key from `X-API-Key` header. It returns ApiError::Unauthorized if the header is missing or empty
+fn parse_api_key(headers: &HeaderMap) -> Result<&str, ApiError>{
+    match headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok()).map(str::trim){
+        Some(api_key) if !api_key.is_empty() => Ok(api_key),
+        _ => Err(ApiError::Unauthorized)
+    }
+}
+
+
+/// helper function to check whether API key is the configured admin key (hashes are compared, so that lengths don't leak)
+fn is_admin_api_key(admin_api_key: &str, api_key: &str) -> bool{
+    hash_api_key(admin_api_key) == hash_api_key(api_key)
+}
+
+
+/// Credential of authenticated request. It is attached to request extensions by require_credentials middleware,
+/// so that RequireRole extractor can check the role later
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credentials{
+    /// role of staff using the key or token
+    pub role: Role,
+
+    /// id of issued key (None for ADMIN_API_KEY and staff tokens)
+    pub api_key_id: Option<i32>,
+
+    /// staff logged in with the token (None for API keys)
+    pub staff_id: Option<String>
+}
+
+
+/// helper function to authenticate request. Bearer token (staff login) is checked first, then API key
+/// (admin key is accepted as well as issued, not revoked keys)
+async fn authenticate<D: StorageDAO>(context: &ApiContext<D>, admin_api_key: &str, headers: &HeaderMap) -> Result<Credentials, ApiError>{
+    if let Some(token) = parse_bearer_token(headers){
+        let jwt = context.jwt.as_ref().ok_or(ApiError::Unauthorized)?;
+        return jwt.verify_token(token, TokenType::Access)
+            .map(|claims| Credentials{ role: claims.role, api_key_id: None, staff_id: Some(claims.sub) });
+    }
+
+    let api_key = parse_api_key(headers)?;
+    if is_admin_api_key(admin_api_key, api_key){
+        return Ok(Credentials{ role: Role::Admin, api_key_id: None, staff_id: None });
+    }
+
+    context.dbo.authenticate_api_key(&hash_api_key(api_key)).await
+        .map(|api_key| Credentials{ role: api_key.role, api_key_id: Some(api_key.api_key_id), staff_id: None })
+}
+
+
+/// middleware function to reject requests without valid API key or staff token (401). Credentials are attached to the request.
+/// Authentication is disabled if ADMIN_API_KEY is not configured, since no key or staff could be registered then
+/// (server refuses to start so unless AUTH_DISABLED is set, see AppConfig::validate)
+pub async fn require_credentials<D: StorageDAO>(State(context): State<ApiContext<D>>, mut request: Request, next: Next) -> Response{
+    let Some(admin_api_key) = context.config.get_admin_api_key() else{
+        return next.run(request).await;
+    };
+
+    match authenticate(&context, admin_api_key, request.headers()).await{
+        Ok(credentials) => {
+            tracing::debug!("[auth] authenticated role = {}, api_key_id = {:?}, staff_id = {:?}", credentials.role, credentials.api_key_id, credentials.staff_id);
+            request.extensions_mut().insert(credentials);
+            next.run(request).await
+        },
+        Err(err) => {
+            tracing::error!("[auth] rejected request to {}: {err}", request.uri().path());
+            err.into_response()
+        }
+    }
+}
+
+
+/// trait of role policies which handlers declare with RequireRole extractor (e.g., `RequireRole<WaiterAccess>`)
+pub trait RolePolicy: Send + Sync + 'static{
+    /// roles allowed by the policy
+    const ROLES: &'static [Role];
+
+    /// whether the policy rejects requests while authentication is disabled (no ADMIN_API_KEY)
+    const REQUIRES_CREDENTIALS: bool = false;
+}
+
+
+/// any staff (read only endpoints, streams and devices)
+pub struct StaffAccess;
+
+/// waiters taking, changing and voiding orders and seating parties (and managers)
+pub struct WaiterAccess;
+
+/// kitchen cooking orders and waiters serving them, i.e., order status changes (and managers)
+pub struct KitchenAccess;
+
+/// cashiers settling bills (and managers)
+pub struct CashierAccess;
+
+/// managers editing menu and table registry
+pub struct ManagerAccess;
+
+/// admin managing API keys. Admin endpoints are closed while authentication is disabled
+pub struct AdminAccess;
+
+
+impl RolePolicy for StaffAccess{
+    const ROLES: &'static [Role] = &[Role::Waiter, Role::Kitchen, Role::Cashier, Role::Manager, Role::Admin];
+}
+
+impl RolePolicy for WaiterAccess{
+    const ROLES: &'static [Role] = &[Role::Waiter, Role::Manager, Role::Admin];
+}
+
+impl RolePolicy for KitchenAccess{
+    const ROLES: &'static [Role] = &[Role::Kitchen, Role::Waiter, Role::Manager, Role::Admin];
+}
+
+impl RolePolicy for CashierAccess{
+    const ROLES: &'static [Role] = &[Role::Cashier, Role::Manager, Role::Admin];
+}
+
+impl RolePolicy for ManagerAccess{
+    const ROLES: &'static [Role] = &[Role::Manager, Role::Admin];
+}
+
+impl RolePolicy for AdminAccess{
+    const ROLES: &'static [Role] = &[Role::Admin];
+    const REQUIRES_CREDENTIALS: bool = true;
+}
+
+
+/// extractor to authorize request by role of its credential. It is rejected with ApiError::Forbidden (403) if the role isn't allowed by policy P.
+/// While authentication is disabled, requests have no credential and are allowed (except policies requiring credentials)
+pub struct RequireRole<P: RolePolicy>{
+    /// credential of request (None while authentication is disabled)
+    pub credentials: Option<Credentials>,
+    policy: PhantomData<P>
+}
+
+
+impl<P: RolePolicy> RequireRole<P>{
+    /// function to get role of request (None while authentication is disabled)
+    pub fn role(&self) -> Option<Role>{
+        self.credentials.as_ref().map(|credentials| credentials.role)
+    }
+
+    /// function to get staff logged in with the token (None for API keys or while authentication is disabled)
+    pub fn staff_id(&self) -> Option<&str>{
+        self.credentials.as_ref().and_then(|credentials| credentials.staff_id.as_deref())
+    }
+}
+
+
+/// helper function to check role of credential against policy
+fn authorize<P: RolePolicy>(credentials: Option<Credentials>, authentication_enabled: bool) -> Result<RequireRole<P>, ApiError>{
+    match credentials{
+        Some(credentials) if P::ROLES.contains(&credentials.role) => Ok(RequireRole{ credentials: Some(credentials), policy: PhantomData }),
+        Some(_) => Err(ApiError::Forbidden),
+        None if !authentication_enabled && !P::REQUIRES_CREDENTIALS => Ok(RequireRole{ credentials: None, policy: PhantomData }),
+        None => Err(ApiError::Unauthorized)
+    }
+}
+
+
+#[async_trait]
+impl<D: StorageDAO, P: RolePolicy> FromRequestParts<ApiContext<D>> for RequireRole<P>{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, context: &ApiContext<D>) -> Result<Self, Self::Rejection> {
+        let credentials = parts.extensions.get::<Credentials>().cloned();
+        let role = credentials.as_ref().map(|credentials| credentials.role);
+        let authorized = authorize(credentials, context.config.get_admin_api_key().is_some());
+        if let Err(err) = &authorized{
+            tracing::error!("[auth] {} {} is not authorized for role {role:?}: {err}", parts.method, parts.uri.path());
+        }
+        authorized
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use axum::http::{HeaderMap, HeaderValue};
+
+    use super::*;
+
+
+    #[test]
+    fn test_hash_api_key(){
+        // sha256("abc")
+        assert_eq!(hash_api_key("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hash_api_key("abc").len(), 64);
+    }
+
+
+    #[test]
+    fn test_generate_api_key(){
+        let first = generate_api_key();
+        let second = generate_api_key();
+
+        assert_eq!(first.len(), 64);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first, second);
+    }
+
+
+    #[test]
+    fn test_parse_api_key(){
+        let mut headers = HeaderMap::new();
+        assert!(matches!(parse_api_key(&headers), Err(ApiError::Unauthorized)));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("  "));
+        assert!(matches!(parse_api_key(&headers), Err(ApiError::Unauthorized)));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("secret"));
+        assert_eq!(parse_api_key(&headers).unwrap(), "secret");
+
+        assert!(is_admin_api_key("secret", "secret"));
+        assert!(!is_admin_api_key("secret", "secret2"));
+    }
+
+
+    #[test]
+    fn test_authorize(){
+        let credentials = |role| Some(Credentials{ role, api_key_id: Some(1), staff_id: None });
+
+        assert!(authorize::<StaffAccess>(credentials(Role::Kitchen), true).is_ok());
+        assert!(matches!(authorize::<WaiterAccess>(credentials(Role::Kitchen), true), Err(ApiError::Forbidden)));
+        assert!(matches!(authorize::<KitchenAccess>(credentials(Role::Cashier), true), Err(ApiError::Forbidden)));
+        assert!(matches!(authorize::<ManagerAccess>(credentials(Role::Waiter), true), Err(ApiError::Forbidden)));
+        assert_eq!(authorize::<CashierAccess>(credentials(Role::Cashier), true).unwrap().role(), Some(Role::Cashier));
+        assert!(authorize::<AdminAccess>(credentials(Role::Admin), true).is_ok());
+
+        // without credential, only allowed while authentication is disabled (except admin endpoints)
+        assert!(matches!(authorize::<StaffAccess>(None, true), Err(ApiError::Unauthorized)));
+        assert_eq!(authorize::<ManagerAccess>(None, false).unwrap().role(), None);
+        assert!(matches!(authorize::<AdminAccess>(None, false), Err(ApiError::Unauthorized)));
+    }
+
+
+    #[test]
+    fn test_hash_pin(){
+        let salt = generate_pin_salt();
+        assert_eq!(salt.len(), 32);
+        assert_ne!(salt, generate_pin_salt());
+
+        let hash = hash_pin("1234", &salt);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_pin("1234", &salt));
+        assert_ne!(hash, hash_pin("1235", &salt));
+        assert_ne!(hash, hash_pin("1234", &generate_pin_salt()));
+    }
+
+
+    #[test]
+    fn test_parse_bearer_token(){
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(parse_bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer abc.def.ghi"));
+        assert_eq!(parse_bearer_token(&headers), Some("abc.def.ghi"));
+    }
+}
@@ -0,0 +1,352 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use chrono::Utc;
+
+use crate::dao::StorageDAO;
+use crate::dao::bill_dao::build_bill;
+use crate::error::ApiError;
+use crate::model::{Bill, BillLineItem, BillResponse, SplitBillRequest, SplitBillResponse, SubBill};
+use crate::auth::{RequireRole, CashierAccess};
+use crate::context::ApiContext;
+use crate::handlers::check_table;
+use crate::events::OrderEventType;
+
+
+/// helper function to split amount evenly to n shares. Remaining units (amount % n) are given one by one
+/// starting from share `offset` (wrapping around), so that remainders of several amounts are spread over different shares.
+/// It returns shares and the offset for the next amount
+fn allocate_evenly(amount: i32, n: usize, offset: usize) -> (Vec<i32>, usize){
+    let base = amount / n as i32;
+    let remainder = (amount % n as i32) as usize;
+
+    let shares = (0..n)
+        .map(|index| base + i32::from((index + n - offset) % n < remainder))
+        .collect();
+    (shares, (offset + remainder) % n)
+}
+
+
+/// helper function to split amount proportionally to weights (largest remainder method), so that shares always sum to amount.
+/// Remaining units go to shares with the largest fractional part (earlier share first). If all weights are 0, amount is split evenly
+fn allocate_by_weights(amount: i32, weights: &[i64]) -> Vec<i32>{
+    let total_weight: i64 = weights.iter().sum();
+    if total_weight == 0{
+        return allocate_evenly(amount, weights.len(), 0).0;
+    }
+
+    let amount = i64::from(amount);
+    let mut shares: Vec<i64> = weights.iter().map(|weight| amount * weight / total_weight).collect();
+    let mut leftover = amount - shares.iter().sum::<i64>();
+
+    // stable sort keeps the original order for equal remainders
+    let mut indexes: Vec<usize> = (0..weights.len()).collect();
+    indexes.sort_by_key(|index| std::cmp::Reverse(amount * weights[*index] % total_weight));
+    for index in indexes{
+        if leftover == 0{
+            break;
+        }
+        shares[index] += 1;
+        leftover -= 1;
+    }
+
+    // every share is at most amount, so it always fits in i32
+    shares.into_iter().map(|share| share as i32).collect()
+}
+
+
+/// helper function to combine allocated shares of each bill component into sub-bills
+fn build_sub_bills(line_items: Vec<Vec<BillLineItem>>, subtotals: Vec<i32>, service_charges: Vec<i32>, taxes: Vec<i32>) -> Vec<SubBill>{
+    line_items.into_iter()
+        .zip(subtotals)
+        .zip(service_charges)
+        .zip(taxes)
+        .map(|(((line_items, subtotal), service_charge), tax)| SubBill{
+            line_items,
+            subtotal,
+            service_charge,
+            tax,
+            total: subtotal + service_charge + tax
+        })
+        .collect()
+}
+
+
+/// function to split bill evenly by number of guests. Each bill component is split separately and remainders are
+/// spread over consecutive guests, so guest totals differ by at most one unit and sum exactly to bill total
+fn split_bill_evenly(bill: &Bill, guests: u16) -> Result<Vec<SubBill>, ApiError>{
+    if guests == 0{
+        return Err(ApiError::BadRequest("number of guests must be at least 1".to_string()));
+    }
+
+    let n = usize::from(guests);
+    let (subtotals, offset) = allocate_evenly(bill.subtotal, n, 0);
+    let (service_charges, offset) = allocate_evenly(bill.service_charge, n, offset);
+    let (taxes, _) = allocate_evenly(bill.tax, n, offset);
+
+    Ok(build_sub_bills(vec![Vec::new(); n], subtotals, service_charges, taxes))
+}
+
+
+/// function to split bill by assigning order ids to sub-bills. Every billed order must be assigned to exactly one group.
+/// Service charge and tax are allocated proportionally to group subtotals, so sub-bill totals sum exactly to bill total
+fn split_bill_by_items(bill: &Bill, groups: &[Vec<i32>]) -> Result<Vec<SubBill>, ApiError>{
+    if groups.is_empty() || groups.iter().any(|group| group.is_empty()){
+        return Err(ApiError::BadRequest("each sub-bill must contain at least one order".to_string()));
+    }
+
+    let mut assigned: Vec<i32> = Vec::new();
+    let mut line_items: Vec<Vec<BillLineItem>> = Vec::new();
+    for group in groups{
+        let mut group_items = Vec::new();
+        for order_id in group{
+            if assigned.contains(order_id){
+                return Err(ApiError::BadRequest(format!("order {order_id} is assigned to more than one sub-bill")));
+            }
+
+            let line = bill.line_items.iter()
+                .find(|line| line.order_id == *order_id)
+                .ok_or(ApiError::BadRequest(format!("order {order_id} is not a billed order of table {}", bill.table_id)))?;
+
+            assigned.push(*order_id);
+            group_items.push(line.clone());
+        }
+        line_items.push(group_items);
+    }
+
+    if assigned.len() != bill.line_items.len(){
+        let unassigned: Vec<String> = bill.line_items.iter()
+            .filter(|line| !assigned.contains(&line.order_id))
+            .map(|line| line.order_id.to_string())
+            .collect();
+        return Err(ApiError::BadRequest(format!("orders are not assigned to any sub-bill: {}", unassigned.join(", "))));
+    }
+
+    let subtotals: Vec<i32> = line_items.iter()
+        .map(|group| group.iter().map(|line| line.amount).sum())
+        .collect();
+    let weights: Vec<i64> = subtotals.iter().map(|subtotal| i64::from(*subtotal)).collect();
+    let service_charges = allocate_by_weights(bill.service_charge, &weights);
+    let taxes = allocate_by_weights(bill.tax, &weights);
+
+    Ok(build_sub_bills(line_items, subtotals, service_charges, taxes))
+}
+
+
+/// function to split bill according to the request
+fn split_bill(bill: &Bill, request: &SplitBillRequest) -> Result<Vec<SubBill>, ApiError>{
+    match request{
+        SplitBillRequest::Even{ guests } => split_bill_evenly(bill, *guests),
+        SplitBillRequest::Items{ groups } => split_bill_by_items(bill, groups)
+    }
+}
+
+
+/// helper function to compute the current table bill (same as checkout but nothing is stored) and split it
+async fn compute_split_bill<D: StorageDAO>(context: &ApiContext<D>, table_id: i16, request: &SplitBillRequest) -> Result<SplitBillResponse, ApiError>{
+    let orders = context.dbo.get_table_orders(table_id, None).await?;
+    let names: Vec<String> = orders.iter().map(|order| order.item_name.clone()).collect();
+    let menu_items = context.dbo.get_menu_items_by_names(&names).await?;
+
+    let bill = build_bill(table_id, &orders, &menu_items, context.config.get_bill_charges(), Utc::now())?;
+    let sub_bills = split_bill(&bill, request)?;
+    Ok(SplitBillResponse::new(200, table_id, bill.total, sub_bills))
+}
+
+
+/// handler function for closing out a table. It computes the bill from the table's orders and their prices (with configured
+/// service charge and tax), records the bill, and clears the table. Then returns the created bill (BillResponse)
+pub async fn handle_checkout_table<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<CashierAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[checkout] table id from path = {table_id}");
+
+    check_table!(context, table_id);
+
+    let bill = match context.dbo.checkout_table(table_id, context.config.get_bill_charges(), Utc::now()).await{ // compute bill and clear table in one transaction
+        Ok(bill) => bill,
+        Err(err) => return err.into_response()
+    };
+
+    // notify streams with orders left on the table (none unless orders were added after the checkout)
+    match context.dbo.get_table_orders(table_id, None).await{
+        Ok(orders) => { context.events.publish(OrderEventType::TableCheckedOut, table_id, None, orders); },
+        Err(err) => tracing::error!("[checkout] failed to get orders of checked out table: {err}")
+    }
+
+    BillResponse::new(200, bill).into_response() // generate BillResponse from bill
+}
+
+
+/// handler function for splitting the current bill of a table (evenly or by orders). Orders are not changed,
+/// so the split can be previewed any number of times before checkout. Then returns the sub-bills (SplitBillResponse)
+pub async fn handle_split_bill<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<CashierAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    WithRejection(Json(request), _): WithRejection<Json<SplitBillRequest>, ApiError>) -> Response{
+
+    tracing::info!("[split bill] table id from path = {table_id}, request = {request:?}");
+
+    check_table!(context, table_id);
+
+    compute_split_bill(&context, table_id, &request)
+        .await
+        .map(IntoResponse::into_response)
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for getting a specific bill
+pub async fn handle_get_bill<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<CashierAccess>,
+    WithRejection(Path(bill_id), _): WithRejection<Path<i32>, ApiError>) -> Response{
+
+    tracing::info!("[get bill] bill_id = {bill_id}");
+
+    if bill_id < 1{
+        tracing::error!("out of range bill id={bill_id}");
+        return ApiError::BillNotFound.into_response();
+    }
+
+    context.dbo.get_bill(bill_id)
+        .await
+        .map( |bill: Bill| BillResponse::new(200, bill).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+    fn line(order_id: i32, amount: i32) -> BillLineItem{
+        BillLineItem{
+            order_id,
+            item_name: format!("item {order_id}"),
+            quantity: 1,
+            unit_price: amount,
+            amount
+        }
+    }
+
+    fn bill(line_items: Vec<BillLineItem>, service_charge: i32, tax: i32) -> Bill{
+        let subtotal = line_items.iter().map(|line| line.amount).sum();
+        Bill{
+            bill_id: -1,
+            table_id: 1,
+            line_items,
+            subtotal,
+            service_charge,
+            tax,
+            total: subtotal + service_charge + tax,
+            creation_time: Utc::now()
+        }
+    }
+
+    fn sum_totals(sub_bills: &[SubBill]) -> i32{
+        sub_bills.iter().map(|sub_bill| sub_bill.total).sum()
+    }
+
+
+    #[test]
+    fn test_allocate_evenly(){
+        assert_eq!(allocate_evenly(100, 3, 0), (vec![34, 33, 33], 1));
+        assert_eq!(allocate_evenly(100, 3, 1), (vec![33, 34, 33], 2));
+        assert_eq!(allocate_evenly(101, 3, 2), (vec![34, 33, 34], 1));
+        assert_eq!(allocate_evenly(99, 3, 2), (vec![33, 33, 33], 2));
+        assert_eq!(allocate_evenly(2, 5, 4), (vec![1, 0, 0, 0, 1], 1));
+    }
+
+
+    #[test]
+    fn test_allocate_by_weights(){
+        assert_eq!(allocate_by_weights(100, &[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(allocate_by_weights(10, &[1, 2]), vec![3, 7]);
+        assert_eq!(allocate_by_weights(1, &[1, 1, 1]), vec![1, 0, 0]);
+        assert_eq!(allocate_by_weights(5, &[0, 0]), vec![3, 2]);
+        assert_eq!(allocate_by_weights(0, &[3, 5]), vec![0, 0]);
+    }
+
+
+    #[test]
+    fn test_split_bill_evenly_with_remainders(){
+        // 100.01 subtotal, 10.00 service charge, 7.70 tax split by 3 guests
+        let table_bill = bill(vec![line(1, 10001)], 1000, 770);
+        let sub_bills = split_bill(&table_bill, &SplitBillRequest::Even{ guests: 3 }).unwrap();
+
+        assert_eq!(sub_bills.len(), 3);
+        assert_eq!(sum_totals(&sub_bills), table_bill.total);
+        assert_eq!(sub_bills.iter().map(|sub_bill| sub_bill.subtotal).sum::<i32>(), table_bill.subtotal);
+        assert_eq!(sub_bills.iter().map(|sub_bill| sub_bill.service_charge).sum::<i32>(), table_bill.service_charge);
+        assert_eq!(sub_bills.iter().map(|sub_bill| sub_bill.tax).sum::<i32>(), table_bill.tax);
+
+        // guests pay at most one unit different from each other
+        let max_total = sub_bills.iter().map(|sub_bill| sub_bill.total).max().unwrap();
+        let min_total = sub_bills.iter().map(|sub_bill| sub_bill.total).min().unwrap();
+        assert!(max_total - min_total <= 1);
+    }
+
+
+    #[test]
+    fn test_split_small_bill_evenly_by_many_guests(){
+        let table_bill = bill(vec![line(1, 5)], 1, 1);
+        let sub_bills = split_bill(&table_bill, &SplitBillRequest::Even{ guests: 10 }).unwrap();
+
+        assert_eq!(sub_bills.len(), 10);
+        assert_eq!(sum_totals(&sub_bills), 7);
+        assert!(sub_bills.iter().all(|sub_bill| sub_bill.total <= 1));
+
+        assert!(matches!(split_bill(&table_bill, &SplitBillRequest::Even{ guests: 0 }), Err(ApiError::BadRequest(_))));
+    }
+
+
+    #[test]
+    fn test_split_bill_by_items_with_remainders(){
+        // service charge and tax can't be divided exactly by 1:1:1 subtotals
+        let table_bill = bill(vec![line(1, 3333), line(2, 3333), line(3, 3334)], 1001, 701);
+        let request = SplitBillRequest::Items{ groups: vec![vec![2], vec![1], vec![3]] };
+        let sub_bills = split_bill(&table_bill, &request).unwrap();
+
+        assert_eq!(sub_bills.len(), 3);
+        assert_eq!(sub_bills[0].line_items, vec![line(2, 3333)]);
+        assert_eq!(sub_bills[0].subtotal, 3333);
+        assert_eq!(sub_bills[2].subtotal, 3334);
+        assert_eq!(sub_bills.iter().map(|sub_bill| sub_bill.service_charge).sum::<i32>(), 1001);
+        assert_eq!(sub_bills.iter().map(|sub_bill| sub_bill.tax).sum::<i32>(), 701);
+        assert_eq!(sum_totals(&sub_bills), table_bill.total);
+    }
+
+
+    #[test]
+    fn test_split_bill_by_items_with_uneven_groups(){
+        let table_bill = bill(vec![line(1, 999), line(2, 1), line(3, 2500)], 350, 269);
+        let request = SplitBillRequest::Items{ groups: vec![vec![1, 2], vec![3]] };
+        let sub_bills = split_bill(&table_bill, &request).unwrap();
+
+        assert_eq!(sub_bills[0].subtotal, 1000);
+        assert_eq!(sub_bills[1].subtotal, 2500);
+        assert_eq!(sub_bills[0].service_charge, 100);
+        assert_eq!(sub_bills[1].service_charge, 250);
+        // 269 * 1000 / 3500 = 76.86, 269 * 2500 / 3500 = 192.14 -> the larger remainder gets the leftover unit
+        assert_eq!(sub_bills[0].tax, 77);
+        assert_eq!(sub_bills[1].tax, 192);
+        assert_eq!(sum_totals(&sub_bills), table_bill.total);
+    }
+
+
+    #[test]
+    fn test_split_bill_by_invalid_items(){
+        let table_bill = bill(vec![line(1, 100), line(2, 200)], 0, 0);
+        let split = |groups: Vec<Vec<i32>>| split_bill(&table_bill, &SplitBillRequest::Items{ groups });
+
+        assert!(matches!(split(vec![]), Err(ApiError::BadRequest(_))));
+        assert!(matches!(split(vec![vec![1, 2], vec![]]), Err(ApiError::BadRequest(_))));
+        assert!(matches!(split(vec![vec![1], vec![1, 2]]), Err(ApiError::BadRequest(_))));
+        assert!(matches!(split(vec![vec![1], vec![3]]), Err(ApiError::BadRequest(_))));
+        assert!(matches!(split(vec![vec![1]]), Err(ApiError::BadRequest(cause)) if cause.contains('2')));
+        assert!(split(vec![vec![1], vec![2]]).is_ok());
+    }
+}
@@ -1,49 +1,109 @@
 
 use std::sync::Arc;
 use sqlx::{self, postgres::PgPoolOptions};
-use crate::{dao::pg_order_dao::PgTableOrderDAO, config::AppConfig, error::ApiError};
+use crate::{dao::{StorageDAO, pg_order_dao::PgTableOrderDAO, mem_order_dao::MemTableOrderDAO}, config::AppConfig, error::ApiError};
+use crate::estimation::{EstimationStrategy, new_estimation_strategy};
+use crate::events::OrderEventBus;
+use crate::jwt::JwtKeys;
+use crate::rate_limit::RateLimits;
+#[cfg(feature = "sqlite")]
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
 
 
 /// struct to store configuration as shared state in axum.
+/// It is generic over DAO implementation (postgres by default) so that the same router can run on any storage
 #[derive(Clone)]
-pub struct ApiContext {
+pub struct ApiContext<D = PgTableOrderDAO> {
     /// variable to store config parameters from environments (using hashmap for future proof)
     pub config: Arc<AppConfig>,
 
-    /// official async support in rust 1.75 doesn't support dyn trait object yet. So it is fixed to postgres impl for now 
-    pub dbo: PgTableOrderDAO  
+    /// DAO implementation used by handlers
+    pub dbo: D,
+
+    /// strategy to estimate preparation time of new orders (selected from config)
+    pub estimator: Arc<dyn EstimationStrategy>,
+
+    /// bus of order change events pushed to streaming clients
+    pub events: Arc<OrderEventBus>,
+
+    /// keys of staff login tokens (None if JWT login is not configured)
+    pub jwt: Option<Arc<JwtKeys>>,
+
+    /// request rate limiters of each route group (shared by clones of context)
+    pub rate_limits: Arc<RateLimits>
 }
 
 
 
-impl ApiContext{
+impl<D: StorageDAO> ApiContext<D>{
+
+    /// Helper function to generate API context object from any DAO implementation
+    pub fn with_dao(config: AppConfig, dbo: D) -> Self{
+        // server refuses to start with misconfigured keys (AppConfig::validate), so this only happens with unvalidated config
+        let jwt = JwtKeys::from_config(&config)
+            .unwrap_or_else(|err| {
+                tracing::error!("JWT login is disabled: {err}");
+                None
+            });
 
-    /// Helper function to generate API context object
-    pub fn new(db: sqlx::PgPool) -> Self{
-        let config = AppConfig::new_from_env();
         Self{
+            estimator: new_estimation_strategy(&config),
+            jwt: jwt.map(Arc::new),
+            rate_limits: Arc::new(RateLimits::from_config(&config)),
             config: Arc::new(config),
-            dbo: PgTableOrderDAO::new(db)
+            dbo,
+            events: Arc::new(OrderEventBus::default())
         }
     }
 
+    /// Helper function to replace estimation strategy (e.g., fixed estimation in tests)
+    pub fn with_estimator(mut self, estimator: Arc<dyn EstimationStrategy>) -> Self{
+        self.estimator = estimator;
+        self
+    }
+}
+
+
+impl ApiContext<PgTableOrderDAO>{
+
+    /// Helper function to generate API context object. It panics if env parameters are invalid (see AppConfig::new_from_env)
+    pub fn new(db: sqlx::PgPool) -> Self{
+        Self::with_dao(AppConfig::new_from_env().expect("invalid configuration"), PgTableOrderDAO::new(db))
+    }
+
 
-    /// Helper function to read env parameters and generate context
+    /// Helper function to read env parameters and generate context (connect to postgres)
     pub async fn new_from_env() -> Result<Self, ApiError>{
-        let config = AppConfig::new_from_env();
-    
-        PgPoolOptions::new()
-        .max_connections((&config).get_max_db_pool_size())
-        .connect(&config.get_db_url()).await
-        .map_err(|x| ApiError::DatabaseError(x))
-        .and_then(|pool|{
-            Ok(
-                Self{
-                    config: Arc::new(config),
-                    dbo: PgTableOrderDAO::new(pool)
-                }
-            )
-        })
-        
+        let config = AppConfig::new_from_env()?;
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.get_max_db_pool_size())
+            .connect(&config.get_db_url()).await
+            .map_err(ApiError::DatabaseError)?;
+
+        Ok(Self::with_dao(config, PgTableOrderDAO::new(pool)))
+    }
+}
+
+
+impl ApiContext<MemTableOrderDAO>{
+
+    /// Helper function to generate API context object with empty in-memory storage (no database required).
+    /// It panics if env parameters are invalid (see AppConfig::new_from_env)
+    pub fn new_in_memory() -> Self{
+        Self::with_dao(AppConfig::new_from_env().expect("invalid configuration"), MemTableOrderDAO::new())
+    }
+}
+
+
+#[cfg(feature = "sqlite")]
+impl ApiContext<SqliteTableOrderDAO>{
+
+    /// Helper function to read env parameters and generate context (open sqlite database file and apply migrations)
+    pub async fn new_sqlite_from_env() -> Result<Self, ApiError>{
+        let config = AppConfig::new_from_env()?;
+        let dbo = SqliteTableOrderDAO::connect(&config.get_db_url(), config.get_max_db_pool_size()).await?;
+
+        Ok(Self::with_dao(config, dbo))
     }
 }
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use trait_variant;
+use crate::model::{ApiKey, Role};
+use crate::error::ApiError;
+
+
+/// trait for API key access object. contains issue / get / authenticate / revoke key functions.
+/// Keys are passed as SHA-256 hashes (see auth::hash_api_key), so storage never sees the key itself.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (ApiKeyDAO)
+#[trait_variant::make(ApiKeyDAO: Send)]
+pub trait LocalApiKeyDAO: Clone + Sync + 'static{
+    /// function for storing a newly issued key of staff role. It returns the created record (with api_key_id)
+    async fn add_api_key(&self, name: &str, role: Role, key_hash: &str, creation_time: DateTime<Utc>) -> Result<ApiKey, ApiError>;
+
+    /// function to get all keys including revoked ones (ordered by api_key_id)
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, ApiError>;
+
+    /// function to find the valid key of a hash. It returns ApiError::Unauthorized if the key is unknown or revoked
+    async fn authenticate_api_key(&self, key_hash: &str) -> Result<ApiKey, ApiError>;
+
+    /// function to revoke specific key. Revoking a revoked key keeps the first revocation time.
+    /// It returns the revoked record, or ApiError::ApiKeyNotFound if the key doesn't exist
+    async fn revoke_api_key(&self, api_key_id: i32, revocation_time: DateTime<Utc>) -> Result<ApiKey, ApiError>;
+}
+
+
+/// utility function to map sqlx error of API key queries to ApiError
+pub(crate) fn map_api_key_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::ApiKeyNotFound,
+        _ => ApiError::DatabaseError(err),
+    }
+}
+
+
+/// utility function to reject unknown or revoked key (shared by DAO implementations)
+pub(crate) fn check_valid_api_key(api_key: Option<ApiKey>) -> Result<ApiKey, ApiError>{
+    match api_key{
+        Some(api_key) if api_key.revocation_time.is_none() => Ok(api_key),
+        _ => Err(ApiError::Unauthorized)
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use trait_variant;
+use crate::model::{Bill, BillCharges, BillLineItem, MenuItem, OrderItem, OrderStatus};
+use crate::error::ApiError;
+
+/// trait for bill access object. contains checkout / get bill functions.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (BillDAO)
+#[trait_variant::make(BillDAO: Send)]
+pub trait LocalBillDAO: Clone + Sync + 'static{
+    /// function to close out a table. It computes the bill from orders of the table's current seating session and their snapshotted prices,
+    /// stores the bill, removes the billed orders (soft deletion with BILLED_ORDER_REASON, rows are kept) and closes the session in one transaction
+    async fn checkout_table(&self, table_id: i16, charges: BillCharges, creation_time: DateTime<Utc>) -> Result<Bill, ApiError>;
+
+    /// function to get specific bill (including line items)
+    async fn get_bill(&self, bill_id: i32) -> Result<Bill, ApiError>;
+}
+
+
+/// reason code of orders removed by checkout (billed orders are soft deleted like voided orders)
+pub const BILLED_ORDER_REASON: &str = "billed";
+
+
+/// utility function to map sqlx error of bill queries to ApiError
+pub(crate) fn map_bill_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::BillNotFound,
+        _ => ApiError::DatabaseError(err),
+    }
+}
+
+
+/// utility function to compute charge of amount from rate in basis points (rounded half up)
+fn apply_rate(amount: i64, rate_bps: u16) -> i64{
+    (amount * i64::from(rate_bps) + 5_000) / 10_000
+}
+
+
+/// utility function to convert computed amount back to the storage type
+fn to_amount(amount: i64) -> Result<i32, ApiError>{
+    i32::try_from(amount)
+        .map_err(|_| ApiError::BadRequest("bill amount is too large".to_string()))
+}
+
+
+/// utility function to build a bill (bill_id = -1 until it is stored) from table's orders (shared by DAO implementations).
+/// Orders are priced from the price snapshotted when they were added. Orders without snapshot (added before it existed) are priced from the menu.
+/// Cancelled orders are not billed. It returns ApiError::BadRequest if table has no orders or price of any billed item is not available
+pub(crate) fn build_bill(table_id: i16, orders: &[OrderItem], menu_items: &[MenuItem], charges: BillCharges, creation_time: DateTime<Utc>) -> Result<Bill, ApiError>{
+    if orders.is_empty(){
+        return Err(ApiError::BadRequest(format!("table {table_id} has no orders to checkout")));
+    }
+
+    let mut line_items = Vec::new();
+    let mut unpriced_items: Vec<&str> = Vec::new();
+    for order in orders.iter().filter(|order| order.status != OrderStatus::Cancelled){
+        let unit_price = order.unit_price
+            .or_else(|| menu_items.iter().find(|item| item.name == order.item_name).map(|item| item.price));
+        match unit_price{
+            Some(unit_price) => line_items.push(BillLineItem{
+                order_id: order.order_id,
+                item_name: order.item_name.clone(),
+                quantity: order.quantity,
+                unit_price,
+                amount: to_amount(i64::from(unit_price) * i64::from(order.quantity))?
+            }),
+            None => unpriced_items.push(&order.item_name)
+        }
+    }
+
+    if !unpriced_items.is_empty(){
+        return Err(ApiError::BadRequest(format!("menu price is not available for: {}", unpriced_items.join(", "))));
+    }
+
+    let subtotal: i64 = line_items.iter().map(|line| i64::from(line.amount)).sum();
+    let service_charge = apply_rate(subtotal, charges.service_charge_bps);
+    let tax = apply_rate(subtotal + service_charge, charges.tax_bps);
+
+    Ok(Bill{
+        bill_id: -1,
+        table_id,
+        line_items,
+        subtotal: to_amount(subtotal)?,
+        service_charge: to_amount(service_charge)?,
+        tax: to_amount(tax)?,
+        total: to_amount(subtotal + service_charge + tax)?,
+        creation_time
+    })
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use super::*;
+
+    fn order(order_id: i32, item_name: &str, quantity: i32, status: OrderStatus) -> OrderItem{
+        let time = Utc::now();
+        OrderItem{
+            order_id,
+            table_id: 1,
+            item_name: item_name.to_string(),
+            quantity,
+            note: None,
+            creation_time: time,
+            estimated_arrival_time: time,
+            status,
+            acknowledged_by: None,
+            acknowledgement_time: None,
+            session_id: None,
+            deleted_at: None,
+            deleted_by: None,
+            reason: None,
+            created_by: None,
+            menu_item_id: None,
+            unit_price: None
+        }
+    }
+
+    fn menu_item(name: &str, price: i32) -> MenuItem{
+        MenuItem{
+            menu_item_id: 1,
+            name: name.to_string(),
+            category: "main".to_string(),
+            price,
+            prep_time_minutes: 10,
+            active: true
+        }
+    }
+
+
+    #[test]
+    fn test_build_bill(){
+        let orders = vec![
+            order(1, "Kapao", 2, OrderStatus::Served),
+            order(2, "Beer", 3, OrderStatus::Ordered),
+            order(3, "Kapao", 1, OrderStatus::Cancelled)
+        ];
+        let menu_items = vec![menu_item("Kapao", 12000), menu_item("Beer", 9950)];
+        let charges = BillCharges{ service_charge_bps: 1000, tax_bps: 700 };
+
+        let bill = build_bill(1, &orders, &menu_items, charges, Utc::now()).unwrap();
+        assert_eq!(bill.bill_id, -1);
+        assert_eq!(bill.table_id, 1);
+        assert_eq!(bill.line_items.len(), 2);
+        assert_eq!(bill.line_items[0].amount, 24000);
+        assert_eq!(bill.line_items[1].unit_price, 9950);
+        assert_eq!(bill.line_items[1].amount, 29850);
+
+        // service charge = 5385, tax = (53850 + 5385) * 7% = 4146.45 -> 4146
+        assert_eq!(bill.subtotal, 53850);
+        assert_eq!(bill.service_charge, 5385);
+        assert_eq!(bill.tax, 4146);
+        assert_eq!(bill.total, 63381);
+    }
+
+
+    #[test]
+    fn test_build_bill_without_charges(){
+        let orders = vec![order(1, "Kapao", 1, OrderStatus::Ordered)];
+        let bill = build_bill(1, &orders, &[menu_item("Kapao", 12000)], BillCharges::default(), Utc::now()).unwrap();
+
+        assert_eq!(bill.subtotal, 12000);
+        assert_eq!(bill.service_charge, 0);
+        assert_eq!(bill.tax, 0);
+        assert_eq!(bill.total, 12000);
+    }
+
+
+    #[test]
+    fn test_build_bill_with_snapshot_price(){
+        // snapshot is used even if the menu price changed or the item was removed from the menu
+        let orders = vec![
+            OrderItem{ unit_price: Some(10000), ..order(1, "Kapao", 2, OrderStatus::Ordered) },
+            OrderItem{ unit_price: Some(5000), ..order(2, "Burger", 1, OrderStatus::Ordered) }
+        ];
+        let bill = build_bill(1, &orders, &[menu_item("Kapao", 12000)], BillCharges::default(), Utc::now()).unwrap();
+
+        assert_eq!(bill.line_items[0].unit_price, 10000);
+        assert_eq!(bill.line_items[1].unit_price, 5000);
+        assert_eq!(bill.subtotal, 25000);
+    }
+
+
+    #[test]
+    fn test_build_bill_with_invalid_orders(){
+        assert!(matches!(build_bill(1, &[], &[], BillCharges::default(), Utc::now()), Err(ApiError::BadRequest(_))));
+
+        let orders = vec![order(1, "Kapao", 1, OrderStatus::Ordered), order(2, "Burger", 1, OrderStatus::Ordered)];
+        let result = build_bill(1, &orders, &[menu_item("Kapao", 12000)], BillCharges::default(), Utc::now());
+        assert!(matches!(result, Err(ApiError::BadRequest(cause)) if cause.contains("Burger")));
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::prelude::FromRow;
+use trait_variant;
+use crate::error::ApiError;
+
+
+/// how long idempotency keys are kept. Retries after that are processed as new requests
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// how long a reservation of unfinished request is kept. A retry after that takes the key over,
+/// so that a request which never finished (e.g., server crashed) doesn't block its retries until the key expires
+pub const IDEMPOTENCY_LEASE_SECONDS: i64 = 30;
+
+
+/// This struct represents a stored idempotency key. status_code and response_body are None while the first request is processed,
+/// and creation_time is the time of the latest reservation
+#[derive(FromRow, Debug, PartialEq, Clone)]
+pub struct IdempotencyRecord{
+    pub idempotency_key: String,
+    pub request_fingerprint: String,
+    pub status_code: Option<i16>,
+    pub response_body: Option<String>,
+    pub creation_time: DateTime<Utc>,
+    pub etag: Option<String>
+}
+
+
+/// Result of reserving an idempotency key
+#[derive(Debug, PartialEq, Clone)]
+pub enum IdempotencyReservation{
+    /// the key is new. The caller processes the request, and then completes (or releases) the key
+    Reserved,
+
+    /// the key was used by an identical request which is already finished, so its response is replayed
+    Completed{ status_code: u16, etag: Option<String>, response_body: String }
+}
+
+
+/// trait for idempotency key access object. contains reserve / complete / release key functions.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (IdempotencyDAO)
+#[trait_variant::make(IdempotencyDAO: Send)]
+pub trait LocalIdempotencyDAO: Clone + Sync + 'static{
+    /// function to reserve an idempotency key for a request (keys older than IDEMPOTENCY_KEY_TTL_HOURS are removed first).
+    /// It returns ApiError::IdempotencyKeyReused if the key was used with a different request fingerprint,
+    /// or ApiError::IdempotentRequestInProgress if the request of the key is not finished yet.
+    /// Unfinished reservation older than IDEMPOTENCY_LEASE_SECONDS is taken over by the new request
+    async fn reserve_idempotency_key(&self, idempotency_key: &str, request_fingerprint: &str, reservation_time: DateTime<Utc>) -> Result<IdempotencyReservation, ApiError>;
+
+    /// function to store the response (and its ETag) of a reserved key, which is replayed for retries
+    async fn complete_idempotency_key(&self, idempotency_key: &str, status_code: u16, etag: Option<&str>, response_body: &str) -> Result<(), ApiError>;
+
+    /// function to remove a reserved key without response (e.g., server error), so that the request can be retried
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<(), ApiError>;
+}
+
+
+/// utility function to get the oldest creation time of keys which are still kept (shared by DAO implementations)
+pub(crate) fn idempotency_key_expiry(reservation_time: DateTime<Utc>) -> DateTime<Utc>{
+    reservation_time - Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS)
+}
+
+
+/// utility function to get the latest reservation time of unfinished requests which can't be taken over yet (shared by DAO implementations)
+pub(crate) fn idempotency_lease_expiry(reservation_time: DateTime<Utc>) -> DateTime<Utc>{
+    reservation_time - Duration::seconds(IDEMPOTENCY_LEASE_SECONDS)
+}
+
+
+/// utility function to check existing key against the retried request (shared by DAO implementations)
+pub(crate) fn check_idempotency_record(record: IdempotencyRecord, request_fingerprint: &str) -> Result<IdempotencyReservation, ApiError>{
+    if record.request_fingerprint != request_fingerprint{
+        return Err(ApiError::IdempotencyKeyReused);
+    }
+
+    match (record.status_code, record.response_body){
+        (Some(status_code), Some(response_body)) => Ok(IdempotencyReservation::Completed{ status_code: status_code as u16, etag: record.etag, response_body }),
+        _ => Err(ApiError::IdempotentRequestInProgress)
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::api_key_dao::{ApiKeyDAO, check_valid_api_key};
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::model::{ApiKey, Role};
+use crate::error::ApiError;
+
+
+impl ApiKeyDAO for MemTableOrderDAO{
+    async fn add_api_key(&self, name: &str, role: Role, key_hash: &str, creation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        let mut store = self.lock()?;
+
+        // same as the unique constraint of sql implementations
+        if store.api_keys.values().any(|api_key| api_key.key_hash == key_hash){
+            return Err(ApiError::BadRequest("API key already exists".to_string()));
+        }
+
+        store.last_api_key_id += 1;
+        let api_key = ApiKey{
+            api_key_id: store.last_api_key_id,
+            name: name.to_string(),
+            role,
+            key_hash: key_hash.to_string(),
+            creation_time,
+            revocation_time: None
+        };
+        store.api_keys.insert(api_key.api_key_id, api_key.clone());
+        Ok(api_key)
+    }
+
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.api_keys.values().cloned().collect())
+    }
+
+
+    async fn authenticate_api_key(&self, key_hash: &str) -> Result<ApiKey, ApiError> {
+        let store = self.lock()?;
+        check_valid_api_key(store.api_keys.values().find(|api_key| api_key.key_hash == key_hash).cloned())
+    }
+
+
+    async fn revoke_api_key(&self, api_key_id: i32, revocation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        let mut store = self.lock()?;
+        let api_key = store.api_keys.get_mut(&api_key_id).ok_or(ApiError::ApiKeyNotFound)?;
+
+        if api_key.revocation_time.is_none(){
+            api_key.revocation_time = Some(revocation_time);
+        }
+        Ok(api_key.clone())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::{Duration, Utc};
+
+    use crate::dao::api_key_dao::ApiKeyDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::model::Role;
+
+
+    #[tokio::test]
+    async fn test_api_keys(){
+        let dao = MemTableOrderDAO::new();
+        let time = Utc::now();
+
+        let first = dao.add_api_key("front desk", Role::Waiter, "hash-1", time).await.unwrap();
+        let second = dao.add_api_key("kitchen", Role::Kitchen, "hash-2", time).await.unwrap();
+        assert_eq!(first.api_key_id, 1);
+        assert_eq!(second.api_key_id, 2);
+        assert!(dao.add_api_key("duplicate", Role::Waiter, "hash-1", time).await.is_err());
+
+        assert_eq!(dao.authenticate_api_key("hash-2").await.unwrap().role, Role::Kitchen);
+        assert!(matches!(dao.authenticate_api_key("unknown").await, Err(ApiError::Unauthorized)));
+
+        // revoking again keeps the first revocation time
+        let revoked = dao.revoke_api_key(2, time).await.unwrap();
+        assert_eq!(revoked.revocation_time, Some(time));
+        let revoked = dao.revoke_api_key(2, time + Duration::minutes(1)).await.unwrap();
+        assert_eq!(revoked.revocation_time, Some(time));
+
+        assert!(matches!(dao.authenticate_api_key("hash-2").await, Err(ApiError::Unauthorized)));
+        assert!(matches!(dao.revoke_api_key(3, time).await, Err(ApiError::ApiKeyNotFound)));
+        assert_eq!(dao.get_api_keys().await.unwrap().len(), 2);
+    }
+}
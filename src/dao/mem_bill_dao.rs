@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::bill_dao::{BillDAO, BILLED_ORDER_REASON, build_bill};
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::model::{Bill, BillCharges, MenuItem, OrderItem};
+use crate::error::ApiError;
+
+
+impl BillDAO for MemTableOrderDAO{
+    async fn checkout_table(&self, table_id: i16, charges: BillCharges, creation_time: DateTime<Utc>) -> Result<Bill, ApiError> {
+        // orders, menu and bills are under the same lock, so checkout is atomic
+        let mut store = self.lock()?;
+
+        let orders: Vec<OrderItem> = store.current_session_orders(table_id);
+        let menu_items: Vec<MenuItem> = store.menu_items.values().cloned().collect();
+
+        let mut bill = build_bill(table_id, &orders, &menu_items, charges, creation_time)?;
+
+        store.last_bill_id += 1;
+        bill.bill_id = store.last_bill_id;
+        store.bills.insert(bill.bill_id, bill.clone());
+
+        for order in orders{
+            store.orders.remove(&order.order_id);
+            store.removed_orders.insert(order.order_id, OrderItem{
+                deleted_at: Some(creation_time),
+                reason: Some(BILLED_ORDER_REASON.to_string()),
+                ..order
+            });
+        }
+
+        // the party has paid, so its seating session is closed
+        if let Some(session_id) = store.current_session_id(table_id){
+            if let Some(session) = store.sessions.get_mut(&session_id){
+                session.close_time = Some(creation_time);
+            }
+        }
+        store.bump_orders_version(table_id);
+        Ok(bill)
+    }
+
+
+    async fn get_bill(&self, bill_id: i32) -> Result<Bill, ApiError> {
+        let store = self.lock()?;
+        store.bills.get(&bill_id)
+            .cloned()
+            .ok_or(ApiError::BillNotFound)
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use crate::dao::bill_dao::{BillDAO, BILLED_ORDER_REASON};
+    use crate::dao::menu_dao::MenuDAO;
+    use crate::dao::order_dao::TableOrderDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::handlers::process_order_requests;
+    use crate::model::{BillCharges, MenuItemRequest, OrderItemRequest, TableOrdersRequest};
+
+
+    #[tokio::test]
+    async fn test_checkout_table(){
+        let dao = MemTableOrderDAO::new();
+        dao.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+        dao.add_menu_item(&MenuItemRequest::new("Beer", "drink", 9000, 1)).await.unwrap();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order_wihtout_note("Kapao");
+        table_orders.orders.push(OrderItemRequest::new_wihout_note(3, "Beer").with_quantity(2));
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        let mut other_table_orders = TableOrdersRequest::new(4);
+        other_table_orders.add_order_wihtout_note("Kapao");
+        dao.add_table_orders(&process_order_requests(other_table_orders), &[]).await.unwrap();
+
+        let charges = BillCharges{ service_charge_bps: 1000, tax_bps: 700 };
+        let bill = dao.checkout_table(3, charges, Utc::now()).await.unwrap();
+        assert_eq!(bill.bill_id, 1);
+        assert_eq!(bill.line_items.len(), 2);
+        assert_eq!(bill.subtotal, 30000);
+        assert_eq!(bill.service_charge, 3000);
+        assert_eq!(bill.tax, 2310);
+        assert_eq!(bill.total, 35310);
+        assert_eq!(dao.get_bill(1).await.unwrap(), bill);
+
+        // table is cleared, other tables are untouched
+        assert!(dao.get_table_orders(3, None).await.unwrap().is_empty());
+        assert_eq!(dao.get_table_orders(4, None).await.unwrap().len(), 1);
+
+        // billed orders are kept (soft deleted)
+        {
+            let store = dao.lock().unwrap();
+            assert_eq!(store.removed_orders.len(), 2);
+            assert!(store.removed_orders.values().all(|order| order.reason.as_deref() == Some(BILLED_ORDER_REASON)));
+        }
+
+        assert!(matches!(dao.checkout_table(3, charges, Utc::now()).await, Err(ApiError::BadRequest(_))));
+        assert!(matches!(dao.get_bill(2).await, Err(ApiError::BillNotFound)));
+    }
+}
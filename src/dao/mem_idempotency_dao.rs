@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::idempotency_dao::{IdempotencyDAO, IdempotencyRecord, IdempotencyReservation, idempotency_key_expiry, idempotency_lease_expiry, check_idempotency_record};
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::error::ApiError;
+
+
+impl IdempotencyDAO for MemTableOrderDAO{
+    async fn reserve_idempotency_key(&self, idempotency_key: &str, request_fingerprint: &str, reservation_time: DateTime<Utc>) -> Result<IdempotencyReservation, ApiError> {
+        let mut store = self.lock()?;
+
+        let expiry = idempotency_key_expiry(reservation_time);
+        store.idempotency_keys.retain(|_, record| record.creation_time >= expiry);
+
+        match store.idempotency_keys.get_mut(idempotency_key){
+            // unfinished request of the same fingerprint is taken over once its lease has expired
+            Some(record) if record.request_fingerprint == request_fingerprint && record.status_code.is_none()
+                && record.creation_time < idempotency_lease_expiry(reservation_time) => {
+                record.creation_time = reservation_time;
+                Ok(IdempotencyReservation::Reserved)
+            },
+            Some(record) => check_idempotency_record(record.clone(), request_fingerprint),
+            None => {
+                store.idempotency_keys.insert(idempotency_key.to_string(), IdempotencyRecord{
+                    idempotency_key: idempotency_key.to_string(),
+                    request_fingerprint: request_fingerprint.to_string(),
+                    status_code: None,
+                    response_body: None,
+                    creation_time: reservation_time,
+                    etag: None
+                });
+                Ok(IdempotencyReservation::Reserved)
+            }
+        }
+    }
+
+
+    async fn complete_idempotency_key(&self, idempotency_key: &str, status_code: u16, etag: Option<&str>, response_body: &str) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+
+        if let Some(record) = store.idempotency_keys.get_mut(idempotency_key){
+            record.status_code = Some(status_code as i16);
+            record.etag = etag.map(str::to_string);
+            record.response_body = Some(response_body.to_string());
+        }
+        Ok(())
+    }
+
+
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+
+        if store.idempotency_keys.get(idempotency_key).is_some_and(|record| record.status_code.is_none()){
+            store.idempotency_keys.remove(idempotency_key);
+        }
+        Ok(())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::{Duration, Utc};
+
+    use crate::dao::idempotency_dao::{IdempotencyDAO, IdempotencyReservation, IDEMPOTENCY_KEY_TTL_HOURS, IDEMPOTENCY_LEASE_SECONDS};
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+
+
+    #[tokio::test]
+    async fn test_idempotency_keys(){
+        let dao = MemTableOrderDAO::new();
+        let time = Utc::now();
+
+        assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.unwrap(), IdempotencyReservation::Reserved);
+        assert!(matches!(dao.reserve_idempotency_key("key-1", "request", time).await, Err(ApiError::IdempotentRequestInProgress)));
+        assert!(matches!(dao.reserve_idempotency_key("key-1", "other request", time).await, Err(ApiError::IdempotencyKeyReused)));
+
+        // released key can be reserved again
+        dao.release_idempotency_key("key-1").await.unwrap();
+        assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.unwrap(), IdempotencyReservation::Reserved);
+
+        dao.complete_idempotency_key("key-1", 200, Some("\"1-1\""), "{}").await.unwrap();
+        dao.release_idempotency_key("key-1").await.unwrap();
+        assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.unwrap(),
+            IdempotencyReservation::Completed{ status_code: 200, etag: Some("\"1-1\"".to_string()), response_body: "{}".to_string() });
+
+        // unfinished request is taken over by a retry after its lease, but not by a different request
+    assert_eq!(dao.reserve_idempotency_key("key-2", "request", time).await.unwrap(), IdempotencyReservation::Reserved);
+    let lease_expired = time + Duration::seconds(IDEMPOTENCY_LEASE_SECONDS + 1);
+    assert!(matches!(dao.reserve_idempotency_key("key-2", "other request", lease_expired).await, Err(ApiError::IdempotencyKeyReused)));
+    assert_eq!(dao.reserve_idempotency_key("key-2", "request", lease_expired).await.unwrap(), IdempotencyReservation::Reserved);
+    assert!(matches!(dao.reserve_idempotency_key("key-2", "request", lease_expired).await, Err(ApiError::IdempotentRequestInProgress)));
+
+    // expired key is forgotten
+        let later = time + Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS + 1);
+        assert_eq!(dao.reserve_idempotency_key("key-1", "other request", later).await.unwrap(), IdempotencyReservation::Reserved);
+    }
+}
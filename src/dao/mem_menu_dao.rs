@@ -0,0 +1,158 @@
+use crate::dao::menu_dao::MenuDAO;
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::model::{MenuItem, MenuItemRequest};
+use crate::error::ApiError;
+
+
+impl MemTableOrderDAO{
+    /// helper function to check unique menu item name (same as unique constraint in sql implementations)
+    fn check_unique_menu_item_name(menu_items: &[&MenuItem], name: &str) -> Result<(), ApiError>{
+        if menu_items.iter().any(|item| item.name == name){
+            Err(ApiError::MenuItemAlreadyExists)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+impl MenuDAO for MemTableOrderDAO{
+    async fn add_menu_item(&self, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        let mut store = self.lock()?;
+        Self::check_unique_menu_item_name(&store.menu_items.values().collect::<Vec<_>>(), &item.name)?;
+
+        store.last_menu_item_id += 1;
+        let menu_item = MenuItem{
+            menu_item_id: store.last_menu_item_id,
+            name: item.name.clone(),
+            category: item.category.clone(),
+            price: item.price,
+            prep_time_minutes: item.prep_time_minutes,
+            active: item.active
+        };
+        store.menu_items.insert(menu_item.menu_item_id, menu_item.clone());
+        Ok(menu_item)
+    }
+
+
+    async fn get_menu_items(&self) -> Result<Vec<MenuItem>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.menu_items.values().cloned().collect())
+    }
+
+
+    async fn get_menu_item(&self, menu_item_id: i32) -> Result<MenuItem, ApiError> {
+        let store = self.lock()?;
+        store.menu_items.get(&menu_item_id)
+            .cloned()
+            .ok_or(ApiError::MenuItemNotFound)
+    }
+
+
+    async fn get_menu_items_by_names(&self, names: &[String]) -> Result<Vec<MenuItem>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.menu_items.values()
+            .filter(|item| names.contains(&item.name))
+            .cloned()
+            .collect())
+    }
+
+
+    async fn update_menu_item(&self, menu_item_id: i32, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        let mut store = self.lock()?;
+        if !store.menu_items.contains_key(&menu_item_id){
+            return Err(ApiError::MenuItemNotFound);
+        }
+
+        let other_items: Vec<&MenuItem> = store.menu_items.values().filter(|other| other.menu_item_id != menu_item_id).collect();
+        Self::check_unique_menu_item_name(&other_items, &item.name)?;
+
+        let menu_item = MenuItem{
+            menu_item_id,
+            name: item.name.clone(),
+            category: item.category.clone(),
+            price: item.price,
+            prep_time_minutes: item.prep_time_minutes,
+            active: item.active
+        };
+        store.menu_items.insert(menu_item_id, menu_item.clone());
+        Ok(menu_item)
+    }
+
+
+    async fn remove_menu_item(&self, menu_item_id: i32) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        let item = store.menu_items.get(&menu_item_id).ok_or(ApiError::MenuItemNotFound)?;
+
+        // removed and billed orders are not kept in store.orders
+        if store.orders.values().any(|order| order.menu_item_id == Some(menu_item_id) || order.item_name == item.name){
+            return Err(ApiError::MenuItemInUse);
+        }
+
+        store.menu_items.remove(&menu_item_id);
+        Ok(())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use crate::dao::bill_dao::BillDAO;
+    use crate::dao::menu_dao::MenuDAO;
+    use crate::dao::order_dao::TableOrderDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::handlers::process_order_requests;
+    use crate::model::{BillCharges, MenuItemRequest, TableOrdersRequest};
+
+
+    #[tokio::test]
+    async fn test_menu_item_crud(){
+        let dao = MemTableOrderDAO::new();
+
+        let kaprao = dao.add_menu_item(&MenuItemRequest::new("Kaprao", "main", 12000, 10)).await.unwrap();
+        let tea = dao.add_menu_item(&MenuItemRequest::new("Thai tea", "drink", 6000, 3)).await.unwrap();
+        assert_eq!(kaprao.menu_item_id, 1);
+        assert_eq!(tea.menu_item_id, 2);
+        assert!(kaprao.active);
+
+        // duplicate name
+        assert!(matches!(dao.add_menu_item(&MenuItemRequest::new("Kaprao", "main", 1, 1)).await, Err(ApiError::MenuItemAlreadyExists)));
+        assert!(matches!(dao.update_menu_item(tea.menu_item_id, &MenuItemRequest::new("Kaprao", "drink", 1, 1)).await, Err(ApiError::MenuItemAlreadyExists)));
+
+        let mut request = MenuItemRequest::new("Thai milk tea", "drink", 6500, 3);
+        request.active = false;
+        let updated = dao.update_menu_item(tea.menu_item_id, &request).await.unwrap();
+        assert_eq!(updated.name, "Thai milk tea");
+        assert_eq!(updated.price, 6500);
+        assert!(!updated.active);
+        assert_eq!(dao.get_menu_item(tea.menu_item_id).await.unwrap(), updated);
+
+        let items = dao.get_menu_items_by_names(&["Thai milk tea".to_string(), "Kapao".to_string()]).await.unwrap();
+        assert_eq!(items, vec![updated]);
+
+        dao.remove_menu_item(kaprao.menu_item_id).await.unwrap();
+        assert!(matches!(dao.get_menu_item(kaprao.menu_item_id).await, Err(ApiError::MenuItemNotFound)));
+        assert!(matches!(dao.remove_menu_item(kaprao.menu_item_id).await, Err(ApiError::MenuItemNotFound)));
+        assert_eq!(dao.get_menu_items().await.unwrap().len(), 1);
+    }
+
+
+    #[tokio::test]
+    async fn test_remove_menu_item_with_open_orders(){
+        let dao = MemTableOrderDAO::new();
+        let kaprao = dao.add_menu_item(&MenuItemRequest::new("Kaprao", "main", 12000, 10)).await.unwrap();
+
+        let mut table_orders = TableOrdersRequest::new(1);
+        table_orders.add_order_wihtout_note("Kaprao");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+        assert!(matches!(dao.remove_menu_item(kaprao.menu_item_id).await, Err(ApiError::MenuItemInUse)));
+
+        // billed orders don't block removal
+        dao.checkout_table(1, BillCharges::default(), Utc::now()).await.unwrap();
+        dao.remove_menu_item(kaprao.menu_item_id).await.unwrap();
+    }
+}
@@ -0,0 +1,611 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::dao::order_dao::{TableOrderDAO, PoolUsage, check_status_transition, remaining_quantity_after_decrement, reason_for_removal_by_decrement, check_transferred_orders, check_merged_orders};
+use crate::dao::table_dao::{default_tables, DEFAULT_TABLE_COUNT};
+use crate::dao::idempotency_dao::IdempotencyRecord;
+use crate::model::{OrderItem, OrderStatus, MenuItem, Bill, RestaurantTable, TableSession, ApiKey, StaffMember};
+use crate::error::ApiError;
+use chrono::{DateTime, Utc};
+
+
+/// Internal storage of in-memory DAO. id counters and records are kept under the same lock
+/// so that ids are always increasing in insertion order (similar to postgres SERIAL)
+pub(crate) struct MemStore{
+    pub(crate) last_order_id: i32,
+    pub(crate) orders: BTreeMap<i32, OrderItem>,
+    /// removed (soft deleted) orders are kept apart, so that only order history sees them
+    pub(crate) removed_orders: BTreeMap<i32, OrderItem>,
+    pub(crate) last_menu_item_id: i32,
+    pub(crate) menu_items: BTreeMap<i32, MenuItem>,
+    pub(crate) last_bill_id: i32,
+    pub(crate) bills: BTreeMap<i32, Bill>,
+    pub(crate) last_table_id: i16,
+    pub(crate) tables: BTreeMap<i16, RestaurantTable>,
+    pub(crate) last_session_id: i32,
+    pub(crate) sessions: BTreeMap<i32, TableSession>,
+    pub(crate) idempotency_keys: BTreeMap<String, IdempotencyRecord>,
+    pub(crate) orders_versions: BTreeMap<i16, i64>,
+    pub(crate) last_api_key_id: i32,
+    pub(crate) api_keys: BTreeMap<i32, ApiKey>,
+    pub(crate) staff: BTreeMap<String, StaffMember>
+}
+
+
+impl Default for MemStore{
+    /// empty storage with default tables (same as the tables migration of sql implementations)
+    fn default() -> Self {
+        Self{
+            last_order_id: 0,
+            orders: BTreeMap::new(),
+            removed_orders: BTreeMap::new(),
+            last_menu_item_id: 0,
+            menu_items: BTreeMap::new(),
+            last_bill_id: 0,
+            bills: BTreeMap::new(),
+            last_table_id: DEFAULT_TABLE_COUNT,
+            tables: default_tables().into_iter().map(|table| (table.table_id, table)).collect(),
+            last_session_id: 0,
+            sessions: BTreeMap::new(),
+            idempotency_keys: BTreeMap::new(),
+            orders_versions: BTreeMap::new(),
+            last_api_key_id: 0,
+            api_keys: BTreeMap::new(),
+            staff: BTreeMap::new()
+        }
+    }
+}
+
+
+impl MemStore{
+    /// helper function to get id of the open seating session of a table
+    pub(crate) fn current_session_id(&self, table_id: i16) -> Option<i32>{
+        self.sessions.values()
+            .find(|session| session.table_id == table_id && session.close_time.is_none())
+            .map(|session| session.session_id)
+    }
+
+    /// helper function to increase version of table's orders. Every change of orders (or seating session) must call it,
+    /// similar to the triggers of sql implementations
+    pub(crate) fn bump_orders_version(&mut self, table_id: i16){
+        *self.orders_versions.entry(table_id).or_insert(0) += 1;
+    }
+
+    /// helper function to compare expected versions of tables' orders (If-Match) under the lock of the change
+    pub(crate) fn check_orders_versions(&self, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>{
+        match expected_versions.iter().all(|(table_id, version)| self.orders_versions.get(table_id).copied().unwrap_or(0) == *version){
+            true => Ok(()),
+            false => Err(ApiError::PreconditionFailed)
+        }
+    }
+
+    /// helper function to open a seating session (the caller checks that the table has no open session)
+    pub(crate) fn insert_session(&mut self, table_id: i16, guest_count: Option<i16>, open_time: DateTime<Utc>) -> TableSession{
+        self.last_session_id += 1;
+        let session = TableSession{
+            session_id: self.last_session_id,
+            table_id,
+            guest_count,
+            open_time,
+            close_time: None
+        };
+        self.sessions.insert(session.session_id, session.clone());
+        session
+    }
+
+    /// helper function to get orders of the open seating session of a table
+    pub(crate) fn current_session_orders(&self, table_id: i16) -> Vec<OrderItem>{
+        let session_id = self.current_session_id(table_id);
+        self.orders.values()
+            .filter(|order| session_id.is_some() && order.session_id == session_id)
+            .cloned()
+            .collect()
+    }
+
+    /// helper function to check that order belongs to an open seating session (orders of closed sessions are not changed anymore)
+    fn is_in_open_session(&self, order: &OrderItem) -> bool{
+        order.session_id
+            .and_then(|session_id| self.sessions.get(&session_id))
+            .is_some_and(|session| session.close_time.is_none())
+    }
+
+    /// helper function to get order of the table's open seating session (ApiError::OrderNotFound otherwise)
+    fn current_session_order(&self, table_id: i16, order_id: i32) -> Result<&OrderItem, ApiError>{
+        self.orders.get(&order_id)
+            .filter(|order| order.table_id == table_id && self.is_in_open_session(order))
+            .ok_or(ApiError::OrderNotFound)
+    }
+
+    /// helper function to move orders of the open session of source table to the open session of target table.
+    /// All orders of the session are moved if order_ids is None. Nothing is moved if check fails with the number of movable orders
+    fn move_orders(&mut self, source_table_id: i16, target_table_id: i16, order_ids: Option<&[i32]>, move_time: DateTime<Utc>,
+        check: impl FnOnce(usize) -> Result<(), ApiError>) -> Result<(), ApiError>{
+
+        let moved: Vec<i32> = self.current_session_orders(source_table_id).into_iter()
+            .map(|order| order.order_id)
+            .filter(|order_id| match order_ids{
+                Some(order_ids) => order_ids.contains(order_id),
+                None => true
+            })
+            .collect();
+        check(moved.len())?;
+
+        let session_id = match self.current_session_id(target_table_id){
+            Some(session_id) => session_id,
+            None => self.insert_session(target_table_id, None, move_time).session_id
+        };
+
+        for order_id in moved{
+            if let Some(order) = self.orders.get_mut(&order_id){
+                order.table_id = target_table_id;
+                order.session_id = Some(session_id);
+            }
+        }
+        self.bump_orders_version(source_table_id);
+        self.bump_orders_version(target_table_id);
+        Ok(())
+    }
+}
+
+
+#[derive(Clone, Default)]
+/// In-memory implementation of order DAO. It is thread-safe (clones share the same storage)
+/// and intended for tests and demos only, since all orders are lost when the server stops
+pub struct MemTableOrderDAO{
+    store: Arc<Mutex<MemStore>>
+}
+
+
+impl MemTableOrderDAO{
+    /// constructor to create empty in-memory storage (only default tables are registered)
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// helper function to acquire storage lock. Poisoned lock (panic while holding lock) is reported as server error
+    pub(crate) fn lock(&self) -> Result<MutexGuard<'_, MemStore>, ApiError>{
+        self.store.lock()
+            .map_err(|_| ApiError::ServerError("in-memory storage is poisoned".to_string()))
+    }
+}
+
+
+impl TableOrderDAO for MemTableOrderDAO{
+    async fn health_check(&self) -> Result<(), ApiError> {
+        self.lock().map(|_| ())
+    }
+
+
+    fn pool_usage(&self) -> Option<PoolUsage> {
+        None
+    }
+
+
+    async fn add_table_orders(&self, items: &[OrderItem], expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+
+        // orders of a request are always added to the same table
+        let Some(first_order) = items.first() else{
+            return Ok(());
+        };
+        store.check_orders_versions(expected_versions)?;
+        let session_id = match store.current_session_id(first_order.table_id){
+            Some(session_id) => session_id,
+            None => store.insert_session(first_order.table_id, None, first_order.creation_time).session_id
+        };
+
+        for item in items{
+            store.last_order_id += 1;
+            let order_id = store.last_order_id;
+            store.orders.insert(order_id, OrderItem{ order_id, session_id: Some(session_id), ..item.clone() });
+        }
+        store.bump_orders_version(first_order.table_id);
+        Ok(())
+    }
+
+
+    async fn get_table_orders(&self, table_id: i16, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        let store = self.lock()?;
+
+        Ok(store.current_session_orders(table_id).into_iter()
+            .filter(|order| status.is_none() || status == Some(order.status))
+            .collect())
+    }
+
+
+    async fn get_specific_table_order(&self, table_id: i16, order_id: i32) -> Result<Vec<OrderItem>, ApiError> {
+        let store = self.lock()?;
+
+        store.current_session_order(table_id, order_id)
+            .map(|order| vec![order.clone()])
+    }
+
+
+    async fn remove_order(&self, table_id: i16, order_id: i32, deleted_by: Option<&str>, reason: Option<&str>, deletion_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        store.check_orders_versions(expected_versions)?;
+
+        let removed = OrderItem{
+            deleted_at: Some(deletion_time),
+            deleted_by: deleted_by.map(str::to_string),
+            reason: reason.map(str::to_string),
+            ..store.current_session_order(table_id, order_id)?.clone()
+        };
+        store.orders.remove(&order_id);
+        store.removed_orders.insert(order_id, removed);
+        store.bump_orders_version(table_id);
+        Ok(())
+    }
+
+
+    async fn get_table_order_history(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError> {
+        let store = self.lock()?;
+
+        let session_id = store.current_session_id(table_id);
+        let mut orders: Vec<OrderItem> = store.current_session_orders(table_id).into_iter()
+            .chain(store.removed_orders.values()
+                .filter(|order| session_id.is_some() && order.session_id == session_id)
+                .cloned())
+            .collect();
+        orders.sort_by_key(|order| order.order_id);
+        Ok(orders)
+    }
+
+
+    async fn update_order_status(&self, table_id: i16, order_id: i32, status: OrderStatus, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        store.check_orders_versions(expected_versions)?;
+
+        check_status_transition(store.current_session_order(table_id, order_id)?.status, status)?;
+        if let Some(order) = store.orders.get_mut(&order_id){
+            order.status = status;
+        }
+        store.bump_orders_version(table_id);
+        Ok(())
+    }
+
+
+    async fn decrement_order_quantity(&self, table_id: i16, order_id: i32, quantity: i32, deleted_by: Option<&str>, reason: Option<&str>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        store.check_orders_versions(expected_versions)?;
+
+        let remaining = remaining_quantity_after_decrement(store.current_session_order(table_id, order_id)?.quantity, quantity)?;
+
+        // the order is removed (soft deleted with its last quantity) once all of its items are decremented
+        if remaining == 0{
+            let reason = reason_for_removal_by_decrement(reason)?;
+            if let Some(order) = store.orders.remove(&order_id){
+                store.removed_orders.insert(order_id, OrderItem{ deleted_at: Some(Utc::now()), deleted_by: deleted_by.map(str::to_string), reason: Some(reason.to_string()), ..order });
+            }
+        }else if let Some(order) = store.orders.get_mut(&order_id){
+            order.quantity = remaining;
+        }
+        store.bump_orders_version(table_id);
+        Ok(())
+    }
+
+
+    async fn acknowledge_order(&self, table_id: i16, order_id: i32, device_id: &str, acknowledgement_time: DateTime<Utc>) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+
+        store.current_session_order(table_id, order_id)?;
+        if let Some(order) = store.orders.get_mut(&order_id).filter(|order| order.acknowledged_by.is_none()){
+            order.acknowledged_by = Some(device_id.to_string());
+            order.acknowledgement_time = Some(acknowledgement_time);
+        }
+        store.bump_orders_version(table_id);
+        Ok(())
+    }
+
+
+    async fn transfer_orders(&self, source_table_id: i16, target_table_id: i16, order_ids: &[i32], transfer_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        store.check_orders_versions(expected_versions)?;
+
+        store.move_orders(source_table_id, target_table_id, Some(order_ids), transfer_time,
+            |moved_count| check_transferred_orders(order_ids, moved_count))
+    }
+
+
+    async fn merge_tables(&self, source_table_id: i16, target_table_id: i16, merge_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        store.check_orders_versions(expected_versions)?;
+
+        let source_session_id = store.current_session_id(source_table_id);
+        store.move_orders(source_table_id, target_table_id, None, merge_time,
+            |moved_count| check_merged_orders(source_table_id, moved_count))?;
+
+        // the party has moved to target table, so source table is empty for the next party
+        if let Some(session) = source_session_id.and_then(|session_id| store.sessions.get_mut(&session_id)){
+            session.close_time = Some(merge_time);
+        }
+        Ok(())
+    }
+
+
+    async fn count_unfinished_orders(&self) -> Result<i64, ApiError> {
+        let store = self.lock()?;
+
+        Ok(store.orders.values()
+            .filter(|order| matches!(order.status, OrderStatus::Ordered | OrderStatus::Cooking) && store.is_in_open_session(order))
+            .count() as i64)
+    }
+
+
+    async fn get_kitchen_queue(&self, category: Option<&str>, limit: i64) -> Result<Vec<OrderItem>, ApiError> {
+        let store = self.lock()?;
+
+        // orders are matched by their menu item snapshot, or by name if they were added without snapshot
+        let is_in_category = |order: &OrderItem| match category{
+            None => true,
+            Some(category) => match order.menu_item_id{
+                Some(menu_item_id) => store.menu_items.get(&menu_item_id),
+                None => store.menu_items.values().find(|item| item.name == order.item_name)
+            }.is_some_and(|item| item.category == category)
+        };
+
+        let mut orders: Vec<OrderItem> = store.orders.values()
+            .filter(|order| order.status.is_open() && store.is_in_open_session(order))
+            .filter(|order| is_in_category(order))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| (order.estimated_arrival_time, order.creation_time, order.order_id));
+        orders.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(orders)
+    }
+
+
+    async fn count_open_orders_by_table(&self) -> Result<Vec<(i16, i64)>, ApiError> {
+        let store = self.lock()?;
+
+        let mut open_orders = BTreeMap::new();
+        store.orders.values()
+            .filter(|order| matches!(order.status, OrderStatus::Ordered | OrderStatus::Cooking | OrderStatus::Ready) && store.is_in_open_session(order))
+            .for_each(|order| *open_orders.entry(order.table_id).or_insert(0) += 1);
+        Ok(open_orders.into_iter().collect())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use crate::dao::order_dao::TableOrderDAO;
+    use crate::error::ApiError;
+    use crate::handlers::process_order_requests;
+    use crate::model::{TableOrdersRequest, OrderItemRequest, OrderStatus};
+
+    use super::MemTableOrderDAO;
+
+
+    #[tokio::test]
+    async fn test_add_and_get_orders(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order("A", "B");
+        table_orders.add_order_wihtout_note("C");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        let mut other_table_orders = TableOrdersRequest::new(4);
+        other_table_orders.add_order_wihtout_note("D");
+        dao.add_table_orders(&process_order_requests(other_table_orders), &[]).await.unwrap();
+
+        let orders = dao.get_table_orders(3, None).await.unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].order_id, 1);
+        assert_eq!(orders[0].item_name, "A");
+        assert_eq!(orders[0].note, Some("B".to_string()));
+        assert_eq!(orders[1].order_id, 2);
+        assert_eq!(orders[1].item_name, "C");
+        assert_eq!(orders[1].note, None);
+
+        let orders = dao.get_table_orders(4, None).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, 3);
+
+        assert_eq!(dao.get_table_orders(5, None).await.unwrap().len(), 0);
+    }
+
+
+    #[tokio::test]
+    async fn test_get_specific_order(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order("A", "B");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        let orders = dao.get_specific_table_order(3, 1).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].item_name, "A");
+
+        // wrong table or unknown order
+        assert!(matches!(dao.get_specific_table_order(4, 1).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.get_specific_table_order(3, 2).await, Err(ApiError::OrderNotFound)));
+    }
+
+
+    #[tokio::test]
+    async fn test_remove_order(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order("A", "B");
+        table_orders.add_order("C", "D");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        assert!(matches!(dao.remove_order(4, 1, None, None, Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+        dao.remove_order(3, 1, Some("waiter-1"), Some("customer changed mind"), Utc::now(), &[]).await.unwrap();
+        assert!(matches!(dao.remove_order(3, 1, None, None, Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+
+        let orders = dao.get_table_orders(3, None).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].item_name, "C");
+        assert!(matches!(dao.get_specific_table_order(3, 1).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.update_order_status(3, 1, OrderStatus::Cooking, &[]).await, Err(ApiError::OrderNotFound)));
+
+        // removed order is kept in order history
+        let history = dao.get_table_order_history(3).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].item_name, "A");
+        assert_eq!(history[0].deleted_by, Some("waiter-1".to_string()));
+        assert_eq!(history[0].reason, Some("customer changed mind".to_string()));
+        assert!(history[0].deleted_at.is_some());
+        assert!(history[1].deleted_at.is_none());
+
+        // order ids are not reused after removal
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order_wihtout_note("E");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+        assert_eq!(dao.get_table_orders(3, None).await.unwrap()[1].order_id, 3);
+    }
+
+
+    #[tokio::test]
+    async fn test_update_order_status(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order("A", "B");
+        table_orders.add_order("C", "D");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        dao.update_order_status(3, 1, OrderStatus::Cooking, &[]).await.unwrap();
+        assert!(matches!(dao.update_order_status(3, 1, OrderStatus::Served, &[]).await, Err(ApiError::IllegalStatusTransition(OrderStatus::Cooking, OrderStatus::Served))));
+        assert!(matches!(dao.update_order_status(4, 1, OrderStatus::Ready, &[]).await, Err(ApiError::OrderNotFound)));
+
+        let orders = dao.get_table_orders(3, Some(OrderStatus::Cooking)).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, 1);
+
+        let orders = dao.get_table_orders(3, Some(OrderStatus::Ordered)).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, 2);
+
+        dao.update_order_status(3, 1, OrderStatus::Ready, &[]).await.unwrap();
+        assert_eq!(dao.count_unfinished_orders().await.unwrap(), 1);
+        assert_eq!(dao.count_open_orders_by_table().await.unwrap(), vec![(3, 2)]);
+    }
+
+
+    #[tokio::test]
+    async fn test_decrement_order_quantity(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.orders.push(OrderItemRequest::new_wihout_note(3, "Beer").with_quantity(3));
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+        assert_eq!(dao.get_table_orders(3, None).await.unwrap()[0].quantity, 3);
+
+        dao.decrement_order_quantity(3, 1, 2, None, None, &[]).await.unwrap();
+        assert_eq!(dao.get_table_orders(3, None).await.unwrap()[0].quantity, 1);
+
+        assert!(matches!(dao.decrement_order_quantity(3, 1, 2, None, None, &[]).await, Err(ApiError::BadRequest(_))));
+        assert!(matches!(dao.decrement_order_quantity(4, 1, 1, None, None, &[]).await, Err(ApiError::OrderNotFound)));
+
+        // order is removed when quantity reaches 0 (only with a void reason), and its last quantity is kept in order history
+        assert!(matches!(dao.decrement_order_quantity(3, 1, 1, None, None, &[]).await, Err(ApiError::BadRequest(_))));
+        dao.decrement_order_quantity(3, 1, 1, Some("waiter-1"), Some("comp"), &[]).await.unwrap();
+        assert!(dao.get_table_orders(3, None).await.unwrap().is_empty());
+        let history = dao.get_table_order_history(3).await.unwrap();
+        assert_eq!(history[0].quantity, 1);
+        assert!(history[0].deleted_at.is_some());
+        assert_eq!(history[0].deleted_by, Some("waiter-1".to_string()));
+        assert_eq!(history[0].reason, Some("comp".to_string()));
+    }
+
+
+    #[tokio::test]
+    async fn test_acknowledge_order(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order_wihtout_note("A");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        let time = Utc::now();
+        dao.acknowledge_order(3, 1, "kitchen-1", time).await.unwrap();
+        dao.acknowledge_order(3, 1, "kitchen-2", time).await.unwrap();
+        assert!(matches!(dao.acknowledge_order(4, 1, "kitchen-1", time).await, Err(ApiError::OrderNotFound)));
+
+        let order = &dao.get_table_orders(3, None).await.unwrap()[0];
+        assert_eq!(order.acknowledged_by, Some("kitchen-1".to_string()));
+        assert_eq!(order.acknowledgement_time, Some(time));
+    }
+
+
+    #[tokio::test]
+    async fn test_transfer_and_merge_orders(){
+        use crate::dao::session_dao::SessionDAO;
+
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order_wihtout_note("A");
+        table_orders.add_order_wihtout_note("B");
+        table_orders.add_order_wihtout_note("C");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        // nothing is moved if any order is not in the source table
+        assert!(matches!(dao.transfer_orders(3, 4, &[1, 5], Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+        assert_eq!(dao.get_table_orders(3, None).await.unwrap().len(), 3);
+
+        dao.transfer_orders(3, 4, &[1, 2], Utc::now(), &[]).await.unwrap();
+        let orders = dao.get_table_orders(4, None).await.unwrap();
+        assert_eq!(orders.iter().map(|order| order.order_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(orders[0].session_id, Some(dao.get_current_session(4).await.unwrap().session_id));
+        assert_eq!(dao.get_table_orders(3, None).await.unwrap().len(), 1);
+
+        // merge moves the remaining orders and closes the source session
+        dao.merge_tables(3, 4, Utc::now(), &[]).await.unwrap();
+        assert_eq!(dao.get_table_orders(4, None).await.unwrap().len(), 3);
+        assert!(dao.get_table_orders(3, None).await.unwrap().is_empty());
+        assert!(matches!(dao.get_current_session(3).await, Err(ApiError::SessionNotFound)));
+        assert!(matches!(dao.merge_tables(3, 4, Utc::now(), &[]).await, Err(ApiError::BadRequest(_))));
+    }
+
+
+    #[tokio::test]
+    async fn test_get_kitchen_queue(){
+        use crate::dao::menu_dao::MenuDAO;
+        use crate::model::MenuItemRequest;
+
+        let dao = MemTableOrderDAO::new();
+        dao.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+        dao.add_menu_item(&MenuItemRequest::new("Beer", "drink", 9000, 1)).await.unwrap();
+
+        let mut table_orders = TableOrdersRequest::new(3);
+        table_orders.add_order_wihtout_note("Kapao");
+        table_orders.add_order_wihtout_note("Beer");
+        table_orders.add_order_wihtout_note("Kapao");
+        let mut orders = process_order_requests(table_orders);
+        orders[0].estimated_arrival_time += chrono::Duration::minutes(30);
+        orders[0].menu_item_id = Some(1);
+        dao.add_table_orders(&orders, &[]).await.unwrap();
+
+        let mut other_table_orders = TableOrdersRequest::new(4);
+        other_table_orders.add_order_wihtout_note("Beer");
+        let mut orders = process_order_requests(other_table_orders);
+        orders[0].estimated_arrival_time -= chrono::Duration::minutes(30);
+        dao.add_table_orders(&orders, &[]).await.unwrap();
+
+        dao.update_order_status(3, 3, OrderStatus::Cancelled, &[]).await.unwrap();
+
+        let queue = dao.get_kitchen_queue(None, 10).await.unwrap();
+        assert_eq!(queue.iter().map(|order| order.order_id).collect::<Vec<_>>(), vec![4, 2, 1]);
+
+        let queue = dao.get_kitchen_queue(Some("main"), 10).await.unwrap();
+        assert_eq!(queue.iter().map(|order| order.order_id).collect::<Vec<_>>(), vec![1]);
+
+        let queue = dao.get_kitchen_queue(None, 2).await.unwrap();
+        assert_eq!(queue.iter().map(|order| order.order_id).collect::<Vec<_>>(), vec![4, 2]);
+
+        // order with menu item snapshot stays in the category after the item is renamed
+        dao.update_menu_item(1, &MenuItemRequest::new("Kaprao", "main", 12000, 10)).await.unwrap();
+        let queue = dao.get_kitchen_queue(Some("main"), 10).await.unwrap();
+        assert_eq!(queue.iter().map(|order| order.order_id).collect::<Vec<_>>(), vec![1]);
+    }
+}
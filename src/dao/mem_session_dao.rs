@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::session_dao::SessionDAO;
+use crate::dao::bill_dao::BILLED_ORDER_REASON;
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::model::{OrderItem, OrderStatus, TableSession};
+use crate::error::ApiError;
+
+
+impl SessionDAO for MemTableOrderDAO{
+    async fn open_session(&self, table_id: i16, guest_count: i16, open_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        let mut store = self.lock()?;
+        if store.current_session_id(table_id).is_some(){
+            return Err(ApiError::SessionAlreadyOpen);
+        }
+
+        Ok(store.insert_session(table_id, Some(guest_count), open_time))
+    }
+
+
+    async fn get_current_session(&self, table_id: i16) -> Result<TableSession, ApiError> {
+        let store = self.lock()?;
+        store.current_session_id(table_id)
+            .and_then(|session_id| store.sessions.get(&session_id))
+            .cloned()
+            .ok_or(ApiError::SessionNotFound)
+    }
+
+
+    async fn get_table_sessions(&self, table_id: i16) -> Result<Vec<TableSession>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.sessions.values()
+            .filter(|session| session.table_id == table_id)
+            .cloned()
+            .collect())
+    }
+
+
+    async fn close_current_session(&self, table_id: i16, close_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        let mut store = self.lock()?;
+        let session_id = store.current_session_id(table_id).ok_or(ApiError::SessionNotFound)?;
+        if store.orders.values().any(|order| order.session_id == Some(session_id)){
+            return Err(ApiError::SessionHasOrders);
+        }
+
+        let session = store.sessions.get_mut(&session_id).ok_or(ApiError::SessionNotFound)?;
+        session.close_time = Some(close_time);
+        let session = session.clone();
+
+        store.bump_orders_version(table_id);
+        Ok(session)
+    }
+
+
+    async fn get_session_orders(&self, table_id: i16, session_id: i32, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        let store = self.lock()?;
+        if !matches!(store.sessions.get(&session_id), Some(session) if session.table_id == table_id){
+            return Err(ApiError::SessionNotFound);
+        }
+
+        // orders billed at checkout are soft deleted, but they are still orders of the (past) session
+        let billed_orders = store.removed_orders.values()
+            .filter(|order| order.reason.as_deref() == Some(BILLED_ORDER_REASON));
+        let mut orders: Vec<OrderItem> = store.orders.values()
+            .chain(billed_orders)
+            .filter(|order| order.session_id == Some(session_id))
+            .filter(|order| status.is_none() || status == Some(order.status))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.order_id);
+        Ok(orders)
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use crate::dao::session_dao::SessionDAO;
+    use crate::dao::order_dao::TableOrderDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::handlers::process_order_requests;
+    use crate::model::{OrderStatus, TableOrdersRequest};
+
+
+    #[tokio::test]
+    async fn test_seating_sessions(){
+        let dao = MemTableOrderDAO::new();
+        assert!(matches!(dao.get_current_session(1).await, Err(ApiError::SessionNotFound)));
+
+        let first = dao.open_session(1, 2, Utc::now()).await.unwrap();
+        assert_eq!(first.guest_count, Some(2));
+        assert!(matches!(dao.open_session(1, 3, Utc::now()).await, Err(ApiError::SessionAlreadyOpen)));
+
+        let mut table_orders = TableOrdersRequest::new(1);
+        table_orders.add_order_wihtout_note("Kapao");
+        dao.add_table_orders(&process_order_requests(table_orders.clone()), &[]).await.unwrap();
+        let previous_orders = dao.get_table_orders(1, None).await.unwrap();
+        assert_eq!(previous_orders[0].session_id, Some(first.session_id));
+
+        // the session can't be closed while it has unbilled orders
+        assert!(matches!(dao.close_current_session(1, Utc::now()).await, Err(ApiError::SessionHasOrders)));
+
+        // next party doesn't see orders of the previous party (e.g., session closed before its orders were billed)
+        let closed = {
+            let mut store = dao.lock().unwrap();
+            let session = store.sessions.get_mut(&first.session_id).unwrap();
+            session.close_time = Some(Utc::now());
+            session.clone()
+        };
+        assert!(dao.get_table_orders(1, None).await.unwrap().is_empty());
+        assert!(matches!(dao.close_current_session(1, Utc::now()).await, Err(ApiError::SessionNotFound)));
+
+        // orders of the closed session can't be changed anymore, and they are not open orders of the kitchen or the table
+        let order_id = previous_orders[0].order_id;
+        assert!(matches!(dao.get_specific_table_order(1, order_id).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.update_order_status(1, order_id, OrderStatus::Cooking, &[]).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.decrement_order_quantity(1, order_id, 1, None, None, &[]).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.remove_order(1, order_id, None, None, Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+        assert!(matches!(dao.acknowledge_order(1, order_id, "kitchen-1", Utc::now()).await, Err(ApiError::OrderNotFound)));
+        assert_eq!(dao.count_unfinished_orders().await.unwrap(), 0);
+        assert!(dao.get_kitchen_queue(None, 10).await.unwrap().is_empty());
+        assert!(dao.count_open_orders_by_table().await.unwrap().is_empty());
+
+        // adding orders without open session opens a session implicitly
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+        let second = dao.get_current_session(1).await.unwrap();
+        assert_eq!(second.guest_count, None);
+        let orders = dao.get_table_orders(1, None).await.unwrap();
+        assert_eq!(orders.len(), 1);
+
+        // the session can be closed once its orders are removed
+        dao.remove_order(1, orders[0].order_id, None, Some("customer_changed_mind"), Utc::now(), &[]).await.unwrap();
+        let second = dao.close_current_session(1, Utc::now()).await.unwrap();
+
+        // past sessions are still queryable
+        assert_eq!(dao.get_table_sessions(1).await.unwrap(), vec![closed, second]);
+        assert_eq!(dao.get_session_orders(1, first.session_id, None).await.unwrap().len(), 1);
+        assert!(matches!(dao.get_session_orders(2, first.session_id, None).await, Err(ApiError::SessionNotFound)));
+    }
+}
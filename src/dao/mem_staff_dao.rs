@@ -0,0 +1,81 @@
+use crate::dao::staff_dao::StaffDAO;
+use crate::dao::mem_order_dao::MemTableOrderDAO;
+use crate::model::StaffMember;
+use crate::error::ApiError;
+
+
+impl StaffDAO for MemTableOrderDAO{
+    async fn add_staff(&self, staff: &StaffMember) -> Result<StaffMember, ApiError> {
+        let mut store = self.lock()?;
+
+        if store.staff.contains_key(&staff.staff_id){
+            return Err(ApiError::StaffAlreadyExists);
+        }
+        store.staff.insert(staff.staff_id.clone(), staff.clone());
+        Ok(staff.clone())
+    }
+
+
+    async fn get_staff(&self) -> Result<Vec<StaffMember>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.staff.values().cloned().collect())
+    }
+
+
+    async fn get_staff_member(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        let store = self.lock()?;
+        store.staff.get(staff_id).cloned().ok_or(ApiError::StaffNotFound)
+    }
+
+
+    async fn deactivate_staff(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        let mut store = self.lock()?;
+        let staff = store.staff.get_mut(staff_id).ok_or(ApiError::StaffNotFound)?;
+        staff.active = false;
+        Ok(staff.clone())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use chrono::Utc;
+
+    use crate::dao::staff_dao::StaffDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::model::{Role, StaffMember};
+
+
+    fn staff_member(staff_id: &str, role: Role) -> StaffMember{
+        StaffMember{
+            staff_id: staff_id.to_string(),
+            name: staff_id.to_string(),
+            role,
+            pin_hash: "hash".to_string(),
+            pin_salt: "salt".to_string(),
+            active: true,
+            creation_time: Utc::now()
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_staff(){
+        let dao = MemTableOrderDAO::new();
+
+        dao.add_staff(&staff_member("waiter-1", Role::Waiter)).await.unwrap();
+        dao.add_staff(&staff_member("chef-1", Role::Kitchen)).await.unwrap();
+        assert!(matches!(dao.add_staff(&staff_member("waiter-1", Role::Manager)).await, Err(ApiError::StaffAlreadyExists)));
+
+        assert_eq!(dao.get_staff_member("waiter-1").await.unwrap().role, Role::Waiter);
+        assert!(matches!(dao.get_staff_member("unknown").await, Err(ApiError::StaffNotFound)));
+
+        assert!(!dao.deactivate_staff("waiter-1").await.unwrap().active);
+        assert!(matches!(dao.deactivate_staff("unknown").await, Err(ApiError::StaffNotFound)));
+
+        let staff = dao.get_staff().await.unwrap();
+        assert_eq!(staff.iter().map(|staff| staff.staff_id.as_str()).collect::<Vec<_>>(), vec!["chef-1", "waiter-1"]);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::dao::table_dao::TableDAO;
+use crate::dao::mem_order_dao::{MemTableOrderDAO, MemStore};
+use crate::model::{RestaurantTable, TableRequest};
+use crate::error::ApiError;
+
+
+impl MemTableOrderDAO{
+    /// helper function to check that table has no orders before it is deactivated or removed
+    fn check_table_without_orders(store: &MemStore, table_id: i16) -> Result<(), ApiError>{
+        if store.orders.values().any(|order| order.table_id == table_id){
+            Err(ApiError::TableInUse)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+impl TableDAO for MemTableOrderDAO{
+    async fn add_table(&self, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        let mut store = self.lock()?;
+        if store.last_table_id == i16::MAX{
+            return Err(ApiError::BadRequest("no more table ids are available".to_string()));
+        }
+
+        store.last_table_id += 1;
+        let table = RestaurantTable{
+            table_id: store.last_table_id,
+            label: table.label.clone(),
+            capacity: table.capacity,
+            section: table.section.clone(),
+            active: table.active
+        };
+        store.tables.insert(table.table_id, table.clone());
+        Ok(table)
+    }
+
+
+    async fn get_tables(&self) -> Result<Vec<RestaurantTable>, ApiError> {
+        let store = self.lock()?;
+        Ok(store.tables.values().cloned().collect())
+    }
+
+
+    async fn get_table(&self, table_id: i16) -> Result<RestaurantTable, ApiError> {
+        let store = self.lock()?;
+        store.tables.get(&table_id)
+            .cloned()
+            .ok_or(ApiError::TableNotFound)
+    }
+
+
+    async fn update_table(&self, table_id: i16, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        let mut store = self.lock()?;
+        if !store.tables.contains_key(&table_id){
+            return Err(ApiError::TableNotFound);
+        }
+
+        if !table.active{
+            Self::check_table_without_orders(&store, table_id)?;
+        }
+
+        let table = RestaurantTable{
+            table_id,
+            label: table.label.clone(),
+            capacity: table.capacity,
+            section: table.section.clone(),
+            active: table.active
+        };
+        store.tables.insert(table_id, table.clone());
+        Ok(table)
+    }
+
+
+    async fn remove_table(&self, table_id: i16) -> Result<(), ApiError> {
+        let mut store = self.lock()?;
+        if !store.tables.contains_key(&table_id){
+            return Err(ApiError::TableNotFound);
+        }
+
+        Self::check_table_without_orders(&store, table_id)?;
+        store.tables.remove(&table_id);
+        Ok(())
+    }
+
+
+    async fn get_orders_version(&self, table_id: i16) -> Result<i64, ApiError> {
+        let store = self.lock()?;
+        if !store.tables.contains_key(&table_id){
+            return Err(ApiError::TableNotFound);
+        }
+
+        Ok(store.orders_versions.get(&table_id).copied().unwrap_or(0))
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use crate::dao::table_dao::{TableDAO, DEFAULT_TABLE_COUNT};
+    use crate::dao::order_dao::TableOrderDAO;
+    use crate::dao::mem_order_dao::MemTableOrderDAO;
+    use crate::error::ApiError;
+    use crate::handlers::process_order_requests;
+    use crate::model::{TableRequest, TableOrdersRequest, OrderStatus};
+
+
+    #[tokio::test]
+    async fn test_table_crud(){
+        let dao = MemTableOrderDAO::new();
+        assert_eq!(dao.get_tables().await.unwrap().len(), DEFAULT_TABLE_COUNT as usize);
+        assert_eq!(dao.get_table(1).await.unwrap().label, "Table 1");
+
+        let patio = dao.add_table(&TableRequest::new("Patio 3", 2, "patio")).await.unwrap();
+        assert_eq!(patio.table_id, DEFAULT_TABLE_COUNT + 1);
+        assert!(patio.active);
+
+        let mut request = TableRequest::new("Patio 3", 6, "patio");
+        request.active = false;
+        let updated = dao.update_table(patio.table_id, &request).await.unwrap();
+        assert_eq!(updated.capacity, 6);
+        assert!(!updated.active);
+        assert_eq!(dao.get_table(patio.table_id).await.unwrap(), updated);
+
+        dao.remove_table(patio.table_id).await.unwrap();
+        assert!(matches!(dao.get_table(patio.table_id).await, Err(ApiError::TableNotFound)));
+        assert!(matches!(dao.remove_table(patio.table_id).await, Err(ApiError::TableNotFound)));
+        assert!(matches!(dao.update_table(patio.table_id, &request).await, Err(ApiError::TableNotFound)));
+    }
+
+
+    #[tokio::test]
+    async fn test_table_with_orders_in_use(){
+        let dao = MemTableOrderDAO::new();
+
+        let mut table_orders = TableOrdersRequest::new(5);
+        table_orders.add_order_wihtout_note("Kapao");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+        let mut request = TableRequest::new("Table 5", 4, "main");
+        request.active = false;
+        assert!(matches!(dao.update_table(5, &request).await, Err(ApiError::TableInUse)));
+        assert!(matches!(dao.remove_table(5).await, Err(ApiError::TableInUse)));
+
+        // attributes of table in use can still be changed
+        request.active = true;
+        request.capacity = 8;
+        assert_eq!(dao.update_table(5, &request).await.unwrap().capacity, 8);
+    }
+
+
+    #[tokio::test]
+    async fn test_orders_version(){
+        let dao = MemTableOrderDAO::new();
+        assert_eq!(dao.get_orders_version(5).await.unwrap(), 0);
+        assert!(matches!(dao.get_orders_version(1000).await, Err(ApiError::TableNotFound)));
+
+        let mut table_orders = TableOrdersRequest::new(5);
+        table_orders.add_order_wihtout_note("Kapao");
+        dao.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+        let version = dao.get_orders_version(5).await.unwrap();
+        assert!(version > 0);
+        assert_eq!(dao.get_orders_version(6).await.unwrap(), 0);
+
+        // change with an outdated version is rejected without changing anything, and the same version matches only once
+        assert!(matches!(dao.update_order_status(5, 1, OrderStatus::Cooking, &[(5, version - 1)]).await, Err(ApiError::PreconditionFailed)));
+        assert_eq!(dao.get_orders_version(5).await.unwrap(), version);
+
+        dao.update_order_status(5, 1, OrderStatus::Cooking, &[(5, version)]).await.unwrap();
+        assert!(matches!(dao.update_order_status(5, 1, OrderStatus::Ready, &[(5, version)]).await, Err(ApiError::PreconditionFailed)));
+        assert!(dao.get_orders_version(5).await.unwrap() > version);
+    }
+}
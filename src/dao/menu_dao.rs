@@ -0,0 +1,37 @@
+
+use trait_variant;
+use crate::model::{MenuItem, MenuItemRequest};
+use crate::error::ApiError;
+
+/// trait for menu catalog access object. contains add / get / update / remove menu item functions.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (MenuDAO)
+#[trait_variant::make(MenuDAO: Send)]
+pub trait LocalMenuDAO: Clone + Sync + 'static{
+    /// function for adding new menu item. It returns the created item (with menu_item_id)
+    async fn add_menu_item(&self, item: &MenuItemRequest) -> Result<MenuItem, ApiError>;
+
+    /// function to get all menu items (ordered by menu_item_id)
+    async fn get_menu_items(&self) -> Result<Vec<MenuItem>, ApiError>;
+
+    /// function to get specific menu item
+    async fn get_menu_item(&self, menu_item_id: i32) -> Result<MenuItem, ApiError>;
+
+    /// function to get menu items by names (unknown names are simply not included in the result)
+    async fn get_menu_items_by_names(&self, names: &[String]) -> Result<Vec<MenuItem>, ApiError>;
+
+    /// function to replace all attributes of specific menu item. It returns the updated item
+    async fn update_menu_item(&self, menu_item_id: i32, item: &MenuItemRequest) -> Result<MenuItem, ApiError>;
+
+    /// function to remove specific menu item. Menu item referenced by open (not removed or billed) orders can't be removed (ApiError::MenuItemInUse)
+    async fn remove_menu_item(&self, menu_item_id: i32) -> Result<(), ApiError>;
+}
+
+
+/// utility function to map sqlx error of menu queries to ApiError
+pub(crate) fn map_menu_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::MenuItemNotFound,
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => ApiError::MenuItemAlreadyExists,
+        _ => ApiError::DatabaseError(err),
+    }
+}
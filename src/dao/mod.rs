@@ -1,2 +1,56 @@
 pub mod order_dao;
-pub mod pg_order_dao;
\ No newline at end of file
+pub mod menu_dao;
+pub mod bill_dao;
+pub mod table_dao;
+pub mod session_dao;
+pub mod idempotency_dao;
+pub mod api_key_dao;
+pub mod staff_dao;
+pub mod pg_order_dao;
+pub mod pg_menu_dao;
+pub mod pg_bill_dao;
+pub mod pg_table_dao;
+pub mod pg_session_dao;
+pub mod pg_idempotency_dao;
+pub mod pg_api_key_dao;
+pub mod pg_staff_dao;
+pub mod mem_order_dao;
+pub mod mem_menu_dao;
+pub mod mem_bill_dao;
+pub mod mem_table_dao;
+pub mod mem_session_dao;
+pub mod mem_idempotency_dao;
+pub mod mem_api_key_dao;
+pub mod mem_staff_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_order_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_menu_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_bill_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_table_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_session_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_idempotency_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_api_key_dao;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_staff_dao;
+
+
+use order_dao::TableOrderDAO;
+use menu_dao::MenuDAO;
+use bill_dao::BillDAO;
+use table_dao::TableDAO;
+use session_dao::SessionDAO;
+use idempotency_dao::IdempotencyDAO;
+use api_key_dao::ApiKeyDAO;
+use staff_dao::StaffDAO;
+
+/// Umbrella trait of all DAO traits required by ApiContext and handlers. 
+/// It is implemented automatically for any type implementing every DAO trait
+pub trait StorageDAO: TableOrderDAO + MenuDAO + BillDAO + TableDAO + SessionDAO + IdempotencyDAO + ApiKeyDAO + StaffDAO {}
+
+impl<T: TableOrderDAO + MenuDAO + BillDAO + TableDAO + SessionDAO + IdempotencyDAO + ApiKeyDAO + StaffDAO> StorageDAO for T {}
@@ -1,21 +1,155 @@
+use std::collections::BTreeSet;
 
+use chrono::{DateTime, Utc};
 use trait_variant;
-use crate::model::OrderItem;
+use crate::model::{OrderItem, OrderStatus};
 use crate::error::ApiError;
 
-/// trait for database access object. contains add / get / remove order record(s) functions 
-#[trait_variant::make(HttpService: Send)]
-pub trait TableOrderDAO{
-    /// function for adding OrderItems to table (each item already contains table_id)
-    async fn add_table_orders(&self, items: &[OrderItem]) ->  Result<(), ApiError> ;
+/// trait for database access object. contains add / get / remove order record(s) functions.
+/// Implementations should implement the Send variant (TableOrderDAO) so that they can be used as shared state by axum handlers.
+/// Functions changing orders take expected versions of tables' orders (table id and version from `If-Match`), which are compared in the same transaction as the change.
+/// They return ApiError::PreconditionFailed (and change nothing) if any version doesn't match
+/// Orders of closed seating sessions are kept only for history, so functions reading or changing a specific order, and the kitchen queue and counts
+/// only see orders of open sessions (ApiError::OrderNotFound for an order of a closed session)
+#[trait_variant::make(TableOrderDAO: Send)]
+pub trait LocalTableOrderDAO: Clone + Sync + 'static{
+    /// function to check whether the underlying storage is alive or not
+    async fn health_check(&self) -> Result<(), ApiError>;
 
-    /// function to get all OrderItems for specific table_id
-    async fn get_table_orders(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError>;
+    /// function to get connection usage of the underlying database pool (None if storage has no pool)
+    fn pool_usage(&self) -> Option<PoolUsage>;
+
+    /// function for adding OrderItems to table (each item already contains table_id).
+    /// Orders are attached to the open seating session of the table, which is opened implicitly if there is none
+    async fn add_table_orders(&self, items: &[OrderItem], expected_versions: &[(i16, i64)]) ->  Result<(), ApiError> ;
+
+    /// function to get all OrderItems of the open seating session of specific table_id (empty if no session is open).
+    /// If status is specified, only orders in that status are returned
+    async fn get_table_orders(&self, table_id: i16, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError>;
 
     /// function to get specific OrderItem (in a vec for simplicity for caller) in the specific table
     async fn get_specific_table_order(&self, table_id: i16, order_id: i32) -> Result<Vec<OrderItem>, ApiError>;
 
-    /// function to remove specific OrderItem from DB
-    async fn remove_order(&self, table_id: i16, order_id: i32) -> Result<(), ApiError>;
+    /// function to remove specific OrderItem. The order is soft deleted (kept with deletion time, remover and reason for order history),
+    /// and it isn't returned by other functions anymore
+    async fn remove_order(&self, table_id: i16, order_id: i32, deleted_by: Option<&str>, reason: Option<&str>, deletion_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>;
+
+    /// function to get all OrderItems of the open seating session of specific table_id including removed ones (empty if no session is open)
+    async fn get_table_order_history(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError>;
+
+    /// function to change status of specific OrderItem. It returns ApiError::IllegalStatusTransition if the transition is not allowed
+    async fn update_order_status(&self, table_id: i16, order_id: i32, status: OrderStatus, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>;
+
+    /// function to decrement quantity of specific OrderItem. The order is removed (soft deleted with its last quantity, remover and reason) when its quantity reaches 0.
+    /// It returns ApiError::BadRequest if the decrement is larger than the current quantity, or if it removes the order without a reason
+    async fn decrement_order_quantity(&self, table_id: i16, order_id: i32, quantity: i32, deleted_by: Option<&str>, reason: Option<&str>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>;
+
+    /// function to record that a device has seen specific OrderItem. Only the first acknowledgement is kept,
+    /// so acknowledging the same order again has no effect
+    async fn acknowledge_order(&self, table_id: i16, order_id: i32, device_id: &str, acknowledgement_time: DateTime<Utc>) -> Result<(), ApiError>;
+
+    /// function to move specific OrderItems of the open seating session of source table to target table in one transaction.
+    /// Moved orders join the open session of target table (opened implicitly if there is none).
+    /// It returns ApiError::OrderNotFound if any order is not in the open session of source table (nothing is moved then)
+    async fn transfer_orders(&self, source_table_id: i16, target_table_id: i16, order_ids: &[i32], transfer_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>;
+
+    /// function to move all OrderItems of the open seating session of source table to target table, and close the source session in one transaction
+    /// (e.g., two tables are pushed together). It returns ApiError::BadRequest if source table has no orders
+    async fn merge_tables(&self, source_table_id: i16, target_table_id: i16, merge_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>;
+
+    /// function to count unfinished (ordered or cooking) orders of all tables, i.e., length of the kitchen queue
+    async fn count_unfinished_orders(&self) -> Result<i64, ApiError>;
+
+    /// function to get open (ordered, cooking or ready) orders of all tables sorted by estimated arrival time, then creation time.
+    /// If category is specified, only orders of menu items in that category are returned
+    async fn get_kitchen_queue(&self, category: Option<&str>, limit: i64) -> Result<Vec<OrderItem>, ApiError>;
+
+    /// function to count open (ordered, cooking or ready) orders of each table (table id, count), sorted by table id.
+    /// Tables without open orders are not returned
+    async fn count_open_orders_by_table(&self) -> Result<Vec<(i16, i64)>, ApiError>;
+}
+
+
+/// Connection usage of database pool (see TableOrderDAO::pool_usage)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PoolUsage{
+    /// open connections (idle or in use)
+    pub size: u32,
+
+    /// idle connections
+    pub idle: u32,
+
+    /// maximum connections of the pool
+    pub max_size: u32
+}
+
+
+/// utility function to map sqlx error (shared by sql based DAO implementations) to ApiError
+pub(crate) fn map_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::OrderNotFound,
+        _ => ApiError::DatabaseError(err),
+    }
+}
+
+
+/// utility function for get specific order. It returns ApiError::OrderNotFound if returning result is 0. Otherwise, OK with query result
+#[allow(clippy::len_zero)]
+pub(crate) fn is_existing_order(orders: Vec<OrderItem>) -> Result<Vec<OrderItem>, ApiError>{
+    if orders.len() == 0{
+        Err(ApiError::OrderNotFound)
+    }else{
+        Ok(orders)
+    }
+}
+
+
+/// utility function to validate status transition (shared by DAO implementations). 
+/// It returns ApiError::IllegalStatusTransition if current status can't be changed to next status
+pub(crate) fn check_status_transition(current: OrderStatus, next: OrderStatus) -> Result<(), ApiError>{
+    if current.can_transition_to(next){
+        Ok(())
+    }else{
+        Err(ApiError::IllegalStatusTransition(current, next))
+    }
+}
+
+
+/// utility function to compute remaining quantity after decrement (shared by DAO implementations). 
+/// It returns ApiError::BadRequest if decrement is larger than current quantity
+pub(crate) fn remaining_quantity_after_decrement(current: i32, decrement: i32) -> Result<i32, ApiError>{
+    if decrement > current{
+        Err(ApiError::BadRequest(format!("cannot remove {decrement} items from order with quantity {current}")))
+    }else{
+        Ok(current - decrement)
+    }
 }
 
+
+/// utility function to get reason of removing an order by decrementing its quantity to 0 (shared by DAO implementations).
+/// It returns ApiError::BadRequest if there is no reason, since removals are voids and must be explained in order history
+pub(crate) fn reason_for_removal_by_decrement(reason: Option<&str>) -> Result<&str, ApiError>{
+    reason.ok_or_else(|| ApiError::BadRequest("removing all items of an order requires a void reason".to_string()))
+}
+
+
+/// utility function to check that every requested order is moved (shared by DAO implementations).
+/// It returns ApiError::OrderNotFound if any order is not in the source table, so that the caller rolls back the transfer
+pub(crate) fn check_transferred_orders(order_ids: &[i32], moved_count: usize) -> Result<(), ApiError>{
+    let requested: BTreeSet<&i32> = order_ids.iter().collect();
+    if requested.len() == moved_count{
+        Ok(())
+    }else{
+        Err(ApiError::OrderNotFound)
+    }
+}
+
+
+/// utility function to check that source table of merge has orders (shared by DAO implementations)
+pub(crate) fn check_merged_orders(source_table_id: i16, moved_count: usize) -> Result<(), ApiError>{
+    if moved_count == 0{
+        Err(ApiError::BadRequest(format!("table {source_table_id} has no orders to merge")))
+    }else{
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::api_key_dao::{ApiKeyDAO, map_api_key_sqlx_error_to_api_error, check_valid_api_key};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::{ApiKey, Role};
+use crate::error::ApiError;
+
+
+/// API keys are stored in the same postgres database as orders, so the postgres DAO implements ApiKeyDAO as well
+impl ApiKeyDAO for PgTableOrderDAO{
+    async fn add_api_key(&self, name: &str, role: Role, key_hash: &str, creation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("INSERT INTO api_keys(name, role, key_hash, creation_time) VALUES($1, $2, $3, $4) RETURNING *")
+            .bind(name)
+            .bind(role)
+            .bind(key_hash)
+            .bind(creation_time)
+            .fetch_one(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, ApiError> {
+        sqlx::query_as("SELECT * FROM api_keys ORDER BY api_key_id")
+            .fetch_all(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+    }
+
+
+    async fn authenticate_api_key(&self, key_hash: &str) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("SELECT * FROM api_keys WHERE key_hash = $1")
+            .bind(key_hash)
+            .fetch_optional(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+            .and_then(check_valid_api_key)
+    }
+
+
+    async fn revoke_api_key(&self, api_key_id: i32, revocation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("UPDATE api_keys SET revocation_time = COALESCE(revocation_time, $2) WHERE api_key_id = $1 RETURNING *")
+            .bind(api_key_id)
+            .bind(revocation_time)
+            .fetch_one(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::bill_dao::{BillDAO, BILLED_ORDER_REASON, build_bill, map_bill_sqlx_error_to_api_error};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::{Bill, BillCharges, BillLineItem, MenuItem, OrderItem};
+use crate::error::ApiError;
+use crate::events::OrderEventType;
+use sqlx::Postgres;
+
+
+/// Bills are stored in the same postgres database as orders, so checkout can be done in a single transaction
+impl BillDAO for PgTableOrderDAO{
+    async fn checkout_table(&self, table_id: i16, charges: BillCharges, creation_time: DateTime<Utc>) -> Result<Bill, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        // lock the current seating session and its orders so that orders can't be added, changed (or billed twice) during checkout
+        PgTableOrderDAO::lock_open_session(&mut transaction, table_id).await?;
+        let orders: Vec<OrderItem> = sqlx::query_as("SELECT o.* FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = $1 and s.close_time IS NULL and o.deleted_at IS NULL ORDER BY o.order_id FOR UPDATE OF o")
+            .bind(table_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        let names: Vec<String> = orders.iter().map(|order| order.item_name.clone()).collect();
+        let menu_items: Vec<MenuItem> = sqlx::query_as("SELECT * FROM menu_items WHERE name = ANY($1)")
+            .bind(&names)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        let mut bill = build_bill(table_id, &orders, &menu_items, charges, creation_time)?;
+
+        bill.bill_id = sqlx::query_scalar("INSERT INTO bills(table_id, subtotal, service_charge, tax, total, creation_time) VALUES($1, $2, $3, $4, $5, $6) RETURNING bill_id")
+            .bind(bill.table_id)
+            .bind(bill.subtotal)
+            .bind(bill.service_charge)
+            .bind(bill.tax)
+            .bind(bill.total)
+            .bind(bill.creation_time)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        if !bill.line_items.is_empty(){
+            let mut query_builder: sqlx::QueryBuilder<'_, Postgres> = sqlx::QueryBuilder::new("INSERT INTO bill_items(bill_id, order_id, item_name, quantity, unit_price, amount) ");
+            query_builder.push_values(&bill.line_items, |mut binder, line| {
+                binder.push_bind(bill.bill_id)
+                    .push_bind(line.order_id)
+                    .push_bind(&line.item_name)
+                    .push_bind(line.quantity)
+                    .push_bind(line.unit_price)
+                    .push_bind(line.amount);
+            });
+
+            query_builder.build().execute(&mut *transaction)
+                .await
+                .map_err(map_bill_sqlx_error_to_api_error)?;
+        }
+
+        // only the locked (billed) orders are removed. They are soft deleted, so the rows are kept for reference from bill items
+        let order_ids: Vec<i32> = orders.iter().map(|order| order.order_id).collect();
+        sqlx::query("UPDATE ORDERS SET deleted_at = $2, reason = $3 WHERE order_id = ANY($1)")
+            .bind(&order_ids)
+            .bind(creation_time)
+            .bind(BILLED_ORDER_REASON)
+            .execute(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        // the party has paid, so its seating session is closed
+        sqlx::query("UPDATE table_sessions SET close_time = $2 WHERE table_id = $1 and close_time IS NULL")
+            .bind(table_id)
+            .bind(creation_time)
+            .execute(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::TableCheckedOut, table_id, None).await?;
+
+        transaction.commit().await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        Ok(bill)
+    }
+
+
+    async fn get_bill(&self, bill_id: i32) -> Result<Bill, ApiError> {
+        let mut bill: Bill = sqlx::query_as("SELECT * FROM bills WHERE bill_id = $1")
+            .bind(bill_id)
+            .fetch_one(&self.db).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        bill.line_items = sqlx::query_as::<_, BillLineItem>("SELECT * FROM bill_items WHERE bill_id = $1 ORDER BY order_id")
+            .bind(bill_id)
+            .fetch_all(&self.db).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        Ok(bill)
+    }
+}
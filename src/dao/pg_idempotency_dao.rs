@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::idempotency_dao::{IdempotencyDAO, IdempotencyRecord, IdempotencyReservation, idempotency_key_expiry, idempotency_lease_expiry, check_idempotency_record};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::error::ApiError;
+
+
+/// Idempotency keys are stored in the same postgres database as orders (shared by all server instances),
+/// so the postgres DAO implements IdempotencyDAO as well
+impl IdempotencyDAO for PgTableOrderDAO{
+    async fn reserve_idempotency_key(&self, idempotency_key: &str, request_fingerprint: &str, reservation_time: DateTime<Utc>) -> Result<IdempotencyReservation, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(ApiError::DatabaseError)?;
+
+        sqlx::query("DELETE FROM idempotency_keys WHERE creation_time < $1")
+            .bind(idempotency_key_expiry(reservation_time))
+            .execute(&mut *transaction).await
+            .map_err(ApiError::DatabaseError)?;
+
+        // concurrent reservations of the same key wait for each other on the primary key, so only one of them is reserved
+        let inserted = sqlx::query("INSERT INTO idempotency_keys(idempotency_key, request_fingerprint, creation_time) VALUES($1, $2, $3) ON CONFLICT DO NOTHING")
+            .bind(idempotency_key)
+            .bind(request_fingerprint)
+            .bind(reservation_time)
+            .execute(&mut *transaction).await
+            .map_err(ApiError::DatabaseError)?
+            .rows_affected();
+
+        // unfinished request of the same fingerprint is taken over once its lease has expired
+        let taken_over = match inserted{
+            0 => sqlx::query("UPDATE idempotency_keys SET creation_time = $3 \
+                    WHERE idempotency_key = $1 and request_fingerprint = $2 and status_code IS NULL and creation_time < $4")
+                .bind(idempotency_key)
+                .bind(request_fingerprint)
+                .bind(reservation_time)
+                .bind(idempotency_lease_expiry(reservation_time))
+                .execute(&mut *transaction).await
+                .map_err(ApiError::DatabaseError)?
+                .rows_affected(),
+            _ => 0
+        };
+
+        let reservation = match inserted + taken_over{
+            0 => sqlx::query_as::<_, IdempotencyRecord>("SELECT * FROM idempotency_keys WHERE idempotency_key = $1")
+                .bind(idempotency_key)
+                .fetch_one(&mut *transaction).await
+                .map_err(ApiError::DatabaseError)
+                .and_then(|record| check_idempotency_record(record, request_fingerprint))?,
+            _ => IdempotencyReservation::Reserved
+        };
+
+        transaction.commit().await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| reservation)
+    }
+
+
+    async fn complete_idempotency_key(&self, idempotency_key: &str, status_code: u16, etag: Option<&str>, response_body: &str) -> Result<(), ApiError> {
+        sqlx::query("UPDATE idempotency_keys SET status_code = $2, etag = $3, response_body = $4 WHERE idempotency_key = $1")
+            .bind(idempotency_key)
+            .bind(status_code as i16)
+            .bind(etag)
+            .bind(response_body)
+            .execute(&self.db).await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+
+
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE idempotency_key = $1 and status_code IS NULL")
+            .bind(idempotency_key)
+            .execute(&self.db).await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+}
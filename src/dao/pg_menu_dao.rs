@@ -0,0 +1,91 @@
+use crate::dao::menu_dao::{MenuDAO, map_menu_sqlx_error_to_api_error};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::{MenuItem, MenuItemRequest};
+use crate::error::ApiError;
+use sqlx::{Postgres, Transaction};
+
+
+impl PgTableOrderDAO{
+    /// helper function to check that menu item isn't referenced by open orders (by id, or by name for orders without menu snapshot) before it is removed
+    async fn check_menu_item_without_orders(transaction: &mut Transaction<'static, Postgres>, menu_item_id: i32, name: &str) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM ORDERS WHERE (menu_item_id = $1 or item_name = $2) and deleted_at IS NULL)")
+            .bind(menu_item_id)
+            .bind(name)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_menu_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::MenuItemInUse)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Menu catalog is stored in the same postgres database as orders, so the postgres DAO implements MenuDAO as well
+impl MenuDAO for PgTableOrderDAO{
+    async fn add_menu_item(&self, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("INSERT INTO menu_items(name, category, price, prep_time_minutes, active) VALUES($1, $2, $3, $4, $5) RETURNING *")
+            .bind(&item.name)
+            .bind(&item.category)
+            .bind(item.price)
+            .bind(item.prep_time_minutes)
+            .bind(item.active)
+            .fetch_one(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_menu_items(&self) -> Result<Vec<MenuItem>, ApiError> {
+        sqlx::query_as("SELECT * FROM menu_items ORDER BY menu_item_id")
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_menu_item(&self, menu_item_id: i32) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("SELECT * FROM menu_items WHERE menu_item_id = $1")
+            .bind(menu_item_id)
+            .fetch_one(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_menu_items_by_names(&self, names: &[String]) -> Result<Vec<MenuItem>, ApiError> {
+        sqlx::query_as("SELECT * FROM menu_items WHERE name = ANY($1) ORDER BY menu_item_id")
+            .bind(names)
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_menu_item(&self, menu_item_id: i32, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("UPDATE menu_items SET name = $2, category = $3, price = $4, prep_time_minutes = $5, active = $6 WHERE menu_item_id = $1 RETURNING *")
+            .bind(menu_item_id)
+            .bind(&item.name)
+            .bind(&item.category)
+            .bind(item.price)
+            .bind(item.prep_time_minutes)
+            .bind(item.active)
+            .fetch_one(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn remove_menu_item(&self, menu_item_id: i32) -> Result<(), ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_menu_sqlx_error_to_api_error)?;
+
+        let name: String = sqlx::query_scalar("DELETE FROM menu_items WHERE menu_item_id = $1 RETURNING name")
+            .bind(menu_item_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_menu_sqlx_error_to_api_error)?;
+
+        Self::check_menu_item_without_orders(&mut transaction, menu_item_id, &name).await?;
+
+        transaction.commit().await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+}
@@ -1,40 +1,142 @@
-use crate::dao::order_dao::TableOrderDAO;
-use crate::model::OrderItem;
+use crate::dao::table_dao::check_matched_orders_version;
+use crate::dao::order_dao::{TableOrderDAO, PoolUsage, map_sqlx_error_to_api_error, is_existing_order, check_status_transition, remaining_quantity_after_decrement, reason_for_removal_by_decrement,
+    check_transferred_orders, check_merged_orders};
+use crate::model::{OrderItem, OrderStatus};
 use crate::error::ApiError;
+use crate::metrics::dao_query_timer;
+use crate::events::OrderEventType;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{Postgres, Transaction, PgPool};
 
+
+/// postgres channel of order change notifications, which are relayed to streaming clients of every server instance
+pub const ORDER_EVENTS_CHANNEL: &str = "order_events";
+
+
+/// Payload of order change notification (json). Orders are not included because NOTIFY payload is limited to 8000 bytes,
+/// so listening instances fetch the table's orders by themselves
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderNotification{
+    /// server instance which made the change (it has already published the event to its own clients)
+    pub instance_id: String,
+
+    /// type of the change
+    pub event_type: OrderEventType,
+
+    /// table of the changed orders
+    pub table_id: i16,
+
+    /// changed order (None if several orders are changed)
+    pub order_id: Option<i32>
+}
+
+
 #[derive(Clone)]
 /// Implementation of order DAO
 pub struct PgTableOrderDAO{
-    pub db: PgPool
-}
+    pub db: PgPool,
 
-
-fn map_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
-    match err{
-        sqlx::Error::RowNotFound => ApiError::OrderNotFound,
-        _ => ApiError::DatabaseError(err),
-    }
+    /// random id of this server instance, which is sent with order change notifications
+    pub instance_id: String
 }
 
+
 impl PgTableOrderDAO{
     /// constructor to attach sqlx postgres pool (clonable) 
     pub fn new(db: PgPool) -> Self{
         Self{
-            db
+            db,
+            instance_id: format!("{:016x}", rand::random::<u64>())
+        }
+    }
+
+    /// helper function to notify order change to other server instances. It must be called in the transaction of the change,
+    /// so that the notification is delivered only if the change is committed
+    pub(crate) async fn notify_order_change(&self, transaction: &mut Transaction<'static, Postgres>, event_type: OrderEventType,
+        table_id: i16, order_id: Option<i32>) -> Result<(), ApiError>{
+
+        let notification = OrderNotification{
+            instance_id: self.instance_id.clone(),
+            event_type,
+            table_id,
+            order_id
+        };
+        let payload = serde_json::to_string(&notification)
+            .map_err(|err| ApiError::ServerError(format!("Couldn't serialize order notification: {err}")))?;
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(ORDER_EVENTS_CHANNEL)
+            .bind(payload)
+            .execute(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+            .map(|_| ())
+    }
+
+    /// helper function to compare expected versions of tables' orders (If-Match) in the transaction of the change. The compared rows stay locked
+    /// until the transaction ends (locked in table id order to avoid deadlocks), and the versions are increased by the triggers of the change
+    pub(crate) async fn check_orders_versions(transaction: &mut Transaction<'static, Postgres>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>{
+        let mut expected_versions = expected_versions.to_vec();
+        expected_versions.sort_unstable();
+
+        for (table_id, version) in expected_versions{
+            sqlx::query("UPDATE restaurant_tables SET orders_version = orders_version WHERE table_id = $1 and orders_version = $2")
+                .bind(table_id)
+                .bind(version)
+                .execute(&mut **transaction).await
+                .map_err(map_sqlx_error_to_api_error)
+                .and_then(|result| check_matched_orders_version(result.rows_affected()))?;
+        }
+        Ok(())
+    }
+
+    /// helper function to get the open seating session of a table in the transaction. A session is opened implicitly if the table has none
+    /// (concurrent implicit openings are resolved by the unique index of open sessions).
+    /// The session row is share locked until the transaction ends, so that the session can't be closed (see lock_open_session) before orders are added to it
+    async fn current_or_new_session_id(transaction: &mut Transaction<'static, Postgres>, table_id: i16, open_time: DateTime<Utc>) -> Result<i32, ApiError>{
+        let find_open_session = || sqlx::query_scalar("SELECT session_id FROM table_sessions WHERE table_id = $1 and close_time IS NULL FOR SHARE")
+            .bind(table_id);
+
+        if let Some(session_id) = find_open_session().fetch_optional(&mut **transaction).await.map_err(map_sqlx_error_to_api_error)?{
+            return Ok(session_id);
         }
+
+        sqlx::query("INSERT INTO table_sessions(table_id, open_time) VALUES($1, $2) ON CONFLICT DO NOTHING")
+            .bind(table_id)
+            .bind(open_time)
+            .execute(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        find_open_session().fetch_one(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+    /// helper function to lock the open seating session of a table (if any) before reading its orders and closing it in the transaction.
+    /// Adding orders to the session waits until the transaction ends, and then adds them to a new session if the session is closed
+    pub(crate) async fn lock_open_session(transaction: &mut Transaction<'static, Postgres>, table_id: i16) -> Result<Option<i32>, ApiError>{
+        sqlx::query_scalar("SELECT session_id FROM table_sessions WHERE table_id = $1 and close_time IS NULL FOR UPDATE")
+            .bind(table_id)
+            .fetch_optional(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
     }
 
     /// helper function to build, and execute insert orders query (using bulk insert for performance but can be changed) 
-    async fn execute_insert_orders(&self, transaction: &mut Transaction<'static, Postgres>, items: &[OrderItem]) -> Result<(), ApiError>{
+    #[allow(clippy::needless_borrows_for_generic_args, clippy::bind_instead_of_map)]
+    async fn execute_insert_orders(&self, transaction: &mut Transaction<'static, Postgres>, items: &[OrderItem], session_id: i32) -> Result<(), ApiError>{
         // build bulk insert query (for performance)
-        let mut query_builder: sqlx::QueryBuilder<'_, Postgres> = sqlx::QueryBuilder::new("INSERT INTO orders(table_id, item_name, note, creation_time, estimated_arrival_time) ");
+        let mut query_builder: sqlx::QueryBuilder<'_, Postgres> = sqlx::QueryBuilder::new("INSERT INTO orders(table_id, item_name, quantity, note, creation_time, estimated_arrival_time, status, session_id, created_by, menu_item_id, unit_price) ");
         query_builder.push_values(items, |mut binder, order| {
             binder.push_bind(&order.table_id)
                 .push_bind(&order.item_name)
+                .push_bind(order.quantity)
                 .push_bind(&order.note)
                 .push_bind(&order.creation_time)
-                .push_bind(&order.estimated_arrival_time);
+                .push_bind(&order.estimated_arrival_time)
+                .push_bind(order.status)
+                .push_bind(session_id)
+                .push_bind(&order.created_by)
+                .push_bind(order.menu_item_id)
+                .push_bind(order.unit_price);
         });
 
         query_builder.build().execute(&mut **transaction)
@@ -44,20 +146,49 @@ impl PgTableOrderDAO{
             .and_then(|_| Ok(()))
     }
 
-    
-    /// utility function for get specific order. It returns ApiError::OrderNotFound if returning result is 0. Otherwise, OK with query result
-    fn is_existing_order(orders: Vec<OrderItem>) -> Result<Vec<OrderItem>, ApiError>{
-        if orders.len() == 0{
-            Err(ApiError::OrderNotFound)
-        }else{
-            Ok(orders)
-        }
+    /// helper function to move orders of the open session of source table to the open session of target table in the transaction.
+    /// All orders of the session are moved if order_ids is None. It returns the number of moved orders
+    async fn execute_move_orders(transaction: &mut Transaction<'static, Postgres>, source_table_id: i16, target_table_id: i16,
+        order_ids: Option<&[i32]>, move_time: DateTime<Utc>) -> Result<usize, ApiError>{
+
+        let session_id = Self::current_or_new_session_id(transaction, target_table_id, move_time).await?;
+
+        sqlx::query_scalar::<_, i32>("UPDATE ORDERS SET table_id = $2, session_id = $3 \
+                WHERE table_id = $1 and session_id = (SELECT session_id FROM table_sessions WHERE table_id = $1 and close_time IS NULL) \
+                and deleted_at IS NULL and ($4::int[] IS NULL or order_id = ANY($4)) RETURNING order_id")
+            .bind(source_table_id)
+            .bind(target_table_id)
+            .bind(session_id)
+            .bind(order_ids)
+            .fetch_all(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+            .map(|moved| moved.len())
     }
 
 }
 
 impl TableOrderDAO for PgTableOrderDAO{
-    async fn add_table_orders(&self, items: &[OrderItem]) -> Result<(), ApiError> {
+    async fn health_check(&self) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("health_check");
+        sqlx::query("SELECT 1")
+            .execute(&self.db)
+            .await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+
+
+    fn pool_usage(&self) -> Option<PoolUsage> {
+        Some(PoolUsage{
+            size: self.db.size(),
+            idle: self.db.num_idle() as u32,
+            max_size: self.db.options().get_max_connections()
+        })
+    }
+
+
+    async fn add_table_orders(&self, items: &[OrderItem], expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("add_table_orders");
         
         // acquire transaction
         // we can  chain with the following statement but the code will be messier to my liking
@@ -65,18 +196,28 @@ impl TableOrderDAO for PgTableOrderDAO{
             .await
             .map_err(map_sqlx_error_to_api_error)?;
             
-        self.execute_insert_orders(&mut transaction, items).await
-            .and( 
-                transaction.commit().await
-                .map_err(map_sqlx_error_to_api_error)  // commit transaction
-            )
+        // orders of a request are always added to the same table
+        let Some(first_order) = items.first() else{
+            return Ok(());
+        };
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        let session_id = Self::current_or_new_session_id(&mut transaction, first_order.table_id, first_order.creation_time).await?;
+        self.execute_insert_orders(&mut transaction, items, session_id).await?;
+        self.notify_order_change(&mut transaction, OrderEventType::OrdersAdded, first_order.table_id, None).await?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)  // commit transaction
     }
 
 
-    async fn get_table_orders(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError> {
+    async fn get_table_orders(&self, table_id: i16, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_table_orders");
         
-        sqlx::query_as("SELECT * FROM ORDERS WHERE table_id = $1 ORDER BY order_id")
+        sqlx::query_as("SELECT o.* FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = $1 and s.close_time IS NULL and o.deleted_at IS NULL and ($2::order_status IS NULL or o.status = $2) ORDER BY o.order_id")
                 .bind(table_id)
+                .bind(status)
                 .fetch_all(&self.db)
                 .await
                 .map_err(map_sqlx_error_to_api_error)
@@ -84,34 +225,235 @@ impl TableOrderDAO for PgTableOrderDAO{
 
 
     async fn get_specific_table_order(&self, table_id: i16, order_id: i32) -> Result<Vec<OrderItem>, ApiError>{
+        let _timer = dao_query_timer("get_specific_table_order");
          
-        sqlx::query_as("SELECT * FROM ORDERS WHERE table_id = $1 and order_id = $2 LIMIT 1")
+        sqlx::query_as("SELECT o.* FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = $1 and o.order_id = $2 and s.close_time IS NULL and o.deleted_at IS NULL LIMIT 1")
                 .bind(table_id)
                 .bind(order_id)
                 .fetch_all(&self.db).await
                 .map_err(map_sqlx_error_to_api_error)
-                .and_then(PgTableOrderDAO::is_existing_order)
+                .and_then(is_existing_order)
 
     }
     
 
-    async fn remove_order(&self, table_id: i16, order_id: i32) -> Result<(), ApiError> {
-        // let mut transaction = self.db.begin()
-        //     .await
-        //     .map_err(map_sqlx_error_to_api_error)?;
+    async fn remove_order(&self, table_id: i16, order_id: i32, deleted_by: Option<&str>, reason: Option<&str>, deletion_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("remove_order");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
 
-        
-        sqlx::query_as( "DELETE FROM ORDERS WHERE table_id = $1 and order_id = $2 RETURNING *")
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        sqlx::query_as("UPDATE ORDERS SET deleted_at = $3, deleted_by = $4, reason = $5 \
+                WHERE table_id = $1 and order_id = $2 and deleted_at IS NULL \
+                and session_id IN (SELECT session_id FROM table_sessions WHERE close_time IS NULL) RETURNING *")
             .bind(table_id)
             .bind(order_id)
-            .fetch_all(&self.db).await
+            .bind(deletion_time)
+            .bind(deleted_by)
+            .bind(reason)
+            .fetch_all(&mut *transaction).await
             .map_err(map_sqlx_error_to_api_error)
-            .and_then(PgTableOrderDAO::is_existing_order)
-            .and_then(|_| Ok(()))
+            .and_then(is_existing_order)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::OrderRemoved, table_id, Some(order_id)).await?;
 
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
     }
 
-}
 
+    async fn get_table_order_history(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_table_order_history");
+
+        sqlx::query_as("SELECT o.* FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = $1 and s.close_time IS NULL ORDER BY o.order_id")
+                .bind(table_id)
+                .fetch_all(&self.db)
+                .await
+                .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_order_status(&self, table_id: i16, order_id: i32, status: OrderStatus, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("update_order_status");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+
+        // lock the order row so that concurrent status changes are validated one by one
+        let current_status: OrderStatus = sqlx::query_scalar("SELECT o.status FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = $1 and o.order_id = $2 and s.close_time IS NULL and o.deleted_at IS NULL FOR UPDATE OF o")
+            .bind(table_id)
+            .bind(order_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        check_status_transition(current_status, status)?;
+
+        sqlx::query("UPDATE ORDERS SET status = $3 WHERE table_id = $1 and order_id = $2")
+            .bind(table_id)
+            .bind(order_id)
+            .bind(status)
+            .execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::OrderStatusChanged, table_id, Some(order_id)).await?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn decrement_order_quantity(&self, table_id: i16, order_id: i32, quantity: i32, deleted_by: Option<&str>, reason: Option<&str>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("decrement_order_quantity");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+
+        // lock the order row so that concurrent decrements can't remove more items than ordered
+        let current_quantity: i32 = sqlx::query_scalar("SELECT o.quantity FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = $1 and o.order_id = $2 and s.close_time IS NULL and o.deleted_at IS NULL FOR UPDATE OF o")
+            .bind(table_id)
+            .bind(order_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        // the order is removed (soft deleted with its last quantity) once all of its items are decremented
+        let query = match remaining_quantity_after_decrement(current_quantity, quantity)?{
+            0 => sqlx::query("UPDATE ORDERS SET deleted_at = $3, deleted_by = $4, reason = $5 WHERE table_id = $1 and order_id = $2")
+                .bind(table_id)
+                .bind(order_id)
+                .bind(Utc::now())
+                .bind(deleted_by)
+                .bind(reason_for_removal_by_decrement(reason)?),
+            remaining => sqlx::query("UPDATE ORDERS SET quantity = $3 WHERE table_id = $1 and order_id = $2")
+                .bind(table_id)
+                .bind(order_id)
+                .bind(remaining)
+        };
+
+        query.execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::OrderQuantityChanged, table_id, Some(order_id)).await?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn acknowledge_order(&self, table_id: i16, order_id: i32, device_id: &str, acknowledgement_time: DateTime<Utc>) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("acknowledge_order");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        sqlx::query("UPDATE ORDERS SET acknowledged_by = COALESCE(acknowledged_by, $3), acknowledgement_time = COALESCE(acknowledgement_time, $4) \
+                WHERE table_id = $1 and order_id = $2 and deleted_at IS NULL \
+                and session_id IN (SELECT session_id FROM table_sessions WHERE close_time IS NULL)")
+            .bind(table_id)
+            .bind(order_id)
+            .bind(device_id)
+            .bind(acknowledgement_time)
+            .execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+            .and_then(|result| match result.rows_affected(){
+                0 => Err(ApiError::OrderNotFound),
+                _ => Ok(())
+            })?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::OrderAcknowledged, table_id, Some(order_id)).await?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
 
 
+    async fn transfer_orders(&self, source_table_id: i16, target_table_id: i16, order_ids: &[i32], transfer_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("transfer_orders");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+
+        // transaction is rolled back (dropped) if any order is not moved
+        let moved_count = Self::execute_move_orders(&mut transaction, source_table_id, target_table_id, Some(order_ids), transfer_time).await?;
+        check_transferred_orders(order_ids, moved_count)?;
+
+        for table_id in [source_table_id, target_table_id]{
+            self.notify_order_change(&mut transaction, OrderEventType::OrdersTransferred, table_id, None).await?;
+        }
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn merge_tables(&self, source_table_id: i16, target_table_id: i16, merge_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("merge_tables");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        Self::lock_open_session(&mut transaction, source_table_id).await?;
+        let moved_count = Self::execute_move_orders(&mut transaction, source_table_id, target_table_id, None, merge_time).await?;
+        check_merged_orders(source_table_id, moved_count)?;
+
+        // the party has moved to target table, so source table is empty for the next party
+        sqlx::query("UPDATE table_sessions SET close_time = $2 WHERE table_id = $1 and close_time IS NULL")
+            .bind(source_table_id)
+            .bind(merge_time)
+            .execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        for table_id in [source_table_id, target_table_id]{
+            self.notify_order_change(&mut transaction, OrderEventType::OrdersTransferred, table_id, None).await?;
+        }
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn count_unfinished_orders(&self) -> Result<i64, ApiError> {
+        let _timer = dao_query_timer("count_unfinished_orders");
+        sqlx::query_scalar("SELECT COUNT(*) FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.status IN ('ordered', 'cooking') and s.close_time IS NULL and o.deleted_at IS NULL")
+            .fetch_one(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_kitchen_queue(&self, category: Option<&str>, limit: i64) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_kitchen_queue");
+        // orders of items which are not in the menu have no category, so they are only returned without category filter.
+        // Orders are matched by their menu item snapshot (renaming an item keeps them), or by name if they were added without snapshot
+        sqlx::query_as("SELECT o.* FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        LEFT JOIN menu_items m ON m.menu_item_id = o.menu_item_id or (o.menu_item_id IS NULL and m.name = o.item_name) \
+                        WHERE o.status IN ('ordered', 'cooking', 'ready') and s.close_time IS NULL and o.deleted_at IS NULL and ($1::text IS NULL or m.category = $1) \
+                        ORDER BY o.estimated_arrival_time, o.creation_time, o.order_id LIMIT $2")
+            .bind(category)
+            .bind(limit)
+            .fetch_all(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+
+    async fn count_open_orders_by_table(&self) -> Result<Vec<(i16, i64)>, ApiError> {
+        let _timer = dao_query_timer("count_open_orders_by_table");
+        sqlx::query_as("SELECT o.table_id, COUNT(*) FROM ORDERS o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.status IN ('ordered', 'cooking', 'ready') and s.close_time IS NULL and o.deleted_at IS NULL \
+                        GROUP BY o.table_id ORDER BY o.table_id")
+            .fetch_all(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::session_dao::{SessionDAO, map_session_sqlx_error_to_api_error};
+use crate::dao::bill_dao::BILLED_ORDER_REASON;
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::{OrderItem, OrderStatus, TableSession};
+use crate::error::ApiError;
+use crate::events::OrderEventType;
+use sqlx::{Postgres, Transaction};
+
+
+impl PgTableOrderDAO{
+    /// helper function to check that the open session of a table has no unbilled (not removed) orders before it is closed
+    async fn check_session_without_orders(transaction: &mut Transaction<'static, Postgres>, session_id: i32) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM ORDERS WHERE session_id = $1 and deleted_at IS NULL)")
+            .bind(session_id)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::SessionHasOrders)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Seating sessions are stored in the same postgres database as orders, so the postgres DAO implements SessionDAO as well
+impl SessionDAO for PgTableOrderDAO{
+    async fn open_session(&self, table_id: i16, guest_count: i16, open_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        // unique index of open sessions rejects the second open session of the same table
+        let session: TableSession = sqlx::query_as("INSERT INTO table_sessions(table_id, guest_count, open_time) VALUES($1, $2, $3) RETURNING *")
+            .bind(table_id)
+            .bind(guest_count)
+            .bind(open_time)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::SessionOpened, table_id, None).await?;
+
+        transaction.commit().await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        Ok(session)
+    }
+
+
+    async fn get_current_session(&self, table_id: i16) -> Result<TableSession, ApiError> {
+        sqlx::query_as("SELECT * FROM table_sessions WHERE table_id = $1 and close_time IS NULL")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table_sessions(&self, table_id: i16) -> Result<Vec<TableSession>, ApiError> {
+        sqlx::query_as("SELECT * FROM table_sessions WHERE table_id = $1 ORDER BY session_id")
+            .bind(table_id)
+            .fetch_all(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+
+
+    async fn close_current_session(&self, table_id: i16, close_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        // closing the session waits for orders being added to it (see PgTableOrderDAO::lock_open_session)
+        let session_id = Self::lock_open_session(&mut transaction, table_id).await?.ok_or(ApiError::SessionNotFound)?;
+        Self::check_session_without_orders(&mut transaction, session_id).await?;
+        let session: TableSession = sqlx::query_as("UPDATE table_sessions SET close_time = $2 WHERE table_id = $1 and close_time IS NULL RETURNING *")
+            .bind(table_id)
+            .bind(close_time)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        self.notify_order_change(&mut transaction, OrderEventType::SessionClosed, table_id, None).await?;
+
+        transaction.commit().await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        Ok(session)
+    }
+
+
+    async fn get_session_orders(&self, table_id: i16, session_id: i32, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        sqlx::query("SELECT session_id FROM table_sessions WHERE session_id = $1 and table_id = $2")
+            .bind(session_id)
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        // orders billed at checkout are soft deleted, but they are still orders of the (past) session
+        sqlx::query_as("SELECT * FROM ORDERS WHERE session_id = $1 and (deleted_at IS NULL or reason = $3) and ($2::order_status IS NULL or status = $2) ORDER BY order_id")
+            .bind(session_id)
+            .bind(status)
+            .bind(BILLED_ORDER_REASON)
+            .fetch_all(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::dao::staff_dao::{StaffDAO, map_staff_sqlx_error_to_api_error};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::StaffMember;
+use crate::error::ApiError;
+
+
+/// Staff accounts are stored in the same postgres database as orders, so the postgres DAO implements StaffDAO as well
+impl StaffDAO for PgTableOrderDAO{
+    async fn add_staff(&self, staff: &StaffMember) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("INSERT INTO staff(staff_id, name, role, pin_hash, pin_salt, active, creation_time) VALUES($1, $2, $3, $4, $5, $6, $7) RETURNING *")
+            .bind(&staff.staff_id)
+            .bind(&staff.name)
+            .bind(staff.role)
+            .bind(&staff.pin_hash)
+            .bind(&staff.pin_salt)
+            .bind(staff.active)
+            .bind(staff.creation_time)
+            .fetch_one(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_staff(&self) -> Result<Vec<StaffMember>, ApiError> {
+        sqlx::query_as("SELECT * FROM staff ORDER BY staff_id")
+            .fetch_all(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_staff_member(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("SELECT * FROM staff WHERE staff_id = $1")
+            .bind(staff_id)
+            .fetch_one(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+
+
+    async fn deactivate_staff(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("UPDATE staff SET active = FALSE WHERE staff_id = $1 RETURNING *")
+            .bind(staff_id)
+            .fetch_one(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::dao::table_dao::{TableDAO, map_table_sqlx_error_to_api_error};
+use crate::dao::pg_order_dao::PgTableOrderDAO;
+use crate::model::{RestaurantTable, TableRequest};
+use crate::error::ApiError;
+use sqlx::{Postgres, Transaction};
+
+
+impl PgTableOrderDAO{
+    /// helper function to check that table has no orders before it is deactivated or removed
+    async fn check_table_without_orders(transaction: &mut Transaction<'static, Postgres>, table_id: i16) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM ORDERS WHERE table_id = $1 and deleted_at IS NULL)")
+            .bind(table_id)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::TableInUse)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Table registry is stored in the same postgres database as orders, so the postgres DAO implements TableDAO as well
+impl TableDAO for PgTableOrderDAO{
+    async fn add_table(&self, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        sqlx::query_as("INSERT INTO restaurant_tables(label, capacity, section, active) VALUES($1, $2, $3, $4) RETURNING *")
+            .bind(&table.label)
+            .bind(table.capacity)
+            .bind(&table.section)
+            .bind(table.active)
+            .fetch_one(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_tables(&self) -> Result<Vec<RestaurantTable>, ApiError> {
+        sqlx::query_as("SELECT * FROM restaurant_tables ORDER BY table_id")
+            .fetch_all(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table(&self, table_id: i16) -> Result<RestaurantTable, ApiError> {
+        sqlx::query_as("SELECT * FROM restaurant_tables WHERE table_id = $1")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_table(&self, table_id: i16, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        // lock the table row first, so that its existence is checked before its orders
+        sqlx::query("SELECT table_id FROM restaurant_tables WHERE table_id = $1 FOR UPDATE")
+            .bind(table_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        if !table.active{
+            Self::check_table_without_orders(&mut transaction, table_id).await?;
+        }
+
+        let updated: RestaurantTable = sqlx::query_as("UPDATE restaurant_tables SET label = $2, capacity = $3, section = $4, active = $5 WHERE table_id = $1 RETURNING *")
+            .bind(table_id)
+            .bind(&table.label)
+            .bind(table.capacity)
+            .bind(&table.section)
+            .bind(table.active)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        transaction.commit().await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        Ok(updated)
+    }
+
+
+    async fn remove_table(&self, table_id: i16) -> Result<(), ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        sqlx::query("DELETE FROM restaurant_tables WHERE table_id = $1 RETURNING table_id")
+            .bind(table_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        Self::check_table_without_orders(&mut transaction, table_id).await?;
+
+        transaction.commit().await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_orders_version(&self, table_id: i16) -> Result<i64, ApiError> {
+        sqlx::query_scalar("SELECT orders_version FROM restaurant_tables WHERE table_id = $1")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use trait_variant;
+use crate::model::{OrderItem, OrderStatus, TableSession};
+use crate::error::ApiError;
+
+
+/// trait for seating session access object. contains open / get / close session functions.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (SessionDAO)
+#[trait_variant::make(SessionDAO: Send)]
+pub trait LocalSessionDAO: Clone + Sync + 'static{
+    /// function to seat a new party at a table. It returns ApiError::SessionAlreadyOpen if the table already has an open session
+    async fn open_session(&self, table_id: i16, guest_count: i16, open_time: DateTime<Utc>) -> Result<TableSession, ApiError>;
+
+    /// function to get the open session of a table. It returns ApiError::SessionNotFound if no session is open
+    async fn get_current_session(&self, table_id: i16) -> Result<TableSession, ApiError>;
+
+    /// function to get all sessions of a table, including closed ones (ordered by session_id)
+    async fn get_table_sessions(&self, table_id: i16) -> Result<Vec<TableSession>, ApiError>;
+
+    /// function to close the open session of a table (the party left). Its (removed) orders are kept for reference.
+    /// It returns the closed session, ApiError::SessionNotFound if no session is open, or ApiError::SessionHasOrders
+    /// if the session still has unbilled orders (they are billed by checkout, which closes the session as well)
+    async fn close_current_session(&self, table_id: i16, close_time: DateTime<Utc>) -> Result<TableSession, ApiError>;
+
+    /// function to get orders of specific (current or past) session of a table, including orders billed at checkout (but not removed ones).
+    /// If status is specified, only orders in that status are returned.
+    /// It returns ApiError::SessionNotFound if the session does not belong to the table
+    async fn get_session_orders(&self, table_id: i16, session_id: i32, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError>;
+}
+
+
+/// utility function to map sqlx error of session queries to ApiError
+pub(crate) fn map_session_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::SessionNotFound,
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => ApiError::SessionAlreadyOpen,
+        _ => ApiError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::api_key_dao::{ApiKeyDAO, map_api_key_sqlx_error_to_api_error, check_valid_api_key};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::{ApiKey, Role};
+use crate::error::ApiError;
+
+
+/// helper function to take the single row of `RETURNING` query (see sqlite_table_dao)
+fn single_api_key(api_keys: Vec<ApiKey>) -> Result<ApiKey, ApiError>{
+    api_keys.into_iter().next().ok_or(ApiError::ApiKeyNotFound)
+}
+
+
+impl ApiKeyDAO for SqliteTableOrderDAO{
+    async fn add_api_key(&self, name: &str, role: Role, key_hash: &str, creation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("INSERT INTO api_keys(name, role, key_hash, creation_time) VALUES(?, ?, ?, ?) RETURNING *")
+            .bind(name)
+            .bind(role)
+            .bind(key_hash)
+            .bind(creation_time)
+            .fetch_all(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+            .and_then(single_api_key)
+    }
+
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, ApiError> {
+        sqlx::query_as("SELECT * FROM api_keys ORDER BY api_key_id")
+            .fetch_all(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+    }
+
+
+    async fn authenticate_api_key(&self, key_hash: &str) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("SELECT * FROM api_keys WHERE key_hash = ?")
+            .bind(key_hash)
+            .fetch_optional(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+            .and_then(check_valid_api_key)
+    }
+
+
+    async fn revoke_api_key(&self, api_key_id: i32, revocation_time: DateTime<Utc>) -> Result<ApiKey, ApiError> {
+        sqlx::query_as("UPDATE api_keys SET revocation_time = COALESCE(revocation_time, ?) WHERE api_key_id = ? RETURNING *")
+            .bind(revocation_time)
+            .bind(api_key_id)
+            .fetch_all(&self.db).await
+            .map_err(map_api_key_sqlx_error_to_api_error)
+            .and_then(single_api_key)
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::bill_dao::{BillDAO, BILLED_ORDER_REASON, build_bill, map_bill_sqlx_error_to_api_error};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::{Bill, BillCharges, BillLineItem, MenuItem, OrderItem};
+use crate::error::ApiError;
+use sqlx::Sqlite;
+
+
+/// Bills are stored in the same sqlite database as orders, so checkout can be done in a single transaction
+impl BillDAO for SqliteTableOrderDAO{
+    async fn checkout_table(&self, table_id: i16, charges: BillCharges, creation_time: DateTime<Utc>) -> Result<Bill, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        // only orders of the current seating session are billed
+        let orders: Vec<OrderItem> = sqlx::query_as("SELECT o.* FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = ? and s.close_time IS NULL and o.deleted_at IS NULL ORDER BY o.order_id")
+            .bind(table_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        // menu is small, so all items are loaded instead of building IN (...) list
+        let menu_items: Vec<MenuItem> = sqlx::query_as("SELECT * FROM menu_items")
+            .fetch_all(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        let mut bill = build_bill(table_id, &orders, &menu_items, charges, creation_time)?;
+
+        bill.bill_id = sqlx::query_scalar("INSERT INTO bills(table_id, subtotal, service_charge, tax, total, creation_time) VALUES(?, ?, ?, ?, ?, ?) RETURNING bill_id")
+            .bind(bill.table_id)
+            .bind(bill.subtotal)
+            .bind(bill.service_charge)
+            .bind(bill.tax)
+            .bind(bill.total)
+            .bind(bill.creation_time)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        if !bill.line_items.is_empty(){
+            let mut query_builder: sqlx::QueryBuilder<'_, Sqlite> = sqlx::QueryBuilder::new("INSERT INTO bill_items(bill_id, order_id, item_name, quantity, unit_price, amount) ");
+            query_builder.push_values(&bill.line_items, |mut binder, line| {
+                binder.push_bind(bill.bill_id)
+                    .push_bind(line.order_id)
+                    .push_bind(&line.item_name)
+                    .push_bind(line.quantity)
+                    .push_bind(line.unit_price)
+                    .push_bind(line.amount);
+            });
+
+            query_builder.build().execute(&mut *transaction)
+                .await
+                .map_err(map_bill_sqlx_error_to_api_error)?;
+        }
+
+        // only the billed orders are removed. They are soft deleted, so the rows are kept for reference from bill items
+        let mut query_builder: sqlx::QueryBuilder<'_, Sqlite> = sqlx::QueryBuilder::new("UPDATE orders SET deleted_at = ");
+        query_builder.push_bind(creation_time)
+            .push(", reason = ")
+            .push_bind(BILLED_ORDER_REASON)
+            .push(" WHERE order_id IN (");
+        let mut separated = query_builder.separated(", ");
+        for order in &orders{
+            separated.push_bind(order.order_id);
+        }
+        separated.push_unseparated(")");
+
+        query_builder.build().execute(&mut *transaction)
+            .await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        // the party has paid, so its seating session is closed
+        sqlx::query("UPDATE table_sessions SET close_time = ? WHERE table_id = ? and close_time IS NULL")
+            .bind(creation_time)
+            .bind(table_id)
+            .execute(&mut *transaction).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        transaction.commit().await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        Ok(bill)
+    }
+
+
+    async fn get_bill(&self, bill_id: i32) -> Result<Bill, ApiError> {
+        let mut bill: Bill = sqlx::query_as("SELECT * FROM bills WHERE bill_id = ?")
+            .bind(bill_id)
+            .fetch_one(&self.db).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        bill.line_items = sqlx::query_as::<_, BillLineItem>("SELECT * FROM bill_items WHERE bill_id = ? ORDER BY order_id")
+            .bind(bill_id)
+            .fetch_all(&self.db).await
+            .map_err(map_bill_sqlx_error_to_api_error)?;
+
+        Ok(bill)
+    }
+}
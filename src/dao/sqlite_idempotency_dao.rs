@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::idempotency_dao::{IdempotencyDAO, IdempotencyRecord, IdempotencyReservation, idempotency_key_expiry, idempotency_lease_expiry, check_idempotency_record};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::error::ApiError;
+
+
+/// Idempotency keys are stored in the same sqlite database as orders, so the sqlite DAO implements IdempotencyDAO as well
+impl IdempotencyDAO for SqliteTableOrderDAO{
+    async fn reserve_idempotency_key(&self, idempotency_key: &str, request_fingerprint: &str, reservation_time: DateTime<Utc>) -> Result<IdempotencyReservation, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(ApiError::DatabaseError)?;
+
+        sqlx::query("DELETE FROM idempotency_keys WHERE creation_time < ?")
+            .bind(idempotency_key_expiry(reservation_time))
+            .execute(&mut *transaction).await
+            .map_err(ApiError::DatabaseError)?;
+
+        // sqlite allows only one writer at a time, so the reservation (after the delete above) is never interleaved
+        let inserted = sqlx::query("INSERT INTO idempotency_keys(idempotency_key, request_fingerprint, creation_time) VALUES(?, ?, ?) ON CONFLICT DO NOTHING")
+            .bind(idempotency_key)
+            .bind(request_fingerprint)
+            .bind(reservation_time)
+            .execute(&mut *transaction).await
+            .map_err(ApiError::DatabaseError)?
+            .rows_affected();
+
+        // unfinished request of the same fingerprint is taken over once its lease has expired
+        let taken_over = match inserted{
+            0 => sqlx::query("UPDATE idempotency_keys SET creation_time = ? \
+                    WHERE idempotency_key = ? and request_fingerprint = ? and status_code IS NULL and creation_time < ?")
+                .bind(reservation_time)
+                .bind(idempotency_key)
+                .bind(request_fingerprint)
+                .bind(idempotency_lease_expiry(reservation_time))
+                .execute(&mut *transaction).await
+                .map_err(ApiError::DatabaseError)?
+                .rows_affected(),
+            _ => 0
+        };
+
+        let reservation = match inserted + taken_over{
+            0 => sqlx::query_as::<_, IdempotencyRecord>("SELECT * FROM idempotency_keys WHERE idempotency_key = ?")
+                .bind(idempotency_key)
+                .fetch_one(&mut *transaction).await
+                .map_err(ApiError::DatabaseError)
+                .and_then(|record| check_idempotency_record(record, request_fingerprint))?,
+            _ => IdempotencyReservation::Reserved
+        };
+
+        transaction.commit().await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| reservation)
+    }
+
+
+    async fn complete_idempotency_key(&self, idempotency_key: &str, status_code: u16, etag: Option<&str>, response_body: &str) -> Result<(), ApiError> {
+        sqlx::query("UPDATE idempotency_keys SET status_code = ?, etag = ?, response_body = ? WHERE idempotency_key = ?")
+            .bind(status_code as i16)
+            .bind(etag)
+            .bind(response_body)
+            .bind(idempotency_key)
+            .execute(&self.db).await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+
+
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE idempotency_key = ? and status_code IS NULL")
+            .bind(idempotency_key)
+            .execute(&self.db).await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+}
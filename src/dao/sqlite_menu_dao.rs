@@ -0,0 +1,112 @@
+use crate::dao::menu_dao::{MenuDAO, map_menu_sqlx_error_to_api_error};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::{MenuItem, MenuItemRequest};
+use crate::error::ApiError;
+use sqlx::{Sqlite, Transaction};
+
+
+/// helper function to take the single row of `RETURNING` query.
+/// sqlite applies the change only when the statement runs to completion, so `fetch_all` is used instead of `fetch_one`
+fn single_menu_item(items: Vec<MenuItem>) -> Result<MenuItem, ApiError>{
+    items.into_iter().next().ok_or(ApiError::MenuItemNotFound)
+}
+
+
+impl SqliteTableOrderDAO{
+    /// helper function to check that menu item isn't referenced by open orders (by id, or by name for orders without menu snapshot) before it is removed
+    async fn check_menu_item_without_orders(transaction: &mut Transaction<'static, Sqlite>, menu_item_id: i32, name: &str) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM orders WHERE (menu_item_id = ? or item_name = ?) and deleted_at IS NULL)")
+            .bind(menu_item_id)
+            .bind(name)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_menu_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::MenuItemInUse)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Menu catalog is stored in the same sqlite database as orders, so the sqlite DAO implements MenuDAO as well
+impl MenuDAO for SqliteTableOrderDAO{
+    async fn add_menu_item(&self, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("INSERT INTO menu_items(name, category, price, prep_time_minutes, active) VALUES(?, ?, ?, ?, ?) RETURNING *")
+            .bind(&item.name)
+            .bind(&item.category)
+            .bind(item.price)
+            .bind(item.prep_time_minutes)
+            .bind(item.active)
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+            .and_then(single_menu_item)
+    }
+
+
+    async fn get_menu_items(&self) -> Result<Vec<MenuItem>, ApiError> {
+        sqlx::query_as("SELECT * FROM menu_items ORDER BY menu_item_id")
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_menu_item(&self, menu_item_id: i32) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("SELECT * FROM menu_items WHERE menu_item_id = ?")
+            .bind(menu_item_id)
+            .fetch_one(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_menu_items_by_names(&self, names: &[String]) -> Result<Vec<MenuItem>, ApiError> {
+        if names.is_empty(){
+            return Ok(Vec::new());
+        }
+
+        // sqlite doesn't support array binding, so IN (...) list is built instead
+        let mut query_builder: sqlx::QueryBuilder<'_, Sqlite> = sqlx::QueryBuilder::new("SELECT * FROM menu_items WHERE name IN (");
+        let mut separated = query_builder.separated(", ");
+        for name in names{
+            separated.push_bind(name);
+        }
+        separated.push_unseparated(") ORDER BY menu_item_id");
+
+        query_builder.build_query_as()
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_menu_item(&self, menu_item_id: i32, item: &MenuItemRequest) -> Result<MenuItem, ApiError> {
+        sqlx::query_as("UPDATE menu_items SET name = ?, category = ?, price = ?, prep_time_minutes = ?, active = ? WHERE menu_item_id = ? RETURNING *")
+            .bind(&item.name)
+            .bind(&item.category)
+            .bind(item.price)
+            .bind(item.prep_time_minutes)
+            .bind(item.active)
+            .bind(menu_item_id)
+            .fetch_all(&self.db).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+            .and_then(single_menu_item)
+    }
+
+
+    async fn remove_menu_item(&self, menu_item_id: i32) -> Result<(), ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_menu_sqlx_error_to_api_error)?;
+
+        let item = sqlx::query_as("DELETE FROM menu_items WHERE menu_item_id = ? RETURNING *")
+            .bind(menu_item_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_menu_sqlx_error_to_api_error)
+            .and_then(single_menu_item)?;
+
+        Self::check_menu_item_without_orders(&mut transaction, menu_item_id, &item.name).await?;
+
+        transaction.commit().await
+            .map_err(map_menu_sqlx_error_to_api_error)
+    }
+}
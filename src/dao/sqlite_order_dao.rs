@@ -0,0 +1,386 @@
+use std::str::FromStr;
+
+use crate::dao::table_dao::check_matched_orders_version;
+use crate::dao::order_dao::{TableOrderDAO, PoolUsage, map_sqlx_error_to_api_error, is_existing_order, check_status_transition, remaining_quantity_after_decrement, reason_for_removal_by_decrement,
+    check_transferred_orders, check_merged_orders};
+use crate::model::{OrderItem, OrderStatus};
+use crate::error::ApiError;
+use crate::metrics::dao_query_timer;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{Sqlite, Transaction, SqlitePool};
+
+#[derive(Clone)]
+/// Implementation of order DAO on top of sqlite (for small venues which can't run postgres server)
+pub struct SqliteTableOrderDAO{
+    pub db: SqlitePool
+}
+
+
+impl SqliteTableOrderDAO{
+    /// constructor to attach sqlx sqlite pool (clonable). The schema must be already migrated
+    pub fn new(db: SqlitePool) -> Self{
+        Self{
+            db
+        }
+    }
+
+    /// function to open (or create) sqlite database file from url, and then apply sqlite migrations in `migrations/sqlite`
+    pub async fn connect(db_url: &str, max_connections: u32) -> Result<Self, ApiError>{
+        let options = SqliteConnectOptions::from_str(db_url)
+            .map_err(ApiError::DatabaseError)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect_with(options).await
+            .map_err(ApiError::DatabaseError)?;
+
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool).await
+            .map_err(|err| ApiError::ServerError(format!("Couldn't migrate sqlite database: {err}")))?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// helper function to compare expected versions of tables' orders (If-Match) in the transaction of the change.
+    /// The conditional update takes the write lock first, so no other writer can change the orders until the transaction ends
+    async fn check_orders_versions(transaction: &mut Transaction<'static, Sqlite>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError>{
+        for (table_id, version) in expected_versions{
+            sqlx::query("UPDATE restaurant_tables SET orders_version = orders_version WHERE table_id = ? and orders_version = ?")
+                .bind(table_id)
+                .bind(version)
+                .execute(&mut **transaction).await
+                .map_err(map_sqlx_error_to_api_error)
+                .and_then(|result| check_matched_orders_version(result.rows_affected()))?;
+        }
+        Ok(())
+    }
+
+    /// helper function to get the open seating session of a table in the transaction. A session is opened implicitly if the table has none
+    async fn current_or_new_session_id(transaction: &mut Transaction<'static, Sqlite>, table_id: i16, open_time: DateTime<Utc>) -> Result<i32, ApiError>{
+        // insert first so that the transaction takes the write lock before reading (no other writer can open a session in between)
+        sqlx::query("INSERT INTO table_sessions(table_id, open_time) VALUES(?, ?) ON CONFLICT DO NOTHING")
+            .bind(table_id)
+            .bind(open_time)
+            .execute(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        sqlx::query_scalar("SELECT session_id FROM table_sessions WHERE table_id = ? and close_time IS NULL")
+            .bind(table_id)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+    /// helper function to build, and execute insert orders query (using bulk insert similar to postgres impl)
+    async fn execute_insert_orders(&self, transaction: &mut Transaction<'static, Sqlite>, items: &[OrderItem], session_id: i32) -> Result<(), ApiError>{
+        let mut query_builder: sqlx::QueryBuilder<'_, Sqlite> = sqlx::QueryBuilder::new("INSERT INTO orders(table_id, item_name, quantity, note, creation_time, estimated_arrival_time, status, session_id, created_by, menu_item_id, unit_price) ");
+        query_builder.push_values(items, |mut binder, order| {
+            binder.push_bind(order.table_id)
+                .push_bind(&order.item_name)
+                .push_bind(order.quantity)
+                .push_bind(&order.note)
+                .push_bind(order.creation_time)
+                .push_bind(order.estimated_arrival_time)
+                .push_bind(order.status)
+                .push_bind(session_id)
+                .push_bind(&order.created_by)
+                .push_bind(order.menu_item_id)
+                .push_bind(order.unit_price);
+        });
+
+        query_builder.build().execute(&mut **transaction)
+            .await
+            .map_err(map_sqlx_error_to_api_error)
+            .map(|_| ())
+    }
+
+    /// helper function to move orders of the open session of source table to the open session of target table in the transaction.
+    /// All orders of the session are moved if order_ids is None. It returns the number of moved orders
+    async fn execute_move_orders(transaction: &mut Transaction<'static, Sqlite>, source_table_id: i16, target_table_id: i16,
+        order_ids: Option<&[i32]>, move_time: DateTime<Utc>) -> Result<usize, ApiError>{
+
+        let session_id = Self::current_or_new_session_id(transaction, target_table_id, move_time).await?;
+
+        // sqlite has no array parameter, so order ids are bound one by one
+        let mut query_builder: sqlx::QueryBuilder<'_, Sqlite> = sqlx::QueryBuilder::new("UPDATE orders SET table_id = ");
+        query_builder.push_bind(target_table_id)
+            .push(", session_id = ")
+            .push_bind(session_id)
+            .push(" WHERE table_id = ")
+            .push_bind(source_table_id)
+            .push(" and session_id = (SELECT session_id FROM table_sessions WHERE table_id = ")
+            .push_bind(source_table_id)
+            .push(" and close_time IS NULL) and deleted_at IS NULL");
+
+        if let Some(order_ids) = order_ids{
+            query_builder.push(" and order_id IN (");
+            let mut separated = query_builder.separated(", ");
+            for order_id in order_ids{
+                separated.push_bind(*order_id);
+            }
+            separated.push_unseparated(")");
+        }
+
+        query_builder.build().execute(&mut **transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+            .map(|result| result.rows_affected() as usize)
+    }
+
+}
+
+impl TableOrderDAO for SqliteTableOrderDAO{
+    async fn health_check(&self) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("health_check");
+        sqlx::query("SELECT 1")
+            .execute(&self.db)
+            .await
+            .map_err(ApiError::DatabaseError)
+            .map(|_| ())
+    }
+
+
+    fn pool_usage(&self) -> Option<PoolUsage> {
+        Some(PoolUsage{
+            size: self.db.size(),
+            idle: self.db.num_idle() as u32,
+            max_size: self.db.options().get_max_connections()
+        })
+    }
+
+
+    async fn add_table_orders(&self, items: &[OrderItem], expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("add_table_orders");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        // orders of a request are always added to the same table
+        let Some(first_order) = items.first() else{
+            return Ok(());
+        };
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        let session_id = Self::current_or_new_session_id(&mut transaction, first_order.table_id, first_order.creation_time).await?;
+        self.execute_insert_orders(&mut transaction, items, session_id).await?;
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table_orders(&self, table_id: i16, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_table_orders");
+        sqlx::query_as("SELECT o.* FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = ? and s.close_time IS NULL and o.deleted_at IS NULL and (? IS NULL or o.status = ?) ORDER BY o.order_id")
+                .bind(table_id)
+                .bind(status)
+                .bind(status)
+                .fetch_all(&self.db)
+                .await
+                .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_specific_table_order(&self, table_id: i16, order_id: i32) -> Result<Vec<OrderItem>, ApiError>{
+        let _timer = dao_query_timer("get_specific_table_order");
+        sqlx::query_as("SELECT o.* FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = ? and o.order_id = ? and s.close_time IS NULL and o.deleted_at IS NULL LIMIT 1")
+                .bind(table_id)
+                .bind(order_id)
+                .fetch_all(&self.db).await
+                .map_err(map_sqlx_error_to_api_error)
+                .and_then(is_existing_order)
+    }
+
+
+    async fn remove_order(&self, table_id: i16, order_id: i32, deleted_by: Option<&str>, reason: Option<&str>, deletion_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("remove_order");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        sqlx::query_as("UPDATE orders SET deleted_at = ?, deleted_by = ?, reason = ? \
+                WHERE table_id = ? and order_id = ? and deleted_at IS NULL \
+                and session_id IN (SELECT session_id FROM table_sessions WHERE close_time IS NULL) RETURNING *")
+            .bind(deletion_time)
+            .bind(deleted_by)
+            .bind(reason)
+            .bind(table_id)
+            .bind(order_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)
+            .and_then(is_existing_order)?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table_order_history(&self, table_id: i16) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_table_order_history");
+        sqlx::query_as("SELECT o.* FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.table_id = ? and s.close_time IS NULL ORDER BY o.order_id")
+                .bind(table_id)
+                .fetch_all(&self.db)
+                .await
+                .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_order_status(&self, table_id: i16, order_id: i32, status: OrderStatus, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("update_order_status");
+        // sqlite allows only one writer at a time, so read and update in the same transaction is enough
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        let current_status: OrderStatus = sqlx::query_scalar("SELECT o.status FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = ? and o.order_id = ? and s.close_time IS NULL and o.deleted_at IS NULL")
+            .bind(table_id)
+            .bind(order_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        check_status_transition(current_status, status)?;
+
+        sqlx::query("UPDATE orders SET status = ? WHERE table_id = ? and order_id = ?")
+            .bind(status)
+            .bind(table_id)
+            .bind(order_id)
+            .execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn decrement_order_quantity(&self, table_id: i16, order_id: i32, quantity: i32, deleted_by: Option<&str>, reason: Option<&str>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("decrement_order_quantity");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        let current_quantity: i32 = sqlx::query_scalar("SELECT o.quantity FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE o.table_id = ? and o.order_id = ? and s.close_time IS NULL and o.deleted_at IS NULL")
+            .bind(table_id)
+            .bind(order_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        // the order is removed (soft deleted with its last quantity) once all of its items are decremented
+        let query = match remaining_quantity_after_decrement(current_quantity, quantity)?{
+            0 => sqlx::query("UPDATE orders SET deleted_at = ?, deleted_by = ?, reason = ? WHERE table_id = ? and order_id = ?")
+                .bind(Utc::now())
+                .bind(deleted_by)
+                .bind(reason_for_removal_by_decrement(reason)?)
+                .bind(table_id)
+                .bind(order_id),
+            remaining => sqlx::query("UPDATE orders SET quantity = ? WHERE table_id = ? and order_id = ?")
+                .bind(remaining)
+                .bind(table_id)
+                .bind(order_id)
+        };
+
+        query.execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn acknowledge_order(&self, table_id: i16, order_id: i32, device_id: &str, acknowledgement_time: DateTime<Utc>) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("acknowledge_order");
+        sqlx::query("UPDATE orders SET acknowledged_by = COALESCE(acknowledged_by, ?), acknowledgement_time = COALESCE(acknowledgement_time, ?) \
+                WHERE table_id = ? and order_id = ? and deleted_at IS NULL \
+                and session_id IN (SELECT session_id FROM table_sessions WHERE close_time IS NULL)")
+            .bind(device_id)
+            .bind(acknowledgement_time)
+            .bind(table_id)
+            .bind(order_id)
+            .execute(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+            .and_then(|result| match result.rows_affected(){
+                0 => Err(ApiError::OrderNotFound),
+                _ => Ok(())
+            })
+    }
+
+
+    async fn transfer_orders(&self, source_table_id: i16, target_table_id: i16, order_ids: &[i32], transfer_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("transfer_orders");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+
+        // transaction is rolled back (dropped) if any order is not moved
+        let moved_count = Self::execute_move_orders(&mut transaction, source_table_id, target_table_id, Some(order_ids), transfer_time).await?;
+        check_transferred_orders(order_ids, moved_count)?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn merge_tables(&self, source_table_id: i16, target_table_id: i16, merge_time: DateTime<Utc>, expected_versions: &[(i16, i64)]) -> Result<(), ApiError> {
+        let _timer = dao_query_timer("merge_tables");
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        Self::check_orders_versions(&mut transaction, expected_versions).await?;
+        let moved_count = Self::execute_move_orders(&mut transaction, source_table_id, target_table_id, None, merge_time).await?;
+        check_merged_orders(source_table_id, moved_count)?;
+
+        // the party has moved to target table, so source table is empty for the next party
+        sqlx::query("UPDATE table_sessions SET close_time = ? WHERE table_id = ? and close_time IS NULL")
+            .bind(merge_time)
+            .bind(source_table_id)
+            .execute(&mut *transaction).await
+            .map_err(map_sqlx_error_to_api_error)?;
+
+        transaction.commit().await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn count_unfinished_orders(&self) -> Result<i64, ApiError> {
+        let _timer = dao_query_timer("count_unfinished_orders");
+        sqlx::query_scalar("SELECT COUNT(*) FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.status IN ('ordered', 'cooking') and s.close_time IS NULL and o.deleted_at IS NULL")
+            .fetch_one(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_kitchen_queue(&self, category: Option<&str>, limit: i64) -> Result<Vec<OrderItem>, ApiError> {
+        let _timer = dao_query_timer("get_kitchen_queue");
+        sqlx::query_as("SELECT o.* FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        LEFT JOIN menu_items m ON m.menu_item_id = o.menu_item_id or (o.menu_item_id IS NULL and m.name = o.item_name) \
+                        WHERE o.status IN ('ordered', 'cooking', 'ready') and s.close_time IS NULL and o.deleted_at IS NULL and (? IS NULL or m.category = ?) \
+                        ORDER BY o.estimated_arrival_time, o.creation_time, o.order_id LIMIT ?")
+            .bind(category)
+            .bind(category)
+            .bind(limit)
+            .fetch_all(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+
+
+
+    async fn count_open_orders_by_table(&self) -> Result<Vec<(i16, i64)>, ApiError> {
+        let _timer = dao_query_timer("count_open_orders_by_table");
+        sqlx::query_as("SELECT o.table_id, COUNT(*) FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                        WHERE o.status IN ('ordered', 'cooking', 'ready') and s.close_time IS NULL and o.deleted_at IS NULL \
+                        GROUP BY o.table_id ORDER BY o.table_id")
+            .fetch_all(&self.db).await
+            .map_err(map_sqlx_error_to_api_error)
+    }
+}
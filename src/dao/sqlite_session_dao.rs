@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+
+use crate::dao::session_dao::{SessionDAO, map_session_sqlx_error_to_api_error};
+use crate::dao::bill_dao::BILLED_ORDER_REASON;
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::{OrderItem, OrderStatus, TableSession};
+use crate::error::ApiError;
+use sqlx::{Sqlite, Transaction};
+
+
+/// helper function to take the single row of `RETURNING` query.
+/// sqlite applies the change only when the statement runs to completion, so `fetch_all` is used instead of `fetch_one`
+fn single_session(sessions: Vec<TableSession>) -> Result<TableSession, ApiError>{
+    sessions.into_iter().next().ok_or(ApiError::SessionNotFound)
+}
+
+
+impl SqliteTableOrderDAO{
+    /// helper function to check that the open session of a table has no unbilled (not removed) orders before it is closed
+    async fn check_session_without_orders(transaction: &mut Transaction<'static, Sqlite>, table_id: i16) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM orders o JOIN table_sessions s ON s.session_id = o.session_id \
+                WHERE s.table_id = ? and s.close_time IS NULL and o.deleted_at IS NULL)")
+            .bind(table_id)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::SessionHasOrders)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Seating sessions are stored in the same sqlite database as orders, so the sqlite DAO implements SessionDAO as well
+impl SessionDAO for SqliteTableOrderDAO{
+    async fn open_session(&self, table_id: i16, guest_count: i16, open_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        // unique index of open sessions rejects the second open session of the same table
+        sqlx::query_as("INSERT INTO table_sessions(table_id, guest_count, open_time) VALUES(?, ?, ?) RETURNING *")
+            .bind(table_id)
+            .bind(guest_count)
+            .bind(open_time)
+            .fetch_all(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+            .and_then(single_session)
+    }
+
+
+    async fn get_current_session(&self, table_id: i16) -> Result<TableSession, ApiError> {
+        sqlx::query_as("SELECT * FROM table_sessions WHERE table_id = ? and close_time IS NULL")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table_sessions(&self, table_id: i16) -> Result<Vec<TableSession>, ApiError> {
+        sqlx::query_as("SELECT * FROM table_sessions WHERE table_id = ? ORDER BY session_id")
+            .bind(table_id)
+            .fetch_all(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+
+
+    async fn close_current_session(&self, table_id: i16, close_time: DateTime<Utc>) -> Result<TableSession, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        Self::check_session_without_orders(&mut transaction, table_id).await?;
+        let session = sqlx::query_as("UPDATE table_sessions SET close_time = ? WHERE table_id = ? and close_time IS NULL RETURNING *")
+            .bind(close_time)
+            .bind(table_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_session_sqlx_error_to_api_error)
+            .and_then(single_session)?;
+
+        transaction.commit().await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        Ok(session)
+    }
+
+
+    async fn get_session_orders(&self, table_id: i16, session_id: i32, status: Option<OrderStatus>) -> Result<Vec<OrderItem>, ApiError> {
+        sqlx::query("SELECT session_id FROM table_sessions WHERE session_id = ? and table_id = ?")
+            .bind(session_id)
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)?;
+
+        // orders billed at checkout are soft deleted, but they are still orders of the (past) session
+        sqlx::query_as("SELECT * FROM orders WHERE session_id = ? and (deleted_at IS NULL or reason = ?) and (? IS NULL or status = ?) ORDER BY order_id")
+            .bind(session_id)
+            .bind(BILLED_ORDER_REASON)
+            .bind(status)
+            .bind(status)
+            .fetch_all(&self.db).await
+            .map_err(map_session_sqlx_error_to_api_error)
+    }
+}
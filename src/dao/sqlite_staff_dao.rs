@@ -0,0 +1,51 @@
+use crate::dao::staff_dao::{StaffDAO, map_staff_sqlx_error_to_api_error};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::StaffMember;
+use crate::error::ApiError;
+
+
+/// helper function to take the single row of `RETURNING` query (see sqlite_table_dao)
+fn single_staff_member(staff: Vec<StaffMember>) -> Result<StaffMember, ApiError>{
+    staff.into_iter().next().ok_or(ApiError::StaffNotFound)
+}
+
+
+impl StaffDAO for SqliteTableOrderDAO{
+    async fn add_staff(&self, staff: &StaffMember) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("INSERT INTO staff(staff_id, name, role, pin_hash, pin_salt, active, creation_time) VALUES(?, ?, ?, ?, ?, ?, ?) RETURNING *")
+            .bind(&staff.staff_id)
+            .bind(&staff.name)
+            .bind(staff.role)
+            .bind(&staff.pin_hash)
+            .bind(&staff.pin_salt)
+            .bind(staff.active)
+            .bind(staff.creation_time)
+            .fetch_all(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+            .and_then(single_staff_member)
+    }
+
+
+    async fn get_staff(&self) -> Result<Vec<StaffMember>, ApiError> {
+        sqlx::query_as("SELECT * FROM staff ORDER BY staff_id")
+            .fetch_all(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_staff_member(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("SELECT * FROM staff WHERE staff_id = ?")
+            .bind(staff_id)
+            .fetch_one(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+    }
+
+
+    async fn deactivate_staff(&self, staff_id: &str) -> Result<StaffMember, ApiError> {
+        sqlx::query_as("UPDATE staff SET active = FALSE WHERE staff_id = ? RETURNING *")
+            .bind(staff_id)
+            .fetch_all(&self.db).await
+            .map_err(map_staff_sqlx_error_to_api_error)
+            .and_then(single_staff_member)
+    }
+}
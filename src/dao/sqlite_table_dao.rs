@@ -0,0 +1,120 @@
+use crate::dao::table_dao::{TableDAO, map_table_sqlx_error_to_api_error};
+use crate::dao::sqlite_order_dao::SqliteTableOrderDAO;
+use crate::model::{RestaurantTable, TableRequest};
+use crate::error::ApiError;
+use sqlx::{Sqlite, Transaction};
+
+
+/// helper function to take the single row of `RETURNING` query.
+/// sqlite applies the change only when the statement runs to completion, so `fetch_all` is used instead of `fetch_one`
+fn single_table(tables: Vec<RestaurantTable>) -> Result<RestaurantTable, ApiError>{
+    tables.into_iter().next().ok_or(ApiError::TableNotFound)
+}
+
+
+impl SqliteTableOrderDAO{
+    /// helper function to check that table has no orders before it is deactivated or removed
+    async fn check_table_without_orders(transaction: &mut Transaction<'static, Sqlite>, table_id: i16) -> Result<(), ApiError>{
+        let has_orders: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM ORDERS WHERE table_id = ? and deleted_at IS NULL)")
+            .bind(table_id)
+            .fetch_one(&mut **transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        if has_orders{
+            Err(ApiError::TableInUse)
+        }else{
+            Ok(())
+        }
+    }
+}
+
+
+/// Table registry is stored in the same sqlite database as orders, so the sqlite DAO implements TableDAO as well
+impl TableDAO for SqliteTableOrderDAO{
+    async fn add_table(&self, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        sqlx::query_as("INSERT INTO restaurant_tables(label, capacity, section, active) VALUES(?, ?, ?, ?) RETURNING *")
+            .bind(&table.label)
+            .bind(table.capacity)
+            .bind(&table.section)
+            .bind(table.active)
+            .fetch_all(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+            .and_then(single_table)
+    }
+
+
+    async fn get_tables(&self) -> Result<Vec<RestaurantTable>, ApiError> {
+        sqlx::query_as("SELECT * FROM restaurant_tables ORDER BY table_id")
+            .fetch_all(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_table(&self, table_id: i16) -> Result<RestaurantTable, ApiError> {
+        sqlx::query_as("SELECT * FROM restaurant_tables WHERE table_id = ?")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn update_table(&self, table_id: i16, table: &TableRequest) -> Result<RestaurantTable, ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        // check existence of the table first (sqlite transaction locks the whole database on write, so no row lock is needed)
+        sqlx::query("SELECT table_id FROM restaurant_tables WHERE table_id = ?")
+            .bind(table_id)
+            .fetch_one(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        if !table.active{
+            Self::check_table_without_orders(&mut transaction, table_id).await?;
+        }
+
+        let updated = sqlx::query_as("UPDATE restaurant_tables SET label = ?, capacity = ?, section = ?, active = ? WHERE table_id = ? RETURNING *")
+            .bind(&table.label)
+            .bind(table.capacity)
+            .bind(&table.section)
+            .bind(table.active)
+            .bind(table_id)
+            .fetch_all(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)
+            .and_then(single_table)?;
+
+        transaction.commit().await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        Ok(updated)
+    }
+
+
+    async fn remove_table(&self, table_id: i16) -> Result<(), ApiError> {
+        let mut transaction = self.db.begin()
+            .await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        let result = sqlx::query("DELETE FROM restaurant_tables WHERE table_id = ?")
+            .bind(table_id)
+            .execute(&mut *transaction).await
+            .map_err(map_table_sqlx_error_to_api_error)?;
+
+        if result.rows_affected() == 0{
+            return Err(ApiError::TableNotFound);
+        }
+
+        Self::check_table_without_orders(&mut transaction, table_id).await?;
+
+        transaction.commit().await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+
+
+    async fn get_orders_version(&self, table_id: i16) -> Result<i64, ApiError> {
+        sqlx::query_scalar("SELECT orders_version FROM restaurant_tables WHERE table_id = ?")
+            .bind(table_id)
+            .fetch_one(&self.db).await
+            .map_err(map_table_sqlx_error_to_api_error)
+    }
+}
@@ -0,0 +1,33 @@
+use trait_variant;
+use crate::model::StaffMember;
+use crate::error::ApiError;
+
+
+/// trait for staff account access object. contains add / get / deactivate staff functions.
+/// PINs are passed as salted hashes (see auth::hash_pin), so storage never sees PIN itself.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (StaffDAO)
+#[trait_variant::make(StaffDAO: Send)]
+pub trait LocalStaffDAO: Clone + Sync + 'static{
+    /// function for storing a new staff account. It returns ApiError::StaffAlreadyExists if the staff id is taken
+    async fn add_staff(&self, staff: &StaffMember) -> Result<StaffMember, ApiError>;
+
+    /// function to get all staff accounts including inactive ones (ordered by staff_id)
+    async fn get_staff(&self) -> Result<Vec<StaffMember>, ApiError>;
+
+    /// function to get specific staff account. It returns ApiError::StaffNotFound if the staff doesn't exist
+    async fn get_staff_member(&self, staff_id: &str) -> Result<StaffMember, ApiError>;
+
+    /// function to deactivate specific staff account (the account is kept, since orders refer to it).
+    /// It returns the deactivated account, or ApiError::StaffNotFound if the staff doesn't exist
+    async fn deactivate_staff(&self, staff_id: &str) -> Result<StaffMember, ApiError>;
+}
+
+
+/// utility function to map sqlx error of staff queries to ApiError
+pub(crate) fn map_staff_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::StaffNotFound,
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => ApiError::StaffAlreadyExists,
+        _ => ApiError::DatabaseError(err),
+    }
+}
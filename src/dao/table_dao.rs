@@ -0,0 +1,63 @@
+use trait_variant;
+use crate::model::{RestaurantTable, TableRequest};
+use crate::error::ApiError;
+
+
+/// number of tables registered when storage is created (same as the former MAX_TABLES default and the tables migration)
+pub const DEFAULT_TABLE_COUNT: i16 = 100;
+
+
+/// trait for table registry access object. contains add / get / update / remove table functions.
+/// Similar to TableOrderDAO, implementations should implement the Send variant (TableDAO)
+#[trait_variant::make(TableDAO: Send)]
+pub trait LocalTableDAO: Clone + Sync + 'static{
+    /// function for registering new table. It returns the created table (with table_id)
+    async fn add_table(&self, table: &TableRequest) -> Result<RestaurantTable, ApiError>;
+
+    /// function to get all tables (ordered by table_id)
+    async fn get_tables(&self) -> Result<Vec<RestaurantTable>, ApiError>;
+
+    /// function to get specific table (including inactive table)
+    async fn get_table(&self, table_id: i16) -> Result<RestaurantTable, ApiError>;
+
+    /// function to replace all attributes of specific table. It returns the updated table.
+    /// Table with orders can't be deactivated (ApiError::TableInUse)
+    async fn update_table(&self, table_id: i16, table: &TableRequest) -> Result<RestaurantTable, ApiError>;
+
+    /// function to remove specific table. Table with orders can't be removed (ApiError::TableInUse)
+    async fn remove_table(&self, table_id: i16) -> Result<(), ApiError>;
+
+    /// function to get version of table's orders (used as ETag). The version is increased whenever orders of the table are changed
+    /// (expected version of `If-Match` is compared by the functions changing orders, see TableOrderDAO)
+    async fn get_orders_version(&self, table_id: i16) -> Result<i64, ApiError>;
+}
+
+
+/// utility function to map sqlx error of table queries to ApiError
+pub(crate) fn map_table_sqlx_error_to_api_error(err: sqlx::Error) -> ApiError{
+    match err{
+        sqlx::Error::RowNotFound => ApiError::TableNotFound,
+        _ => ApiError::DatabaseError(err),
+    }
+}
+
+
+/// utility function to check result of comparing expected version of table's orders (shared by DAO implementations)
+pub(crate) fn check_matched_orders_version(matched_count: u64) -> Result<(), ApiError>{
+    match matched_count{
+        0 => Err(ApiError::PreconditionFailed),
+        _ => Ok(())
+    }
+}
+
+
+/// utility function to generate default tables (`Table 1` .. `Table 100` with 4 seats in main section)
+pub(crate) fn default_tables() -> Vec<RestaurantTable>{
+    (1..=DEFAULT_TABLE_COUNT).map(|table_id| RestaurantTable{
+        table_id,
+        label: format!("Table {table_id}"),
+        capacity: 4,
+        section: "main".to_string(),
+        active: true
+    }).collect()
+}
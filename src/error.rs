@@ -1,10 +1,11 @@
 
 use axum::response::IntoResponse;
-use axum::http::StatusCode;
-use axum::extract::rejection::{JsonRejection, PathRejection};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
 use serde_json::json;
 use sqlx::Error as DbError;
 use thiserror::Error;
+use crate::model::OrderStatus;
 
 #[derive(Error, Debug)]
 /// Custom error for server application
@@ -24,11 +25,71 @@ pub enum ApiError{
     #[error("Table not found")]
     TableNotFound,
 
+    #[error("Menu item not found")]
+    MenuItemNotFound,
+
+    #[error("Bill not found")]
+    BillNotFound,
+
+    #[error("Menu item with the same name already exists")]
+    MenuItemAlreadyExists,
+
+    #[error("Table still has orders")]
+    TableInUse,
+
+    #[error("Menu item is referenced by open orders")]
+    MenuItemInUse,
+
+    #[error("Seating session not found")]
+    SessionNotFound,
+
+    #[error("Table already has an open seating session")]
+    SessionAlreadyOpen,
+
+    #[error("Seating session still has unbilled orders")]
+    SessionHasOrders,
+
+    #[error("Idempotency key was already used with a different request")]
+    IdempotencyKeyReused,
+
+    #[error("Request with the same idempotency key is still in progress")]
+    IdempotentRequestInProgress,
+
+    #[error("Table orders were changed since they were fetched")]
+    PreconditionFailed,
+
+    #[error("Manager approval is required")]
+    ManagerApprovalRequired,
+
+    #[error("Missing or invalid credentials")]
+    Unauthorized,
+
+    #[error("API key not found")]
+    ApiKeyNotFound,
+
+    #[error("Role of credential is not allowed to perform this action")]
+    Forbidden,
+
+    #[error("Staff not found")]
+    StaffNotFound,
+
+    #[error("Staff with the same id already exists")]
+    StaffAlreadyExists,
+
+    #[error("Too many requests, retry after {0} seconds")]
+    TooManyRequests(u64),
+
+    #[error("Illegal order status transition from {0} to {1}")]
+    IllegalStatusTransition(OrderStatus, OrderStatus),
+
     #[error(transparent)]
     InvalidJsonRequest(#[from] JsonRejection),
 
     #[error(transparent)]
-    InvalidPathRequest(#[from] PathRejection)
+    InvalidPathRequest(#[from] PathRejection),
+
+    #[error(transparent)]
+    InvalidQueryRequest(#[from] QueryRejection)
 }
 
 impl ApiError{
@@ -41,8 +102,28 @@ impl ApiError{
             ApiError::ServerError(_) => 500,
             ApiError::TableNotFound => 404,
             ApiError::OrderNotFound => 404,
+            ApiError::MenuItemNotFound => 404,
+            ApiError::BillNotFound => 404,
+            ApiError::MenuItemAlreadyExists => 409,
+            ApiError::TableInUse => 409,
+            ApiError::MenuItemInUse => 409,
+            ApiError::SessionNotFound => 404,
+            ApiError::SessionAlreadyOpen => 409,
+            ApiError::SessionHasOrders => 409,
+            ApiError::IdempotencyKeyReused => 422,
+            ApiError::IdempotentRequestInProgress => 409,
+            ApiError::PreconditionFailed => 412,
+            ApiError::ManagerApprovalRequired => 403,
+            ApiError::Unauthorized => 401,
+            ApiError::ApiKeyNotFound => 404,
+            ApiError::Forbidden => 403,
+            ApiError::StaffNotFound => 404,
+            ApiError::StaffAlreadyExists => 409,
+            ApiError::TooManyRequests(_) => 429,
+            ApiError::IllegalStatusTransition(_, _) => 409,
             ApiError::InvalidJsonRequest(_) => 400,
-            ApiError::InvalidPathRequest(_) => 400
+            ApiError::InvalidPathRequest(_) => 400,
+            ApiError::InvalidQueryRequest(_) => 400
         }
     }
 
@@ -54,6 +135,7 @@ impl ApiError{
 
 impl IntoResponse for ApiError{
     /// custom response function for ApiError
+    #[allow(clippy::useless_format)]
     fn into_response(self) -> axum::response::Response {
         
         let error_message = match &self{
@@ -62,17 +144,43 @@ impl IntoResponse for ApiError{
             ApiError::ServerError(err) => format!("Server error -> {err}"),
             ApiError::TableNotFound => format!("Table not found"),
             ApiError::OrderNotFound => format!("Order not found"),
+            ApiError::MenuItemNotFound => "Menu item not found".to_string(),
+            ApiError::BillNotFound => "Bill not found".to_string(),
+            ApiError::MenuItemAlreadyExists => "Conflict -> menu item with the same name already exists".to_string(),
+            ApiError::TableInUse => "Conflict -> table still has orders (checkout or remove them first)".to_string(),
+            ApiError::MenuItemInUse => "Conflict -> menu item is referenced by open orders (deactivate it instead)".to_string(),
+            ApiError::SessionNotFound => "Seating session not found".to_string(),
+            ApiError::SessionAlreadyOpen => "Conflict -> table already has an open seating session (close it first)".to_string(),
+            ApiError::SessionHasOrders => "Conflict -> seating session still has unbilled orders (check out or remove them first)".to_string(),
+            ApiError::IdempotencyKeyReused => "Unprocessable request -> idempotency key was already used with a different request".to_string(),
+            ApiError::IdempotentRequestInProgress => "Conflict -> request with the same idempotency key is still in progress (retry later)".to_string(),
+            ApiError::PreconditionFailed => "Precondition failed -> table orders were changed by another client (fetch them again)".to_string(),
+            ApiError::ManagerApprovalRequired => "Forbidden -> voiding this order requires manager approval".to_string(),
+            ApiError::Unauthorized => "Unauthorized -> missing or invalid credentials (API key, token or PIN)".to_string(),
+            ApiError::ApiKeyNotFound => "API key not found".to_string(),
+            ApiError::Forbidden => "Forbidden -> role of credential is not allowed to perform this action".to_string(),
+            ApiError::StaffNotFound => "Staff not found".to_string(),
+            ApiError::StaffAlreadyExists => "Conflict -> staff with the same id already exists".to_string(),
+            ApiError::TooManyRequests(retry_after) => format!("Too many requests -> retry after {retry_after} seconds"),
+            ApiError::IllegalStatusTransition(from, to) => format!("Conflict -> order status cannot be changed from {from} to {to}"),
             ApiError::InvalidJsonRequest(_err) => format!("Bad request -> Json request payload is incorrect"),
-            ApiError::InvalidPathRequest(_err) => format!("Bad request -> parameters in path are incorrect")
+            ApiError::InvalidPathRequest(_err) => format!("Bad request -> parameters in path are incorrect"),
+            ApiError::InvalidQueryRequest(_err) => "Bad request -> query parameters are incorrect".to_string()
         };
 
-        (
+        let mut response = (
             self.axum_status_code(), 
             axum::extract::Json(json!({
                 "status_code": self.status_code(),
                 "error_cause": error_message
             }))
-        ).into_response()
+        ).into_response();
+
+        // rate limited clients are told when to retry
+        if let ApiError::TooManyRequests(retry_after) = self{
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
         
     }
 }
@@ -83,6 +191,7 @@ mod test{
     use axum::http::StatusCode;
 
     use super::ApiError;
+    use crate::model::OrderStatus;
 
 
     #[test]
@@ -92,6 +201,25 @@ mod test{
         let server_error = ApiError::ServerError("hello".to_string());
         let table_not_found = ApiError::TableNotFound;
         let order_not_found = ApiError::OrderNotFound;
+        let illegal_transition = ApiError::IllegalStatusTransition(OrderStatus::Served, OrderStatus::Cancelled);
+        let menu_item_not_found = ApiError::MenuItemNotFound;
+        let menu_item_already_exists = ApiError::MenuItemAlreadyExists;
+        let bill_not_found = ApiError::BillNotFound;
+        let table_in_use = ApiError::TableInUse;
+        let menu_item_in_use = ApiError::MenuItemInUse;
+        let session_not_found = ApiError::SessionNotFound;
+        let session_already_open = ApiError::SessionAlreadyOpen;
+        let session_has_orders = ApiError::SessionHasOrders;
+        let idempotency_key_reused = ApiError::IdempotencyKeyReused;
+        let idempotent_request_in_progress = ApiError::IdempotentRequestInProgress;
+        let precondition_failed = ApiError::PreconditionFailed;
+        let manager_approval_required = ApiError::ManagerApprovalRequired;
+        let unauthorized = ApiError::Unauthorized;
+        let api_key_not_found = ApiError::ApiKeyNotFound;
+        let forbidden = ApiError::Forbidden;
+        let staff_not_found = ApiError::StaffNotFound;
+        let staff_already_exists = ApiError::StaffAlreadyExists;
+        let too_many_requests = ApiError::TooManyRequests(30);
 
         assert_eq!(db_error.status_code(), 500);
         assert_eq!(db_error.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
@@ -109,6 +237,74 @@ mod test{
         
         assert_eq!(order_not_found.status_code(), 404);
         assert_eq!(order_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(illegal_transition.status_code(), 409);
+        assert_eq!(illegal_transition.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(menu_item_not_found.status_code(), 404);
+        assert_eq!(menu_item_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(menu_item_already_exists.status_code(), 409);
+        assert_eq!(menu_item_already_exists.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(bill_not_found.status_code(), 404);
+        assert_eq!(bill_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(table_in_use.status_code(), 409);
+        assert_eq!(table_in_use.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(menu_item_in_use.status_code(), 409);
+        assert_eq!(menu_item_in_use.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(session_not_found.status_code(), 404);
+        assert_eq!(session_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(session_already_open.status_code(), 409);
+        assert_eq!(session_already_open.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(session_has_orders.status_code(), 409);
+        assert_eq!(session_has_orders.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(idempotency_key_reused.status_code(), 422);
+        assert_eq!(idempotency_key_reused.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        assert_eq!(idempotent_request_in_progress.status_code(), 409);
+        assert_eq!(idempotent_request_in_progress.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(precondition_failed.status_code(), 412);
+        assert_eq!(precondition_failed.status_code(), StatusCode::PRECONDITION_FAILED);
+
+        assert_eq!(manager_approval_required.status_code(), 403);
+        assert_eq!(manager_approval_required.status_code(), StatusCode::FORBIDDEN);
+
+        assert_eq!(unauthorized.status_code(), 401);
+        assert_eq!(unauthorized.status_code(), StatusCode::UNAUTHORIZED);
+
+        assert_eq!(api_key_not_found.status_code(), 404);
+        assert_eq!(api_key_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(forbidden.status_code(), 403);
+        assert_eq!(forbidden.status_code(), StatusCode::FORBIDDEN);
+
+        assert_eq!(staff_not_found.status_code(), 404);
+        assert_eq!(staff_not_found.status_code(), StatusCode::NOT_FOUND);
+
+        assert_eq!(staff_already_exists.status_code(), 409);
+        assert_eq!(staff_already_exists.status_code(), StatusCode::CONFLICT);
+
+        assert_eq!(too_many_requests.status_code(), 429);
+        assert_eq!(too_many_requests.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+
+    #[test]
+    fn test_retry_after_header(){
+        use axum::response::IntoResponse;
+
+        let response = ApiError::TooManyRequests(30).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[axum::http::header::RETRY_AFTER], "30");
+        assert!(ApiError::Forbidden.into_response().headers().get(axum::http::header::RETRY_AFTER).is_none());
     }
 
 
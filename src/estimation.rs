@@ -0,0 +1,228 @@
+use std::sync::{Arc, Mutex};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Deserialize;
+
+use crate::config::AppConfig;
+
+
+/// preparation time (minutes) of items without menu preparation time
+const DEFAULT_PREP_TIME_MINUTES: i64 = 10;
+
+
+/// Information available to estimation strategy for a single order item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimationInput<'a>{
+    /// ordered item name
+    pub item_name: &'a str,
+
+    /// preparation time of the item from menu catalog (None if the item is not in the menu)
+    pub prep_time_minutes: Option<i32>,
+
+    /// number of unfinished orders (ordered or cooking) in the kitchen queue before this item
+    pub pending_orders: i64
+}
+
+
+/// Strategy to estimate how long (in minutes) an order item takes to be ready.
+/// The strategy is shared by all handlers through ApiContext, so it must be thread-safe
+pub trait EstimationStrategy: Send + Sync{
+    fn estimate_minutes(&self, input: &EstimationInput) -> i64;
+}
+
+
+/// Estimation strategy selected via `ESTIMATION_STRATEGY` config
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimationStrategyKind{
+    /// uniform random time between 5 and 15 minutes (seedable with `ESTIMATION_SEED`)
+    #[default]
+    Random,
+
+    /// preparation time of the item from menu catalog
+    Menu,
+
+    /// menu preparation time plus waiting time of unfinished orders in the kitchen queue
+    KitchenLoad
+}
+
+
+/// Uniform random estimation between min and max minutes (inclusive).
+/// It can be seeded so that estimations are reproducible (e.g., in tests)
+pub struct UniformRandomEstimation{
+    min_minutes: i64,
+    max_minutes: i64,
+    rng: Mutex<StdRng>
+}
+
+
+impl UniformRandomEstimation{
+    /// constructor of random estimation. If seed is None, random generator is seeded from OS entropy
+    pub fn new(min_minutes: i64, max_minutes: i64, seed: Option<u64>) -> Self{
+        let rng = match seed{
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy()
+        };
+
+        Self{
+            min_minutes,
+            max_minutes,
+            rng: Mutex::new(rng)
+        }
+    }
+}
+
+
+impl Default for UniformRandomEstimation{
+    /// default random estimation is 5-15 minutes (not seeded)
+    fn default() -> Self {
+        Self::new(5, 15, None)
+    }
+}
+
+
+impl EstimationStrategy for UniformRandomEstimation{
+    fn estimate_minutes(&self, _input: &EstimationInput) -> i64 {
+        // generator state is still valid even if another thread panicked while holding the lock
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        rng.gen_range(self.min_minutes..=self.max_minutes)
+    }
+}
+
+
+/// Fixed estimation from menu item preparation time. Items without preparation time use default minutes
+pub struct MenuPrepTimeEstimation{
+    default_minutes: i64
+}
+
+
+impl MenuPrepTimeEstimation{
+    /// constructor of menu based estimation
+    pub fn new(default_minutes: i64) -> Self{
+        Self{ default_minutes }
+    }
+}
+
+
+impl EstimationStrategy for MenuPrepTimeEstimation{
+    fn estimate_minutes(&self, input: &EstimationInput) -> i64 {
+        input.prep_time_minutes
+            .map(i64::from)
+            .unwrap_or(self.default_minutes)
+    }
+}
+
+
+/// Kitchen-load-aware estimation. It is menu preparation time plus waiting time of unfinished orders,
+/// assuming that the kitchen can work on `parallel_orders` orders at the same time and each order takes `minutes_per_order` in average
+pub struct KitchenLoadEstimation{
+    menu_estimation: MenuPrepTimeEstimation,
+    minutes_per_order: i64,
+    parallel_orders: i64
+}
+
+
+impl KitchenLoadEstimation{
+    /// constructor of kitchen load estimation. parallel_orders less than 1 is treated as 1
+    pub fn new(default_minutes: i64, minutes_per_order: i64, parallel_orders: i64) -> Self{
+        Self{
+            menu_estimation: MenuPrepTimeEstimation::new(default_minutes),
+            minutes_per_order,
+            parallel_orders: parallel_orders.max(1)
+        }
+    }
+}
+
+
+impl EstimationStrategy for KitchenLoadEstimation{
+    fn estimate_minutes(&self, input: &EstimationInput) -> i64 {
+        let waiting_minutes = input.pending_orders / self.parallel_orders * self.minutes_per_order;
+        self.menu_estimation.estimate_minutes(input) + waiting_minutes
+    }
+}
+
+
+/// function to build estimation strategy selected in config
+pub fn new_estimation_strategy(config: &AppConfig) -> Arc<dyn EstimationStrategy>{
+    match config.get_estimation_strategy(){
+        EstimationStrategyKind::Random => Arc::new(UniformRandomEstimation::new(5, 15, config.get_estimation_seed())),
+        EstimationStrategyKind::Menu => Arc::new(MenuPrepTimeEstimation::new(DEFAULT_PREP_TIME_MINUTES)),
+        EstimationStrategyKind::KitchenLoad => Arc::new(KitchenLoadEstimation::new(
+            DEFAULT_PREP_TIME_MINUTES, 
+            config.get_kitchen_minutes_per_order(), 
+            config.get_kitchen_parallel_orders()
+        ))
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+    fn input(prep_time_minutes: Option<i32>, pending_orders: i64) -> EstimationInput<'static>{
+        EstimationInput{
+            item_name: "Kapao",
+            prep_time_minutes,
+            pending_orders
+        }
+    }
+
+
+    #[test]
+    fn test_uniform_random_estimation(){
+        let estimation = UniformRandomEstimation::default();
+        for _ in 0..100{
+            let minutes = estimation.estimate_minutes(&input(None, 0));
+            assert!((5..=15).contains(&minutes));
+        }
+
+        // same seed -> same estimations
+        let first = UniformRandomEstimation::new(5, 15, Some(42));
+        let second = UniformRandomEstimation::new(5, 15, Some(42));
+        let first_results: Vec<i64> = (0..20).map(|_| first.estimate_minutes(&input(None, 0))).collect();
+        let second_results: Vec<i64> = (0..20).map(|_| second.estimate_minutes(&input(None, 0))).collect();
+        assert_eq!(first_results, second_results);
+    }
+
+
+    #[test]
+    fn test_menu_prep_time_estimation(){
+        let estimation = MenuPrepTimeEstimation::new(10);
+        assert_eq!(estimation.estimate_minutes(&input(Some(3), 100)), 3);
+        assert_eq!(estimation.estimate_minutes(&input(None, 100)), 10);
+    }
+
+
+    #[test]
+    fn test_kitchen_load_estimation(){
+        let estimation = KitchenLoadEstimation::new(10, 4, 2);
+        assert_eq!(estimation.estimate_minutes(&input(Some(3), 0)), 3);
+        assert_eq!(estimation.estimate_minutes(&input(Some(3), 1)), 3);
+        assert_eq!(estimation.estimate_minutes(&input(Some(3), 5)), 11);
+        assert_eq!(estimation.estimate_minutes(&input(None, 6)), 22);
+
+        let estimation = KitchenLoadEstimation::new(10, 4, 0);
+        assert_eq!(estimation.estimate_minutes(&input(Some(3), 2)), 11);
+    }
+
+
+    #[test]
+    fn test_new_estimation_strategy_from_config(){
+        let mut config = AppConfig{
+            estimation_strategy: Some(EstimationStrategyKind::Menu),
+            ..AppConfig::default()
+        };
+        assert_eq!(new_estimation_strategy(&config).estimate_minutes(&input(None, 8)), DEFAULT_PREP_TIME_MINUTES);
+
+        config.estimation_strategy = Some(EstimationStrategyKind::KitchenLoad);
+        assert_eq!(new_estimation_strategy(&config).estimate_minutes(&input(Some(3), 8)), 7);
+
+        config.estimation_strategy = None;
+        config.estimation_seed = Some(7);
+        let first = new_estimation_strategy(&config);
+        let second = new_estimation_strategy(&config);
+        assert_eq!(first.estimate_minutes(&input(None, 0)), second.estimate_minutes(&input(None, 0)));
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use sqlx::postgres::{PgListener, PgNotification};
+use tokio::task::JoinHandle;
+
+use crate::context::ApiContext;
+use crate::dao::order_dao::TableOrderDAO;
+use crate::dao::pg_order_dao::{PgTableOrderDAO, OrderNotification, ORDER_EVENTS_CHANNEL};
+use crate::error::ApiError;
+
+
+/// waiting time before listening again when postgres can't be reached
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+
+/// helper function to publish order change notified by another server instance to the local streaming clients.
+/// The event gets an id of this instance's epoch, so clients moving between instances get resync instead of resuming from a foreign id
+async fn relay_notification(context: &ApiContext<PgTableOrderDAO>, notification: &PgNotification){
+    let notification: OrderNotification = match serde_json::from_str(notification.payload()){
+        Ok(notification) => notification,
+        Err(err) => {
+            tracing::error!("[relay] invalid order notification {}: {err}", notification.payload());
+            return;
+        }
+    };
+
+    // changes made by this instance are already published by the handlers
+    if notification.instance_id == context.dbo.instance_id{
+        return;
+    }
+
+    match context.dbo.get_table_orders(notification.table_id, None).await{
+        Ok(orders) => { context.events.publish(notification.event_type, notification.table_id, notification.order_id, orders); },
+        Err(err) => tracing::error!("[relay] failed to fetch orders of table {}: {err}", notification.table_id)
+    }
+}
+
+
+/// helper function to connect a new listener to the order events channel
+async fn listen(context: &ApiContext<PgTableOrderDAO>) -> Result<PgListener, ApiError>{
+    let mut listener = PgListener::connect_with(&context.dbo.db).await
+        .map_err(ApiError::DatabaseError)?;
+    listener.listen(ORDER_EVENTS_CHANNEL).await
+        .map_err(ApiError::DatabaseError)?;
+    Ok(listener)
+}
+
+
+/// helper function to listen again after the connection is lost. Changes of other instances during the outage are lost,
+/// so streaming clients are told to re-fetch the orders once the channel is listened again (changes after that are relayed)
+async fn reconnect(context: &ApiContext<PgTableOrderDAO>) -> PgListener{
+    loop{
+        match listen(context).await{
+            Ok(listener) => {
+                tracing::info!("[relay] listening order changes again, streaming clients are resynced");
+                context.events.publish_resync();
+                return listener;
+            },
+            Err(err) => {
+                tracing::error!("[relay] failed to listen order changes: {err}");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+
+/// function to relay order changes made by other server instances (postgres NOTIFY) to the clients streaming from this instance.
+/// It returns after the channel is listened, so changes committed afterwards are never missed. If the connection is lost,
+/// it listens again and publishes resync event
+pub async fn spawn_order_event_relay(context: ApiContext<PgTableOrderDAO>) -> Result<JoinHandle<()>, ApiError>{
+    let mut listener = listen(&context).await?;
+
+    tracing::info!("[relay] listening order changes of other instances (instance id = {})", context.dbo.instance_id);
+
+    Ok(tokio::spawn(async move {
+        loop{
+            match listener.try_recv().await{
+                Ok(Some(notification)) => relay_notification(&context, &notification).await,
+                Ok(None) => {
+                    tracing::warn!("[relay] connection to postgres is lost, order changes of other instances may be missed");
+                    listener = reconnect(&context).await;
+                },
+                Err(err) => {
+                    tracing::error!("[relay] failed to receive order notification: {err}");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                    listener = reconnect(&context).await;
+                }
+            }
+        }
+    }))
+}
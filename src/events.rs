@@ -0,0 +1,402 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::model::OrderItem;
+
+
+/// number of recent events kept for clients resuming a stream (`Last-Event-ID`)
+pub const EVENT_HISTORY_SIZE: usize = 1024;
+
+
+/// Type of order change which is pushed to streaming clients
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventType{
+    /// new orders are added to a table
+    OrdersAdded,
+
+    /// an order is removed from a table
+    OrderRemoved,
+
+    /// status of an order is changed
+    OrderStatusChanged,
+
+    /// quantity of an order is decremented (the order is removed if quantity reaches 0)
+    OrderQuantityChanged,
+
+    /// table is checked out (billed orders are cleared)
+    TableCheckedOut,
+
+    /// an order is acknowledged by a device (e.g., kitchen has seen the order)
+    OrderAcknowledged,
+
+    /// orders are moved between tables (published for both source and target tables)
+    OrdersTransferred,
+
+    /// a party is seated at a table (new seating session)
+    SessionOpened,
+
+    /// seating session of a table is closed (the party left, so its orders are no longer on the table)
+    SessionClosed,
+
+    /// order changes of other server instances may be lost (e.g., connection to postgres was lost), so clients should re-fetch the orders.
+    /// It is sent to clients of every table (table_id is 0 and orders are empty)
+    Resync
+}
+
+
+impl OrderEventType{
+    /// function to get event type name (same as json value), which is used as SSE event name
+    pub fn as_str(&self) -> &'static str{
+        match self{
+            Self::OrdersAdded => "orders_added",
+            Self::OrderRemoved => "order_removed",
+            Self::OrderStatusChanged => "order_status_changed",
+            Self::OrderQuantityChanged => "order_quantity_changed",
+            Self::TableCheckedOut => "table_checked_out",
+            Self::OrderAcknowledged => "order_acknowledged",
+            Self::OrdersTransferred => "orders_transferred",
+            Self::SessionOpened => "session_opened",
+            Self::SessionClosed => "session_closed",
+            Self::Resync => "resync"
+        }
+    }
+}
+
+
+impl Display for OrderEventType{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+
+/// This struct represents an order change pushed to streaming clients.
+/// It carries the table's orders after the change, so clients don't need to re-fetch the table
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderEvent{
+    /// epoch of event ids (random per event bus), so that ids of another server process or instance are not mistaken for this one's
+    pub epoch: String,
+
+    /// increasing event id within the epoch
+    pub event_id: u64,
+
+    /// type of the change
+    pub event_type: OrderEventType,
+
+    /// table of the changed orders
+    pub table_id: i16,
+
+    /// changed order (None if several orders are changed)
+    pub order_id: Option<i32>,
+
+    /// orders of the table after the change
+    pub orders: Vec<OrderItem>
+}
+
+
+impl OrderEvent{
+    /// function to check whether clients of a table should receive the event (resync event is for every table)
+    pub fn is_for_table(&self, table_id: i16) -> bool{
+        self.event_type == OrderEventType::Resync || self.table_id == table_id
+    }
+}
+
+
+/// Last event received by a client resuming a stream (`{epoch}:{event_id}`, which is used as SSE event id)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastEventId{
+    pub epoch: String,
+    pub event_id: u64
+}
+
+
+impl Display for LastEventId{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.epoch, self.event_id)
+    }
+}
+
+
+impl FromStr for LastEventId{
+    type Err = std::num::ParseIntError;
+
+    /// id without epoch (e.g., sent by an old client) is parsed with empty epoch, which never matches
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (epoch, event_id) = value.split_once(':').unwrap_or(("", value));
+        Ok(Self{
+            epoch: epoch.to_string(),
+            event_id: event_id.parse()?
+        })
+    }
+}
+
+
+impl From<&OrderEvent> for LastEventId{
+    fn from(event: &OrderEvent) -> Self {
+        Self{
+            epoch: event.epoch.clone(),
+            event_id: event.event_id
+        }
+    }
+}
+
+
+/// Subscription to order events. Events published after `last_event_id` which are still in history are returned
+/// as `missed` events, and later events are received from `receiver` (without gap or duplicate)
+pub struct OrderEventSubscription{
+    /// events after the requested event id (oldest first)
+    pub missed: Vec<OrderEvent>,
+
+    /// false if some events after the requested event id are not in history anymore (client should re-fetch the orders)
+    pub complete: bool,
+
+    /// receiver of new events
+    pub receiver: broadcast::Receiver<OrderEvent>
+}
+
+
+/// recent events and id of the latest event
+#[derive(Default)]
+struct EventHistory{
+    last_event_id: u64,
+    events: VecDeque<OrderEvent>
+}
+
+
+/// In-process bus of order events. It assigns increasing ids to events, broadcasts them to subscribers
+/// and keeps recent events so that reconnecting clients can resume from the last received event.
+/// Ids restart from 1 with a new random epoch, so ids from before a restart (or from another instance) are detected
+pub struct OrderEventBus{
+    epoch: String,
+    sender: broadcast::Sender<OrderEvent>,
+    history: Mutex<EventHistory>,
+    history_size: usize
+}
+
+
+impl OrderEventBus{
+    /// constructor of event bus which keeps `history_size` recent events (at least 1)
+    pub fn new(history_size: usize) -> Self{
+        let history_size = history_size.max(1);
+        let (sender, _) = broadcast::channel(history_size);
+
+        Self{
+            epoch: format!("{:016x}", rand::random::<u64>()),
+            sender,
+            history: Mutex::new(EventHistory::default()),
+            history_size
+        }
+    }
+
+
+    /// function to get epoch of event ids published by this bus
+    pub fn epoch(&self) -> &str{
+        &self.epoch
+    }
+
+
+    /// function to publish order change. It returns the published event (with assigned event id)
+    pub fn publish(&self, event_type: OrderEventType, table_id: i16, order_id: Option<i32>, orders: Vec<OrderItem>) -> OrderEvent{
+        // history is still valid even if another thread panicked while holding the lock
+        let mut history = self.history.lock().unwrap_or_else(|err| err.into_inner());
+        history.last_event_id += 1;
+
+        let event = OrderEvent{
+            epoch: self.epoch.clone(),
+            event_id: history.last_event_id,
+            event_type,
+            table_id,
+            order_id,
+            orders
+        };
+
+        if history.events.len() == self.history_size{
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+
+        // sending under the lock keeps events in id order for all subscribers. It fails only if nobody is subscribed
+        let _ = self.sender.send(event.clone());
+        event
+    }
+
+
+    /// function to tell all streaming clients to re-fetch the orders. Resync event is kept in history as well,
+    /// so that clients resuming from an earlier event receive it too
+    pub fn publish_resync(&self) -> OrderEvent{
+        self.publish(OrderEventType::Resync, 0, None, Vec::new())
+    }
+
+
+    /// function to subscribe to events published after `last_event_id` (None to receive new events only).
+    /// Id of another epoch is not complete, and no events are returned as missed (client should re-fetch the orders)
+    pub fn subscribe(&self, last_event_id: Option<LastEventId>) -> OrderEventSubscription{
+        let history = self.history.lock().unwrap_or_else(|err| err.into_inner());
+        let receiver = self.sender.subscribe();
+
+        let Some(LastEventId{ epoch, event_id: last_event_id }) = last_event_id else{
+            return OrderEventSubscription{ missed: Vec::new(), complete: true, receiver };
+        };
+        if epoch != self.epoch{
+            return OrderEventSubscription{ missed: Vec::new(), complete: false, receiver };
+        }
+
+        // id newer than the latest event is not complete either. Requested id is sent by client (e.g., u64::MAX), so it must not overflow
+        let oldest_event_id = history.events.front().map_or(history.last_event_id.saturating_add(1), |event| event.event_id);
+        let complete = last_event_id.saturating_add(1) >= oldest_event_id && last_event_id <= history.last_event_id;
+        let missed = history.events.iter()
+            .filter(|event| event.event_id > last_event_id)
+            .cloned()
+            .collect();
+
+        OrderEventSubscription{ missed, complete, receiver }
+    }
+}
+
+
+impl Default for OrderEventBus{
+    /// default event bus keeps EVENT_HISTORY_SIZE recent events
+    fn default() -> Self {
+        Self::new(EVENT_HISTORY_SIZE)
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+    fn event_ids(events: &[OrderEvent]) -> Vec<u64>{
+        events.iter().map(|event| event.event_id).collect()
+    }
+
+
+    fn last_event_id(bus: &OrderEventBus, event_id: u64) -> Option<LastEventId>{
+        Some(LastEventId{ epoch: bus.epoch().to_string(), event_id })
+    }
+
+
+    #[tokio::test]
+    async fn test_publish_and_subscribe(){
+        let bus = OrderEventBus::new(10);
+        bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+
+        let mut subscription = bus.subscribe(None);
+        assert!(subscription.complete);
+        assert!(subscription.missed.is_empty());
+
+        let event = bus.publish(OrderEventType::OrderStatusChanged, 2, Some(5), Vec::new());
+        assert_eq!(event.event_id, 2);
+        assert_eq!(subscription.receiver.recv().await.unwrap(), event);
+    }
+
+
+    #[tokio::test]
+    async fn test_resume_from_last_event_id(){
+        let bus = OrderEventBus::new(3);
+        for table_id in 1..=5{
+            bus.publish(OrderEventType::OrdersAdded, table_id, None, Vec::new());
+        }
+
+        // events 3, 4 and 5 are still in history
+        let subscription = bus.subscribe(last_event_id(&bus, 3));
+        assert!(subscription.complete);
+        assert_eq!(event_ids(&subscription.missed), vec![4, 5]);
+
+        let subscription = bus.subscribe(last_event_id(&bus, 2));
+        assert!(subscription.complete);
+        assert_eq!(event_ids(&subscription.missed), vec![3, 4, 5]);
+
+        let subscription = bus.subscribe(last_event_id(&bus, 5));
+        assert!(subscription.complete);
+        assert!(subscription.missed.is_empty());
+
+        // event 2 is not in history anymore
+        let mut subscription = bus.subscribe(last_event_id(&bus, 1));
+        assert!(!subscription.complete);
+        assert_eq!(event_ids(&subscription.missed), vec![3, 4, 5]);
+
+        // no gap between missed and new events
+        bus.publish(OrderEventType::OrderRemoved, 1, Some(1), Vec::new());
+        assert_eq!(subscription.receiver.recv().await.unwrap().event_id, 6);
+    }
+
+
+    #[test]
+    fn test_resume_from_unknown_event_id(){
+        let bus = OrderEventBus::new(3);
+        let subscription = bus.subscribe(last_event_id(&bus, 0));
+        assert!(subscription.complete);
+
+        // id newer than the latest event
+        bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+        let subscription = bus.subscribe(last_event_id(&bus, 10));
+        assert!(!subscription.complete);
+        assert!(subscription.missed.is_empty());
+
+        let subscription = bus.subscribe(last_event_id(&bus, u64::MAX));
+        assert!(!subscription.complete);
+        assert!(subscription.missed.is_empty());
+    }
+
+
+    #[test]
+    fn test_resume_from_another_epoch(){
+        let bus = OrderEventBus::new(3);
+        let restarted_bus = OrderEventBus::new(3);
+        assert_ne!(bus.epoch(), restarted_bus.epoch());
+
+        let event = bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+        bus.publish(OrderEventType::OrdersAdded, 2, None, Vec::new());
+        assert!(bus.subscribe(Some(LastEventId::from(&event))).complete);
+
+        // same id of the restarted server (or another instance) is not mistaken for the same event
+        restarted_bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+        restarted_bus.publish(OrderEventType::OrdersAdded, 2, None, Vec::new());
+        let subscription = restarted_bus.subscribe(Some(LastEventId::from(&event)));
+        assert!(!subscription.complete);
+        assert!(subscription.missed.is_empty());
+
+        // id without epoch doesn't match either
+        let subscription = bus.subscribe(Some("1".parse().unwrap()));
+        assert!(!subscription.complete);
+    }
+
+
+    #[tokio::test]
+    async fn test_publish_resync(){
+        let bus = OrderEventBus::new(3);
+        let event = bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+        assert!(event.is_for_table(1));
+        assert!(!event.is_for_table(2));
+
+        let mut subscription = bus.subscribe(None);
+        let resync = bus.publish_resync();
+        assert_eq!(resync.event_type, OrderEventType::Resync);
+        assert!(resync.is_for_table(1) && resync.is_for_table(2));
+        assert_eq!(subscription.receiver.recv().await.unwrap(), resync);
+
+        // client resuming from before the resync receives it as missed event
+        let subscription = bus.subscribe(Some(LastEventId::from(&event)));
+        assert!(subscription.complete);
+        assert_eq!(subscription.missed, vec![resync]);
+    }
+
+
+    #[test]
+    fn test_parse_last_event_id(){
+        let last_event_id: LastEventId = "0123abcd:42".parse().unwrap();
+        assert_eq!(last_event_id, LastEventId{ epoch: "0123abcd".to_string(), event_id: 42 });
+        assert_eq!(last_event_id.to_string(), "0123abcd:42");
+
+        assert_eq!("42".parse::<LastEventId>().unwrap(), LastEventId{ epoch: String::new(), event_id: 42 });
+        assert!("0123abcd:abc".parse::<LastEventId>().is_err());
+    }
+}
@@ -1,47 +1,89 @@
 use axum::Json;
-use axum::extract::{State, Path};
-use axum::http::StatusCode;
+use axum::extract::{State, Path, Query};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum_extra::extract::WithRejection;
 use serde_json::{json, Value};
 use futures::TryFutureExt;
 
-use crate::dao::order_dao::TableOrderDAO;
+use crate::dao::StorageDAO;
 use crate::error::ApiError;
-use crate::model::{TableOrdersRequest, TableOrdersResponse, OrderItem, OrderItemRequest};
+use crate::model::{TableOrdersRequest, TableOrdersResponse, OrderItem, OrderItemRequest, OrderStatus, OrderStatusUpdateRequest, OrderQuantityDecrementRequest, TransferOrdersRequest, MergeTablesRequest, TableTransferResponse, TableOrdersQuery, RemoveOrderQuery, KitchenQueueQuery, KitchenQueueResponse, MenuItem, Role, MAX_ORDER_QUANTITY};
+use crate::auth::{RequireRole, KitchenAccess, StaffAccess, WaiterAccess};
 use crate::context::ApiContext;
+use crate::config::AppConfig;
+use crate::events::OrderEventType;
+use crate::idempotency::{parse_idempotency_key, request_fingerprint, with_idempotency_key};
+use crate::metrics::metrics;
 use chrono::{DateTime,Duration, Utc};
 
 
-use rand::{thread_rng, Rng};
+use crate::estimation::{EstimationInput, EstimationStrategy, UniformRandomEstimation};
 
 /// Internal function to convert OrderItemRequest to order item. 
-/// During transformation, it assign estimated arrival time for each order
-fn process_order_request(order_request:  OrderItemRequest, current_time: DateTime<Utc>) -> OrderItem{
-    let estimated_time = current_time + Duration::minutes(thread_rng().gen_range(5..=15));
+/// During transformation, it assign estimated arrival time (current time + estimated minutes) for each order
+#[allow(clippy::clone_on_copy)]
+fn process_order_request(order_request:  OrderItemRequest, current_time: DateTime<Utc>, estimated_minutes: i64) -> OrderItem{
+    let estimated_time = current_time + Duration::minutes(estimated_minutes);
 
     OrderItem{
         order_id: -1,
         table_id: order_request.table_id,
         item_name: order_request.item_name,
+        quantity: order_request.quantity,
         note: order_request.note.clone(),
         creation_time: current_time.clone(),
         estimated_arrival_time: estimated_time,
+        status: OrderStatus::Ordered,
+        acknowledged_by: None,
+        acknowledgement_time: None,
+        session_id: None,
+        deleted_at: None,
+        deleted_by: None,
+        reason: None,
+        created_by: None,
+        menu_item_id: None,
+        unit_price: None
     }
 }
 
 
-/// Function to estimate order arrival time, and conver OrderItemRequests to OrderItem for inserting to DB
+/// Function to estimate order arrival time with the given strategy, and conver OrderItemRequests to OrderItem for inserting to DB.
+/// Each item is estimated with its menu preparation time (if any) and the kitchen queue length, which grows with every item of the same request.
+/// The menu item id and price are snapshotted onto the order, so the bill doesn't depend on later menu changes
+pub fn process_order_requests_with_estimator(order_request:  TableOrdersRequest, estimator: &dyn EstimationStrategy, menu_items: &[MenuItem], pending_orders: i64) -> Vec<OrderItem>{
+    let current_time: DateTime<Utc> = Utc::now();
+    order_request.orders.into_iter()
+        .zip(pending_orders..)
+        .map(|(order_request, pending_orders)| {
+            let menu_item = menu_items.iter().find(|item| item.name == order_request.item_name);
+            let input = EstimationInput{
+                item_name: &order_request.item_name,
+                prep_time_minutes: menu_item.map(|item| item.prep_time_minutes),
+                pending_orders
+            };
+            let estimated_minutes = estimator.estimate_minutes(&input);
+            OrderItem{
+                menu_item_id: menu_item.map(|item| item.menu_item_id),
+                unit_price: menu_item.map(|item| item.price),
+                ..process_order_request(order_request, current_time, estimated_minutes)
+            }
+        })
+        .collect()
+}
+
+
+/// Function to estimate order arrival time (uniform random 5-15 minutes), and conver OrderItemRequests to OrderItem for inserting to DB
 /// The reason that it consumes Vec instead of reference ($[OrderItemRequest]) is because there is no need to used OrderItemRequest object anymore after calling this function
 pub fn process_order_requests(order_request:  TableOrdersRequest) -> Vec<OrderItem>{
-    let current_time: DateTime<Utc> = Utc::now();
-    order_request.orders.into_iter().map(|order_request| process_order_request(order_request, current_time)).collect()
+    process_order_requests_with_estimator(order_request, &UniformRandomEstimation::default(), &[], 0)
 }
 
 
 
 /// helper function to extract table_id from list of orderitems
 /// It returns true if table id is valid. Otherwise, false is returned
+#[allow(clippy::unnecessary_lazy_evaluations, clippy::obfuscated_if_else)]
 fn validate_table_id_from_orders_requests_and_path(input: &[OrderItemRequest], table_id_from_path: i16) -> bool{
     match input{
         [] => false,
@@ -55,6 +97,45 @@ fn validate_table_id_from_orders_requests_and_path(input: &[OrderItemRequest], t
 
 
 
+/// helper function to check quantity of every order item request. It returns true if all quantities are in 1..=MAX_ORDER_QUANTITY
+fn validate_order_quantities(input: &[OrderItemRequest]) -> bool{
+    input.iter().all(|order| (1..=MAX_ORDER_QUANTITY).contains(&order.quantity))
+}
+
+
+/// helper function to check requested item names against menu items found in storage.
+/// It returns list of invalid items (unknown or inactive), which is empty if all requested items can be ordered
+fn find_invalid_order_items(input: &[OrderItemRequest], menu_items: &[MenuItem]) -> Vec<String>{
+    let mut invalid_items: Vec<String> = Vec::new();
+    for order in input{
+        let reason = match menu_items.iter().find(|item| item.name == order.item_name){
+            None => "unknown",
+            Some(item) if !item.active => "inactive",
+            Some(_) => continue
+        };
+
+        let invalid_item = format!("{} ({reason})", order.item_name);
+        if !invalid_items.contains(&invalid_item){
+            invalid_items.push(invalid_item);
+        }
+    }
+    invalid_items
+}
+
+
+/// helper function to validate that all requested items exist in menu catalog and are active. It returns the requested menu items
+async fn validate_order_items_with_menu<D: StorageDAO>(dbo: &D, input: &[OrderItemRequest]) -> Result<Vec<MenuItem>, ApiError>{
+    let names: Vec<String> = input.iter().map(|order| order.item_name.clone()).collect();
+    let menu_items = dbo.get_menu_items_by_names(&names).await?;
+
+    match find_invalid_order_items(input, &menu_items).as_slice(){
+        [] => Ok(menu_items),
+        invalid_items => Err(ApiError::BadRequest(format!("items are not available in menu: {}", invalid_items.join(", "))))
+    }
+}
+
+
+
 /// utility macro to check input range whether it's from 1..=max_range or not. 
 /// If the input value is out of range, the error_type will be convert to Axum's response and returned to client
 macro_rules! check_range {
@@ -69,6 +150,19 @@ macro_rules! check_range {
 }
 
 
+/// validation macro for checking table id from path against the table registry (unknown or deactivated table is not found).
+/// The error will be convert to Axum's response and returned to client. It is shared by handlers of other modules taking table id from path
+macro_rules! check_table {
+    ($context: expr, $table_id: expr) => {
+        if let Err(err) = $crate::table_handlers::check_active_table(&$context, $table_id).await{
+            tracing::error!("table {} is not available: {err}", $table_id);
+            return axum::response::IntoResponse::into_response(err);
+        }
+    };
+}
+pub(crate) use check_table;
+
+
 /// validation macro for validating table id from OrderItems and id from path
 macro_rules! validate_table_id_from_orders_and_path {
     ( $orders:expr, $table_id_from_path:expr)=>{
@@ -79,11 +173,113 @@ macro_rules! validate_table_id_from_orders_and_path {
 }
 
 
+/// helper function to generate ETag of table's orders from their version
+fn table_orders_etag(table_id: i16, version: i64) -> String{
+    format!("\"{table_id}-{version}\"")
+}
+
+
+/// helper function to read expected versions of tables' orders (table id, version) from `If-Match` header (a list of ETags, e.g., of both tables of a transfer).
+/// It returns empty list if the header is not sent (or `*`), or ApiError::PreconditionFailed if any value is not an ETag of the tables' orders (e.g., ETag of another table)
+fn parse_if_match(headers: &HeaderMap, table_ids: &[i16]) -> Result<Vec<(i16, i64)>, ApiError>{
+    let Some(value) = headers.get(header::IF_MATCH) else{
+        return Ok(Vec::new());
+    };
+
+    let value = value.to_str().map_err(|_| ApiError::PreconditionFailed)?.trim();
+    if value == "*"{
+        return Ok(Vec::new());
+    }
+
+    value.split(',')
+        .map(|etag| etag.trim()
+            .strip_prefix('"')
+            .and_then(|etag| etag.strip_suffix('"'))
+            .and_then(|etag| etag.split_once('-'))
+            .and_then(|(table_id, version)| Some((table_id.parse().ok()?, version.parse().ok()?)))
+            .filter(|(table_id, _)| table_ids.contains(table_id))
+            .ok_or(ApiError::PreconditionFailed))
+        .collect()
+}
+
+
+/// helper function to check whether client's cached orders (`If-None-Match` header) are still current
+fn is_not_modified(headers: &HeaderMap, etag: &str) -> bool{
+    headers.get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag))
+}
+
+
+/// helper function to get table's orders with their version. The version is read first, so a concurrent change makes the ETag stale
+/// (and rejected by If-Match later) instead of labelling older orders with a newer ETag
+async fn get_versioned_table_orders<D: StorageDAO>(context: &ApiContext<D>, table_id: i16) -> Result<(i64, Vec<OrderItem>), ApiError>{
+    let version = context.dbo.get_orders_version(table_id).await?;
+    let orders = context.dbo.get_table_orders(table_id, None).await?;
+    Ok((version, orders))
+}
+
+
+/// validation macro for `If-Match` header. It evaluates to expected versions of the tables' orders, which DAO compares in the transaction of the change,
+/// so the change is rejected (412) if another client changed the tables' orders after this client fetched them
+macro_rules! expected_orders_versions {
+    ($headers: expr, $($table_id: expr),+) => {
+        match parse_if_match(&$headers, &[$($table_id),+]){
+            Ok(expected_versions) => expected_versions,
+            Err(err) => {
+                tracing::error!("If-Match is not an ETag of orders of the tables: {err}");
+                return err.into_response();
+            }
+        }
+    };
+}
+
+
+/// helper function to publish change of table's orders to streaming clients, and generate TableOrdersResponse (with ETag) from the updated orders
+fn publish_table_orders<D: StorageDAO>(context: &ApiContext<D>, event_type: OrderEventType, table_id: i16, order_id: Option<i32>,
+    (version, orders): (i64, Vec<OrderItem>)) -> Response{
+
+    context.events.publish(event_type, table_id, order_id, orders.clone());
+    ([(header::ETAG, table_orders_etag(table_id, version))], TableOrdersResponse::new(200, table_id, orders)).into_response()
+}
+
+
+/// helper function to publish orders of both tables after orders are moved between them, and generate TableTransferResponse (with ETags of both tables) from the updated orders.
+/// `ETag` header is the ETag of source table (same as the response of other changes of its orders)
+async fn publish_transferred_orders<D: StorageDAO>(context: &ApiContext<D>, source_table_id: i16, target_table_id: i16) -> Result<Response, ApiError>{
+    let (source_version, source_orders) = get_versioned_table_orders(context, source_table_id).await?;
+    let (target_version, target_orders) = get_versioned_table_orders(context, target_table_id).await?;
+
+    context.events.publish(OrderEventType::OrdersTransferred, source_table_id, None, source_orders.clone());
+    context.events.publish(OrderEventType::OrdersTransferred, target_table_id, None, target_orders.clone());
+
+    let source_etag = table_orders_etag(source_table_id, source_version);
+    let target_etag = table_orders_etag(target_table_id, target_version);
+    Ok(([(header::ETAG, source_etag.clone())], TableTransferResponse::new(200,
+        TableOrdersResponse::new(200, source_table_id, source_orders),
+        TableOrdersResponse::new(200, target_table_id, target_orders),
+        source_etag, target_etag)).into_response())
+}
+
+
+/// validation macro for checking that orders are moved to another table
+macro_rules! check_target_table {
+    ($context: expr, $source_table_id: expr, $target_table_id: expr) => {
+        check_table!($context, $target_table_id);
+        if $source_table_id == $target_table_id{
+            return ApiError::BadRequest("target table must be different from source table".to_string()).into_response();
+        }
+    };
+}
+
+
 /// handler function for health check operation which checks the db whether it is alive or not 
-pub async fn handle_health_check(State(context): State<ApiContext>) ->  (axum::http::StatusCode, Json<Value>){
+pub async fn handle_health_check<D: StorageDAO>(State(context): State<ApiContext<D>>) ->  (axum::http::StatusCode, Json<Value>){
     tracing::info!("[health check]");
 
-    match sqlx::query("SELECT 1").execute(&context.dbo.db).await{
+    match context.dbo.health_check().await{
         Ok(_) => {
             (
                 StatusCode::OK, 
@@ -99,7 +295,7 @@ pub async fn handle_health_check(State(context): State<ApiContext>) ->  (axum::h
                 StatusCode::INTERNAL_SERVER_ERROR, 
                 json!(
                     {
-                    "status": format!("Database error {err}")
+                    "status": format!("{err}")
                     }
                 ).into()
             )
@@ -110,50 +306,138 @@ pub async fn handle_health_check(State(context): State<ApiContext>) ->  (axum::h
 }
 
 
-/// handler function for processing incoming TableOrderRequests,  insert new orders to database, and then returns the updated table's orders (TableOrderResponse)
-pub async fn handle_add_orders(State(context): State<ApiContext>, 
+/// handler function for Prometheus scraping. Database pool and business gauges are updated from storage first,
+/// and request and query metrics are returned even if storage is unavailable
+pub async fn handle_metrics<D: StorageDAO>(State(context): State<ApiContext<D>>) -> Response{
+    tracing::debug!("[metrics]");
+
+    let metrics = metrics();
+    metrics.set_pool_usage(context.dbo.pool_usage());
+    match context.dbo.count_open_orders_by_table().await{
+        Ok(open_orders) => metrics.set_open_orders(&open_orders),
+        Err(err) => tracing::error!("[metrics] couldn't count open orders: {err}")
+    }
+    match context.dbo.count_unfinished_orders().await{
+        Ok(unfinished_orders) => metrics.unfinished_orders.set(unfinished_orders),
+        Err(err) => tracing::error!("[metrics] couldn't count unfinished orders: {err}")
+    }
+
+    metrics.encode()
+        .map(|text| ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], text).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for processing incoming TableOrderRequests,  insert new orders to database, and then returns the updated table's orders (TableOrderResponse).
+/// If `Idempotency-Key` header is sent, retries with the same key and payload get the response of the first request (orders are added only once).
+/// Orders added with staff token record the staff id (created_by)
+pub async fn handle_add_orders<D: StorageDAO>(State(context): State<ApiContext<D>>,
+                        role: RequireRole<WaiterAccess>,
                         WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>, 
+                        headers: HeaderMap,
                         WithRejection(Json(table_orders), _): WithRejection<Json<TableOrdersRequest>, ApiError>) 
                         ->  Response{
     
-    tracing::info!("[add] table id from path = {}, max_table = {}", table_id, context.config.get_max_tables());
+    tracing::info!("[add] table id from path = {}, staff = {:?}", table_id, role.staff_id());
+
+    let created_by = role.staff_id().map(str::to_string);
+    let idempotency_key = match parse_idempotency_key(&headers){
+        Ok(idempotency_key) => idempotency_key,
+        Err(err) => return err.into_response()
+    };
 
-    check_range!(context.config.get_max_tables(), table_id, ApiError::TableNotFound);
+    match idempotency_key{
+        Some(idempotency_key) => {
+            let fingerprint = match request_fingerprint(table_id, &table_orders){
+                Ok(fingerprint) => fingerprint,
+                Err(err) => return err.into_response()
+            };
+            with_idempotency_key(&context, &idempotency_key, &fingerprint, add_orders(context.clone(), table_id, headers, table_orders, created_by)).await
+        },
+        None => add_orders(context, table_id, headers, table_orders, created_by).await
+    }
+}
+
+
+/// helper function for adding orders of a table order request (used by handle_add_orders with or without idempotency key)
+async fn add_orders<D: StorageDAO>(context: ApiContext<D>, table_id: i16, headers: HeaderMap, table_orders: TableOrdersRequest, created_by: Option<String>) -> Response{
+    check_table!(context, table_id);
     validate_table_id_from_orders_and_path!(&table_orders.orders, table_id);
 
-    let orders = process_order_requests(table_orders);
+    if !validate_order_quantities(&table_orders.orders){
+        return ApiError::BadRequest(format!("order quantity must be between 1 and {MAX_ORDER_QUANTITY}")).into_response();
+    }
+
+    let menu_items = match validate_order_items_with_menu(&context.dbo, &table_orders.orders).await{
+        Ok(menu_items) => menu_items,
+        Err(err) => {
+            tracing::error!("[add] order items validation failed: {err}");
+            return err.into_response();
+        }
+    };
+
+    let pending_orders = match context.dbo.count_unfinished_orders().await{
+        Ok(pending_orders) => pending_orders,
+        Err(err) => return err.into_response()
+    };
+
+    let mut orders = process_order_requests_with_estimator(table_orders, context.estimator.as_ref(), &menu_items, pending_orders);
+    orders.iter_mut().for_each(|order| order.created_by.clone_from(&created_by));
     tracing::info!("[add] adding orders (size= {})", orders.len());
+    let expected_versions = expected_orders_versions!(headers, table_id);
     
-    context.dbo.add_table_orders(&orders) // add orders to a table
-        .and_then( |_| context.dbo.get_table_orders(table_id)) // get updated table orders
+    context.dbo.add_table_orders(&orders, &expected_versions) // add orders to a table (if the table's orders weren't changed since If-Match)
+        .and_then( |_| get_versioned_table_orders(&context, table_id)) // get updated table orders
         .await
-        .and_then( |orders: Vec<OrderItem>| Ok(TableOrdersResponse::new(200,  table_id,  orders).into_response()))// generate TableOrdersResponse from orders
+        .map( |orders| publish_table_orders(&context, OrderEventType::OrdersAdded, table_id, None, orders))// notify streams and generate TableOrdersResponse from orders
         .unwrap_or_else(ApiError::into_response)  // generate error response in case of error
 }
 
 
-/// handler function for getting all table's order (of a specific table). 
-pub async fn handle_get_all_orders_for_specific_table(
-        State(context): State<ApiContext>, 
-        WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) ->  Response{
+/// handler function for getting all table's order (of the current seating session of a specific table).
+/// Orders can be filtered by status (e.g., `?status=cooking`), and orders of a past session can be read with `?session_id=<id>`
+pub async fn handle_get_all_orders_for_specific_table<D: StorageDAO>(
+        State(context): State<ApiContext<D>>,
+        _role: RequireRole<StaffAccess>,
+        WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+        WithRejection(Query(query), _): WithRejection<Query<TableOrdersQuery>, ApiError>,
+        headers: HeaderMap) ->  Response{
 
-    tracing::info!("[get all] table id from path = {table_id}");
-    check_range!(context.config.get_max_tables(), table_id, ApiError::TableNotFound);
+    tracing::info!("[get all] table id from path = {table_id}, status filter = {:?}, session = {:?}", query.status, query.session_id);
+    check_table!(context, table_id);
 
-    context.dbo.get_table_orders(table_id) // get tables order
-        .await
-        .and_then( |orders: Vec<OrderItem>| Ok(TableOrdersResponse::new(200,  table_id,  orders).into_response()))// generate TableOrdersResponse from orders
+    // version is read before orders (see get_versioned_table_orders)
+    let etag = match context.dbo.get_orders_version(table_id).await{
+        Ok(version) => table_orders_etag(table_id, version),
+        Err(err) => return err.into_response()
+    };
+    if is_not_modified(&headers, &etag){
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    let orders = match query.session_id{
+        Some(session_id) => {
+            check_range!(i32::MAX, session_id, ApiError::SessionNotFound);
+            context.dbo.get_session_orders(table_id, session_id, query.status).await // get orders of specific session
+        },
+        None => context.dbo.get_table_orders(table_id, query.status).await // get orders of current session
+    };
+
+    orders
+        .map( |orders: Vec<OrderItem>| ([(header::ETAG, etag)], TableOrdersResponse::new(200,  table_id,  orders)).into_response())// generate TableOrdersResponse (with ETag) from orders
         .unwrap_or_else(ApiError::into_response) // generate error response in case of error
 }
 
 
 //handler function for getting specific order 
-pub async fn handle_get_specific_table_order(State(context): State<ApiContext>, 
+#[allow(clippy::bind_instead_of_map)]
+pub async fn handle_get_specific_table_order<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
     WithRejection(Path((table_id, order_id)), _): WithRejection<Path<(i16, i32)>, ApiError>)  ->  Response{
     
     tracing::info!("[get specific] table id = {table_id}, order_id= {order_id} from path");
     
-    check_range!(context.config.get_max_tables(), table_id, ApiError::TableNotFound);
+    check_table!(context, table_id);
     check_range!(i32::MAX, order_id, ApiError::OrderNotFound);
 
 
@@ -164,48 +448,319 @@ pub async fn handle_get_specific_table_order(State(context): State<ApiContext>,
 }
 
 
+//handler function for getting order history of a table, i.e., all orders of the current seating session including removed ones (for loss tracking)
+pub async fn handle_get_table_order_history<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[history] table id = {table_id} from path");
+
+    check_table!(context, table_id);
+
+    context.dbo.get_table_order_history(table_id) // get orders including removed ones
+        .await
+        .map( |orders: Vec<OrderItem>| TableOrdersResponse::new(200,  table_id,  orders).into_response())// generate TableOrdersResponse from orders
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+/// maximum length of the staff / device name recorded when an order is removed
+const MAX_DELETED_BY_LENGTH: usize = 64;
+
+/// header of the token which manager gives to approve voiding orders (see AppConfig::get_manager_approval_token)
+pub const MANAGER_APPROVAL_HEADER: &str = "x-manager-approval";
+
+
+/// helper function to check that voiding the order is allowed. Orders which have reached the configured status (served by default),
+/// or are older than the configured age, can only be voided by managers or with manager approval token. It returns ApiError::ManagerApprovalRequired otherwise
+fn check_void_approval(config: &AppConfig, headers: &HeaderMap, role: Option<Role>, order: &OrderItem, void_time: DateTime<Utc>) -> Result<(), ApiError>{
+    let is_too_old = config.get_void_approval_minutes()
+        .is_some_and(|minutes| void_time - order.creation_time >= Duration::minutes(minutes));
+    if !is_too_old && !order.status.has_reached(config.get_void_approval_status()){
+        return Ok(());
+    }
+
+    // managers approve their own voids
+    if matches!(role, Some(Role::Manager | Role::Admin)){
+        return Ok(());
+    }
+
+    // approval is impossible if no token is configured
+    let approval = headers.get(MANAGER_APPROVAL_HEADER).and_then(|value| value.to_str().ok());
+    match (config.get_manager_approval_token(), approval){
+        (Some(token), Some(approval)) if token == approval => Ok(()),
+        _ => Err(ApiError::ManagerApprovalRequired)
+    }
+}
+
+
 //handler function for delete a specific table's order (of a specific table). Then returns the updated table's orders (TableOrderResponse)
-pub async fn handle_delete_table_order(State(context): State<ApiContext>, 
-    WithRejection(Path((table_id, order_id)), _): WithRejection<Path<(i16, i32)>, ApiError>) ->  impl IntoResponse{
+//The order is soft deleted, and reason code and who removed it (query parameters) are kept in order history.
+//Voiding orders which are served (or older than configured age) requires manager role or manager approval token.
+//When staff token is used, the staff id is recorded as deleted_by instead of the query parameter
+pub async fn handle_delete_table_order<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    role: RequireRole<WaiterAccess>,
+    WithRejection(Path((table_id, order_id)), _): WithRejection<Path<(i16, i32)>, ApiError>,
+    headers: HeaderMap,
+    WithRejection(Query(query), _): WithRejection<Query<RemoveOrderQuery>, ApiError>) ->  impl IntoResponse{
     
-    tracing::info!("[delete] table id = {table_id}, order_id= {order_id} from path");
+    tracing::info!("[delete] table id = {table_id}, order_id= {order_id} from path, reason = {}, deleted by = {:?}", query.reason, query.deleted_by);
     
 
-    check_range!(context.config.get_max_tables(), table_id, ApiError::TableNotFound);
+    check_table!(context, table_id);
     check_range!(i32::MAX, order_id, ApiError::OrderNotFound);
+    if query.deleted_by.as_ref().is_some_and(|deleted_by| deleted_by.chars().count() > MAX_DELETED_BY_LENGTH){
+        return ApiError::BadRequest(format!("deleted_by must be at most {MAX_DELETED_BY_LENGTH} characters")).into_response();
+    }
+
+    let void_time = Utc::now();
+    if let Err(err) = context.dbo.get_specific_table_order(table_id, order_id).await
+        .and_then(|orders| check_void_approval(&context.config, &headers, role.role(), &orders[0], void_time)){
+        return err.into_response();
+    }
+    let expected_versions = expected_orders_versions!(headers, table_id);
 
     // validate_table_id_range!(context, table_id);    
     // validate_order_id_range!(order_id);
     
-    context.dbo.remove_order(table_id, order_id) // remove order
-        .and_then( |_| context.dbo.get_table_orders(table_id)) // get updated table orders
+    let deleted_by = role.staff_id().or(query.deleted_by.as_deref());
+    context.dbo.remove_order(table_id, order_id, deleted_by, Some(query.reason.as_str()), void_time, &expected_versions) // remove (soft delete) order
+        .and_then( |_| get_versioned_table_orders(&context, table_id)) // get updated table orders
         .await
-        .and_then( |orders: Vec<OrderItem>| Ok(TableOrdersResponse::new(200,  table_id,  orders).into_response()))// generate TableOrdersResponse from orders
+        .map( |orders| publish_table_orders(&context, OrderEventType::OrderRemoved, table_id, Some(order_id), orders))// notify streams and generate TableOrdersResponse from orders
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+//handler function for changing status of a specific table's order. Then returns the updated table's orders (TableOrderResponse)
+//Orders can't be cancelled by changing status, since voiding requires reason code and approval (see handle_delete_table_order)
+pub async fn handle_update_order_status<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<KitchenAccess>,
+    WithRejection(Path((table_id, order_id)), _): WithRejection<Path<(i16, i32)>, ApiError>,
+    headers: HeaderMap,
+    WithRejection(Json(request), _): WithRejection<Json<OrderStatusUpdateRequest>, ApiError>) ->  Response{
+
+    tracing::info!("[update status] table id = {table_id}, order_id= {order_id}, status = {}", request.status);
+
+    check_table!(context, table_id);
+    check_range!(i32::MAX, order_id, ApiError::OrderNotFound);
+    if request.status == OrderStatus::Cancelled{
+        return ApiError::BadRequest("orders are cancelled by removing them with a void reason code".to_string()).into_response();
+    }
+    let expected_versions = expected_orders_versions!(headers, table_id);
+
+    context.dbo.update_order_status(table_id, order_id, request.status, &expected_versions) // change status (transition is validated by DAO)
+        .and_then( |_| get_versioned_table_orders(&context, table_id)) // get updated table orders
+        .await
+        .map( |orders| publish_table_orders(&context, OrderEventType::OrderStatusChanged, table_id, Some(order_id), orders))// notify streams and generate TableOrdersResponse from orders
         .unwrap_or_else(ApiError::into_response) // generate error response in case of error
 }
 
 
+//handler function for decrementing quantity of a specific table's order (the order is removed if quantity reaches 0). Then returns the updated table's orders (TableOrderResponse)
+//Removing the order this way is a void, so it requires reason code and the same approval as handle_delete_table_order
+pub async fn handle_decrement_order_quantity<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    role: RequireRole<WaiterAccess>,
+    WithRejection(Path((table_id, order_id)), _): WithRejection<Path<(i16, i32)>, ApiError>,
+    headers: HeaderMap,
+    WithRejection(Json(request), _): WithRejection<Json<OrderQuantityDecrementRequest>, ApiError>) ->  Response{
+
+    tracing::info!("[decrement] table id = {table_id}, order_id= {order_id}, quantity = {}", request.quantity);
+
+    check_table!(context, table_id);
+    check_range!(i32::MAX, order_id, ApiError::OrderNotFound);
+    check_range!(MAX_ORDER_QUANTITY, request.quantity, ApiError::BadRequest(format!("decrement quantity must be between 1 and {MAX_ORDER_QUANTITY}")));
+    if request.deleted_by.as_ref().is_some_and(|deleted_by| deleted_by.chars().count() > MAX_DELETED_BY_LENGTH){
+        return ApiError::BadRequest(format!("deleted_by must be at most {MAX_DELETED_BY_LENGTH} characters")).into_response();
+    }
+
+    // reason is passed to DAO only if the void is approved, so DAO refuses to remove the order otherwise
+    let void_time = Utc::now();
+    let order = match context.dbo.get_specific_table_order(table_id, order_id).await{
+        Ok(orders) => orders[0].clone(),
+        Err(err) => return err.into_response()
+    };
+    let reason = if request.quantity == order.quantity{
+        let Some(reason) = request.reason else{
+            return ApiError::BadRequest("removing all items of an order requires a void reason".to_string()).into_response();
+        };
+        if let Err(err) = check_void_approval(&context.config, &headers, role.role(), &order, void_time){
+            return err.into_response();
+        }
+        Some(reason.as_str())
+    }else{
+        None
+    };
+    let expected_versions = expected_orders_versions!(headers, table_id);
+
+    let deleted_by = role.staff_id().or(request.deleted_by.as_deref());
+    context.dbo.decrement_order_quantity(table_id, order_id, request.quantity, deleted_by, reason, &expected_versions) // decrement quantity (or remove order)
+        .and_then( |_| get_versioned_table_orders(&context, table_id)) // get updated table orders
+        .await
+        .map( |orders| publish_table_orders(&context, OrderEventType::OrderQuantityChanged, table_id, Some(order_id), orders))// notify streams and generate TableOrdersResponse from orders
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+//handler function for moving specific orders (of the current seating session) to another table in one transaction (e.g., a party moves from the bar to a table).
+//Then returns the updated orders of both tables (TableTransferResponse). If-Match may contain ETags of both tables
+pub async fn handle_transfer_orders<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<WaiterAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    headers: HeaderMap,
+    WithRejection(Json(request), _): WithRejection<Json<TransferOrdersRequest>, ApiError>) ->  Response{
+
+    tracing::info!("[transfer] table id = {table_id}, target table id = {}, order ids = {:?}", request.target_table_id, request.order_ids);
+
+    check_table!(context, table_id);
+    check_target_table!(context, table_id, request.target_table_id);
+
+    if request.order_ids.is_empty(){
+        return ApiError::BadRequest("no orders to transfer".to_string()).into_response();
+    }
+    let expected_versions = expected_orders_versions!(headers, table_id, request.target_table_id);
+
+    context.dbo.transfer_orders(table_id, request.target_table_id, &request.order_ids, Utc::now(), &expected_versions) // move orders (all or nothing)
+        .and_then( |_| publish_transferred_orders(&context, table_id, request.target_table_id)) // notify streams of both tables and generate TableTransferResponse
+        .await
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+//handler function for merging all orders (of the current seating session) of a table into another table in one transaction (e.g., two tables are pushed together).
+//The seating session of the merged table is closed. Then returns the updated orders of both tables (TableTransferResponse). If-Match may contain ETags of both tables
+pub async fn handle_merge_tables<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<WaiterAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    headers: HeaderMap,
+    WithRejection(Json(request), _): WithRejection<Json<MergeTablesRequest>, ApiError>) ->  Response{
+
+    tracing::info!("[merge] table id = {table_id}, target table id = {}", request.target_table_id);
+
+    check_table!(context, table_id);
+    check_target_table!(context, table_id, request.target_table_id);
+    let expected_versions = expected_orders_versions!(headers, table_id, request.target_table_id);
+
+    context.dbo.merge_tables(table_id, request.target_table_id, Utc::now(), &expected_versions) // move all orders and close seating session
+        .and_then( |_| publish_transferred_orders(&context, table_id, request.target_table_id)) // notify streams of both tables and generate TableTransferResponse
+        .await
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+/// default and maximum number of orders returned by kitchen queue
+const DEFAULT_KITCHEN_QUEUE_LIMIT: i64 = 100;
+const MAX_KITCHEN_QUEUE_LIMIT: i64 = 500;
+
+
+/// handler function for getting open orders of all tables (kitchen display) sorted by estimated arrival time.
+/// Orders can be filtered by menu category / station (e.g., `?category=drink`) and limited (e.g., `?limit=20`)
+pub async fn handle_get_kitchen_queue<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Query(query), _): WithRejection<Query<KitchenQueueQuery>, ApiError>) -> Response{
+
+    tracing::info!("[kitchen queue] category = {:?}, limit = {:?}", query.category, query.limit);
+
+    let limit = query.limit.unwrap_or(DEFAULT_KITCHEN_QUEUE_LIMIT);
+    check_range!(MAX_KITCHEN_QUEUE_LIMIT, limit, ApiError::BadRequest(format!("limit must be between 1 and {MAX_KITCHEN_QUEUE_LIMIT}")));
+
+    context.dbo.get_kitchen_queue(query.category.as_deref(), limit)
+        .await
+        .map( |orders: Vec<OrderItem>| KitchenQueueResponse::new(200, orders).into_response()) // generate KitchenQueueResponse from orders
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
 
 #[cfg(test)]
 mod test{
+    use axum::http::HeaderValue;
     use crate::model::OrderItemRequest;
 
     use super::*;
+
+    #[test]
+    fn test_parse_if_match(){
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_if_match(&headers, &[1]).unwrap(), vec![]);
+
+        headers.insert(header::IF_MATCH, HeaderValue::from_static("*"));
+        assert_eq!(parse_if_match(&headers, &[1]).unwrap(), vec![]);
+
+        headers.insert(header::IF_MATCH, HeaderValue::from_str(&table_orders_etag(1, 42)).unwrap());
+        assert_eq!(parse_if_match(&headers, &[1]).unwrap(), vec![(1, 42)]);
+
+        // ETags of both tables of a transfer
+        headers.insert(header::IF_MATCH, HeaderValue::from_static("\"1-42\", \"2-7\""));
+        assert_eq!(parse_if_match(&headers, &[1, 2]).unwrap(), vec![(1, 42), (2, 7)]);
+
+        // ETag of another table or invalid ETag never matches
+        assert!(matches!(parse_if_match(&headers, &[1]), Err(ApiError::PreconditionFailed)));
+        headers.insert(header::IF_MATCH, HeaderValue::from_static("\"abc\""));
+        assert!(matches!(parse_if_match(&headers, &[1]), Err(ApiError::PreconditionFailed)));
+    }
+
+    #[test]
+    fn test_is_not_modified(){
+        let etag = table_orders_etag(1, 42);
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, &etag));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"1-41\", W/\"1-42\""));
+        assert!(is_not_modified(&headers, &etag));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"1-41\""));
+        assert!(!is_not_modified(&headers, &etag));
+    }
+
+    #[test]
+    fn test_check_void_approval(){
+        let time = Utc::now();
+        let mut order = process_order_request(OrderItemRequest::new(1, "A", "B"), time, 7);
+        let mut config = AppConfig::default();
+        let mut headers = HeaderMap::new();
+
+        // unserved order can be voided without approval, served one can't (even with a token if none is configured)
+        assert!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time).is_ok());
+        order.status = OrderStatus::Served;
+        headers.insert(MANAGER_APPROVAL_HEADER, HeaderValue::from_static("secret"));
+        assert!(matches!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time), Err(ApiError::ManagerApprovalRequired)));
+
+        config.manager_approval_token = Some("secret".to_string());
+        assert!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time).is_ok());
+        headers.insert(MANAGER_APPROVAL_HEADER, HeaderValue::from_static("wrong"));
+        assert!(matches!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time), Err(ApiError::ManagerApprovalRequired)));
+
+        // configured status and age
+        config.void_approval_status = Some(OrderStatus::Cooking);
+        config.void_approval_minutes = Some(30);
+        order.status = OrderStatus::Ordered;
+        assert!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time + Duration::minutes(29)).is_ok());
+        assert!(matches!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time + Duration::minutes(30)), Err(ApiError::ManagerApprovalRequired)));
+        order.status = OrderStatus::Cooking;
+        assert!(matches!(check_void_approval(&config, &headers, Some(Role::Waiter), &order, time), Err(ApiError::ManagerApprovalRequired)));
+        assert!(matches!(check_void_approval(&config, &headers, None, &order, time), Err(ApiError::ManagerApprovalRequired)));
+
+        // managers don't need approval token
+        assert!(check_void_approval(&config, &headers, Some(Role::Manager), &order, time).is_ok());
+        assert!(check_void_approval(&config, &headers, Some(Role::Admin), &order, time).is_ok());
+    }
+
     #[test]
     fn test_process_one_order_request(){
         let current_time = Utc::now();
         let order_request = OrderItemRequest::new(1, "A", "B");
 
-        let result = process_order_request(order_request, current_time);
+        let result = process_order_request(order_request, current_time, 7);
 
         assert_eq!(result.order_id, -1);
         assert_eq!(result.table_id, 1);
         assert_eq!(result.item_name, "A");
         assert_eq!(result.note, Some("B".to_string()));
+        assert_eq!(result.status, OrderStatus::Ordered);
+        assert_eq!(result.quantity, 1);
         
         let diff = result.estimated_arrival_time - result.creation_time;
-        assert!(diff.num_minutes() >= 5);
-        assert!(diff.num_minutes() <= 15);
+        assert_eq!(diff.num_minutes(), 7);
     }
 
     #[test]
@@ -245,6 +800,34 @@ mod test{
 
 
     #[test]
+    fn test_process_order_requests_with_estimator(){
+        use crate::estimation::KitchenLoadEstimation;
+
+        let mut orders = TableOrdersRequest::new(1);
+        orders.add_order_wihtout_note("A");
+        orders.add_order_wihtout_note("B");
+        orders.add_order_wihtout_note("C");
+
+        let menu_items = vec![
+            MenuItem{ menu_item_id: 1, name: "A".to_string(), category: "main".to_string(), price: 100, prep_time_minutes: 3, active: true },
+            MenuItem{ menu_item_id: 2, name: "B".to_string(), category: "main".to_string(), price: 250, prep_time_minutes: 20, active: true }
+        ];
+
+        // 2 minutes for each order in the queue (one order at a time), 10 minutes for items without menu preparation time
+        let estimator = KitchenLoadEstimation::new(10, 2, 1);
+        let results = process_order_requests_with_estimator(orders, &estimator, &menu_items, 4);
+
+        let minutes: Vec<i64> = results.iter().map(|order| (order.estimated_arrival_time - order.creation_time).num_minutes()).collect();
+        assert_eq!(minutes, vec![3 + 8, 20 + 10, 10 + 12]);
+
+        // menu item and price are snapshotted (no snapshot for item without menu)
+        let snapshots: Vec<(Option<i32>, Option<i32>)> = results.iter().map(|order| (order.menu_item_id, order.unit_price)).collect();
+        assert_eq!(snapshots, vec![(Some(1), Some(100)), (Some(2), Some(250)), (None, None)]);
+    }
+
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison, clippy::useless_vec)]
     fn test_validate_table_id_from_orders(){
         let order1 = OrderItemRequest::new_wihout_note(1, "A");
         let order2 = OrderItemRequest::new_wihout_note(1, "B");
@@ -255,5 +838,40 @@ mod test{
         assert_eq!(validate_table_id_from_orders_requests_and_path(&vec![order1.clone(), order3.clone()], 1), false);
     }
 
+
+    #[test]
+    fn test_validate_order_quantities(){
+        let order = OrderItemRequest::new_wihout_note(1, "A");
+
+        assert!(validate_order_quantities(&[order.clone(), order.clone().with_quantity(MAX_ORDER_QUANTITY)]));
+        assert!(!validate_order_quantities(&[order.clone(), order.clone().with_quantity(0)]));
+        assert!(!validate_order_quantities(&[order.clone().with_quantity(MAX_ORDER_QUANTITY + 1)]));
+    }
+
+
+    #[test]
+    fn test_find_invalid_order_items(){
+        let menu_item = |menu_item_id: i32, name: &str, active: bool| MenuItem{
+            menu_item_id,
+            name: name.to_string(),
+            category: "main".to_string(),
+            price: 1000,
+            prep_time_minutes: 10,
+            active
+        };
+        let menu_items = vec![menu_item(1, "A", true), menu_item(2, "B", false)];
+
+        let orders = vec![OrderItemRequest::new_wihout_note(1, "A"), OrderItemRequest::new(1, "A", "C")];
+        assert!(find_invalid_order_items(&orders, &menu_items).is_empty());
+
+        let orders = vec![
+            OrderItemRequest::new_wihout_note(1, "A"),
+            OrderItemRequest::new_wihout_note(1, "B"),
+            OrderItemRequest::new_wihout_note(1, "C"),
+            OrderItemRequest::new_wihout_note(1, "C")
+        ];
+        assert_eq!(find_invalid_order_items(&orders, &menu_items), vec!["B (inactive)", "C (unknown)"]);
+    }
+
     
 }
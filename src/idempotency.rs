@@ -0,0 +1,153 @@
+use std::future::Future;
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::context::ApiContext;
+use crate::dao::StorageDAO;
+use crate::dao::idempotency_dao::IdempotencyReservation;
+use crate::error::ApiError;
+
+
+/// request header carrying client generated key of a retriable request (e.g., uuid generated once per order submission)
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// response header telling client that the response is replayed from the first request with the same key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// maximum length of idempotency key (same as the column)
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+
+/// helper function to read idempotency key from `Idempotency-Key` header. It returns None if the header is not sent,
+/// or ApiError::BadRequest if the key is empty, too long or not visible ascii
+pub(crate) fn parse_idempotency_key(headers: &HeaderMap) -> Result<Option<String>, ApiError>{
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else{
+        return Ok(None);
+    };
+
+    match value.to_str().map(str::trim){
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => Ok(Some(key.to_string())),
+        _ => Err(ApiError::BadRequest(format!("idempotency key must be 1 to {MAX_IDEMPOTENCY_KEY_LENGTH} visible ascii characters")))
+    }
+}
+
+
+/// helper function to generate fingerprint of a request, which is compared when the same key is used again.
+/// Payload is re-serialized after parsing, so formatting differences (e.g., whitespace or omitted defaults) don't matter
+pub(crate) fn request_fingerprint<T: Serialize>(table_id: i16, payload: &T) -> Result<String, ApiError>{
+    serde_json::to_string(payload)
+        .map(|json| format!("{table_id}:{json}"))
+        .map_err(|err| ApiError::ServerError(format!("Couldn't serialize request payload: {err}")))
+}
+
+
+/// helper function to generate response replayed for a retried request
+fn replay_response(status_code: u16, etag: Option<String>, response_body: String) -> Response{
+    let mut response = (
+        StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        [(header::CONTENT_TYPE, "application/json"), (HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER), "true")],
+        response_body
+    ).into_response();
+
+    if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(&etag).ok()){
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+    response
+}
+
+
+/// function to process a request at most once per idempotency key. The first request runs the handler and its response is stored,
+/// so that retries with the same key and fingerprint get the stored response without running the handler again.
+/// Server errors (5xx) are not stored, so the request can be retried.
+/// The request runs in a spawned task, so that the key is completed or released even if the client disconnects
+pub(crate) async fn with_idempotency_key<D, F>(context: &ApiContext<D>, idempotency_key: &str, request_fingerprint: &str, handler: F) -> Response
+    where D: StorageDAO + 'static, F: Future<Output = Response> + Send + 'static{
+
+    let context = context.clone();
+    let idempotency_key = idempotency_key.to_string();
+    let request_fingerprint = request_fingerprint.to_string();
+
+    tokio::spawn(async move{
+        process_idempotent_request(&context, &idempotency_key, &request_fingerprint, handler).await
+    }).await
+        .unwrap_or_else(|err| ApiError::ServerError(format!("request task failed: {err}")).into_response())
+}
+
+
+/// helper function to reserve the key, run the handler and store its response (used by with_idempotency_key)
+async fn process_idempotent_request<D: StorageDAO, F: Future<Output = Response>>(context: &ApiContext<D>, idempotency_key: &str,
+    request_fingerprint: &str, handler: F) -> Response{
+
+    match context.dbo.reserve_idempotency_key(idempotency_key, request_fingerprint, Utc::now()).await{
+        Ok(IdempotencyReservation::Reserved) => {},
+        Ok(IdempotencyReservation::Completed{ status_code, etag, response_body }) => {
+            tracing::info!("[idempotency] replaying response of key {idempotency_key}");
+            return replay_response(status_code, etag, response_body);
+        },
+        Err(err) => return err.into_response()
+    }
+
+    let (parts, body) = handler.await.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await{
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!("[idempotency] couldn't read response of key {idempotency_key}: {err}");
+            if let Err(err) = context.dbo.release_idempotency_key(idempotency_key).await{
+                tracing::error!("[idempotency] couldn't release key {idempotency_key}: {err}");
+            }
+            return ApiError::ServerError("couldn't read response".to_string()).into_response();
+        }
+    };
+
+    let etag = parts.headers.get(header::ETAG).and_then(|etag| etag.to_str().ok());
+    let result = match parts.status.is_server_error(){
+        true => context.dbo.release_idempotency_key(idempotency_key).await,
+        false => context.dbo.complete_idempotency_key(idempotency_key, parts.status.as_u16(), etag, &String::from_utf8_lossy(&body)).await
+    };
+    if let Err(err) = result{
+        tracing::error!("[idempotency] couldn't store response of key {idempotency_key}: {err}");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+    use crate::model::TableOrdersRequest;
+
+    #[test]
+    fn test_parse_idempotency_key(){
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_idempotency_key(&headers).unwrap(), None);
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("order-42"));
+        assert_eq!(parse_idempotency_key(&headers).unwrap(), Some("order-42".to_string()));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static(" "));
+        assert!(matches!(parse_idempotency_key(&headers), Err(ApiError::BadRequest(_))));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_str(&"x".repeat(256)).unwrap());
+        assert!(matches!(parse_idempotency_key(&headers), Err(ApiError::BadRequest(_))));
+    }
+
+
+    #[test]
+    fn test_request_fingerprint(){
+        let mut table_orders = TableOrdersRequest::new(1);
+        table_orders.add_order_wihtout_note("Kapao");
+        let fingerprint = request_fingerprint(1, &table_orders).unwrap();
+
+        assert_eq!(request_fingerprint(1, &table_orders.clone()).unwrap(), fingerprint);
+        assert_ne!(request_fingerprint(2, &table_orders).unwrap(), fingerprint);
+
+        table_orders.add_order_wihtout_note("Beer");
+        assert_ne!(request_fingerprint(1, &table_orders).unwrap(), fingerprint);
+    }
+}
@@ -3,30 +3,67 @@ pub mod error;
 pub mod dao;
 pub mod context;
 pub mod handlers;
+pub mod menu_handlers;
+pub mod bill_handlers;
+pub mod table_handlers;
+pub mod session_handlers;
 pub mod routes;
 pub mod config;
+pub mod estimation;
+pub mod events;
+pub mod stream_handlers;
+pub mod ws_handlers;
+pub mod event_relay;
+pub mod idempotency;
+pub mod auth;
+pub mod jwt;
+pub mod api_key_handlers;
+pub mod staff_handlers;
+pub mod rate_limit;
+pub mod metrics;
+
+use std::net::SocketAddr;
 
 use tokio::net::TcpListener;
 
 
 
+#[allow(clippy::expect_fun_call)]
 pub async fn run_server() -> Result<(), error::ApiError>{
-    let config = config::AppConfig::new_from_env();
+    let config = config::AppConfig::new_from_env()?;
     let host = config.get_host();
     let port = config.get_port();
 
     // let database_url =  config.get_db_url();
 
     tracing::info!("HOST:{host} PORT:{port}");
+    config.validate()?;
+    if config.get_admin_api_key().is_none(){
+        tracing::warn!("AUTH_DISABLED is set, so API key authentication is disabled");
+    }
+
+    // router doesn't expose DAO type, so storage backend can be selected at runtime here
+    let router = match config.get_storage_backend(){
+        config::StorageBackend::Memory => routes::app(context::ApiContext::with_dao(config.clone(), dao::mem_order_dao::MemTableOrderDAO::new())),
+        config::StorageBackend::Postgres => {
+            // replicas share the database, so order changes of other replicas are relayed to local streaming clients
+            let context = context::ApiContext::new_from_env().await?;
+            event_relay::spawn_order_event_relay(context.clone()).await?;
+            routes::app(context)
+        },
 
-    let context = context::ApiContext::new_from_env().await?;
+        #[cfg(feature = "sqlite")]
+        config::StorageBackend::Sqlite => routes::app(context::ApiContext::new_sqlite_from_env().await?),
 
-    let router = routes::app(context);
+        #[cfg(not(feature = "sqlite"))]
+        config::StorageBackend::Sqlite => return Err(error::ApiError::ServerError("sqlite storage requires the `sqlite` cargo feature".to_string()))
+    };
     let listener = TcpListener::bind(format!("{host}:{port}")).await
                                     .expect(&format!("Unable to bind server to {host}:{port}"));
 
     tracing::info!("Server is ready");
-    axum::serve(listener, router).await.expect("Cannot serve service");
+    // client address is used as rate limit key of clients without API key
+    axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await.expect("Cannot serve service");
     Ok(())
 
 }
\ No newline at end of file
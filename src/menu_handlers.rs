@@ -0,0 +1,169 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use futures::TryFutureExt;
+
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::model::{MenuItem, MenuItemRequest, MenuResponse};
+use crate::auth::{RequireRole, ManagerAccess, StaffAccess};
+use crate::context::ApiContext;
+
+
+/// maximum length of menu item name and category (same as menu_items columns)
+const MAX_MENU_ITEM_NAME_LENGTH: usize = 255;
+const MAX_MENU_ITEM_CATEGORY_LENGTH: usize = 64;
+
+
+/// helper function to validate menu item request (name and category are required, price and preparation time can't be negative)
+fn validate_menu_item_request(request: &MenuItemRequest) -> Result<(), ApiError>{
+    for (field, value, max_length) in [("name", &request.name, MAX_MENU_ITEM_NAME_LENGTH), ("category", &request.category, MAX_MENU_ITEM_CATEGORY_LENGTH)]{
+        if value.trim().is_empty() || value.trim().chars().count() > max_length{
+            return Err(ApiError::BadRequest(format!("menu item {field} must be 1 to {max_length} characters")));
+        }
+    }
+
+    if request.price < 0 || request.prep_time_minutes < 0{
+        return Err(ApiError::BadRequest("menu item price and preparation time must not be negative".to_string()));
+    }
+
+    Ok(())
+}
+
+
+/// helper function to trim name and category of menu item request, so the stored name is the one that orders must match
+fn trim_menu_item_request(request: MenuItemRequest) -> MenuItemRequest{
+    MenuItemRequest{
+        name: request.name.trim().to_string(),
+        category: request.category.trim().to_string(),
+        ..request
+    }
+}
+
+
+/// validation macro for menu item request. Error is converted to Axum's response and returned to client
+macro_rules! validate_menu_item_request {
+    ($request: expr) => {
+        if let Err(err) = validate_menu_item_request($request){
+            tracing::error!("[menu] invalid menu item request: {err}");
+            return err.into_response();
+        }
+    };
+}
+
+
+/// utility macro to check menu item id from path (ids start from 1)
+macro_rules! check_menu_item_id {
+    ($menu_item_id: expr) => {
+        if $menu_item_id < 1{
+            tracing::error!("out of range menu item id={}", $menu_item_id);
+            return ApiError::MenuItemNotFound.into_response();
+        }
+    };
+}
+
+
+/// handler function for getting all menu items (including inactive items)
+pub async fn handle_get_menu<D: StorageDAO>(State(context): State<ApiContext<D>>, _role: RequireRole<StaffAccess>) -> Response{
+    tracing::info!("[menu get all]");
+
+    context.dbo.get_menu_items()
+        .await
+        .map( |items: Vec<MenuItem>| MenuResponse::new(200, items).into_response()) // generate MenuResponse from menu items
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+/// handler function for adding a new menu item. It returns the created menu item (including generated id)
+pub async fn handle_add_menu_item<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Json(request), _): WithRejection<Json<MenuItemRequest>, ApiError>) -> Response{
+
+    tracing::info!("[menu add] name = {}", request.name);
+    validate_menu_item_request!(&request);
+
+    context.dbo.add_menu_item(&trim_menu_item_request(request))
+        .await
+        .map( |item: MenuItem| MenuResponse::new(200, vec![item]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for getting a specific menu item
+pub async fn handle_get_menu_item<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(menu_item_id), _): WithRejection<Path<i32>, ApiError>) -> Response{
+
+    tracing::info!("[menu get specific] menu_item_id = {menu_item_id}");
+    check_menu_item_id!(menu_item_id);
+
+    context.dbo.get_menu_item(menu_item_id)
+        .await
+        .map( |item: MenuItem| MenuResponse::new(200, vec![item]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for replacing a specific menu item (e.g., change price or deactivate it). It returns the updated menu item
+pub async fn handle_update_menu_item<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Path(menu_item_id), _): WithRejection<Path<i32>, ApiError>,
+    WithRejection(Json(request), _): WithRejection<Json<MenuItemRequest>, ApiError>) -> Response{
+
+    tracing::info!("[menu update] menu_item_id = {menu_item_id}, name = {}", request.name);
+    check_menu_item_id!(menu_item_id);
+    validate_menu_item_request!(&request);
+
+    context.dbo.update_menu_item(menu_item_id, &trim_menu_item_request(request))
+        .await
+        .map( |item: MenuItem| MenuResponse::new(200, vec![item]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for deleting a specific menu item. Then returns the updated menu.
+/// Menu item referenced by open orders can't be deleted (409), it can be deactivated instead
+pub async fn handle_delete_menu_item<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Path(menu_item_id), _): WithRejection<Path<i32>, ApiError>) -> Response{
+
+    tracing::info!("[menu delete] menu_item_id = {menu_item_id}");
+    check_menu_item_id!(menu_item_id);
+
+    context.dbo.remove_menu_item(menu_item_id) // remove menu item
+        .and_then( |_| context.dbo.get_menu_items()) // get updated menu
+        .await
+        .map( |items: Vec<MenuItem>| MenuResponse::new(200, items).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+    #[test]
+    fn test_validate_menu_item_request(){
+        assert!(validate_menu_item_request(&MenuItemRequest::new("Kaprao", "main", 12000, 10)).is_ok());
+        assert!(validate_menu_item_request(&MenuItemRequest::new("Water", "drink", 0, 0)).is_ok());
+
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new(" ", "main", 12000, 10)), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new("Kaprao", "", 12000, 10)), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new(&"K".repeat(256), "main", 12000, 10)), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new("Kaprao", &"m".repeat(65), 12000, 10)), Err(ApiError::BadRequest(_))));
+        assert!(validate_menu_item_request(&MenuItemRequest::new(&format!(" {} ", "K".repeat(255)), "main", 12000, 10)).is_ok());
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new("Kaprao", "main", -1, 10)), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_menu_item_request(&MenuItemRequest::new("Kaprao", "main", 12000, -1)), Err(ApiError::BadRequest(_))));
+    }
+
+
+    #[test]
+    fn test_trim_menu_item_request(){
+        let request = trim_menu_item_request(MenuItemRequest::new(" Kaprao ", " main", 12000, 10));
+        assert_eq!(request.name, "Kaprao");
+        assert_eq!(request.category, "main");
+        assert_eq!(request.price, 12000);
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use prometheus::{Encoder, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+use crate::dao::order_dao::PoolUsage;
+use crate::error::ApiError;
+
+
+/// route label of requests which don't match any route (path itself isn't used, so that unknown paths can't grow the label set)
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// histogram buckets (seconds) of DAO queries, which are usually much faster than whole requests
+const DAO_QUERY_BUCKETS: &[f64] = &[0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+
+/// Prometheus metrics of the server. They are process wide (like the default prometheus registry),
+/// so that DAO implementations can record query timings without access to ApiContext
+pub struct Metrics{
+    registry: Registry,
+
+    /// number of requests per route, method and status code
+    pub http_requests: IntCounterVec,
+
+    /// request latency per route, method and status code
+    pub http_request_duration: HistogramVec,
+
+    /// latency of each TableOrderDAO function (postgres and sqlite storage)
+    pub dao_query_duration: HistogramVec,
+
+    /// connections of database pool per state (idle or in_use), updated when metrics are scraped
+    pub db_pool_connections: IntGaugeVec,
+
+    /// maximum connections of database pool (max_db_pool_size)
+    pub db_pool_max_connections: IntGauge,
+
+    /// open (ordered, cooking or ready) orders per table, updated when metrics are scraped
+    pub open_orders: IntGaugeVec,
+
+    /// unfinished (ordered or cooking) orders of all tables, i.e., length of the kitchen queue
+    pub unfinished_orders: IntGauge
+}
+
+
+impl Metrics{
+
+    /// function to create and register all metrics
+    fn new() -> Result<Self, prometheus::Error>{
+        let registry = Registry::new();
+        let http_labels = &["method", "route", "status"];
+
+        let metrics = Self{
+            http_requests: IntCounterVec::new(Opts::new("http_requests_total", "Number of HTTP requests"), http_labels)?,
+            http_request_duration: HistogramVec::new(HistogramOpts::new("http_request_duration_seconds", "HTTP request latency in seconds"), http_labels)?,
+            dao_query_duration: HistogramVec::new(HistogramOpts::new("dao_query_duration_seconds", "Latency of order DAO queries in seconds")
+                .buckets(DAO_QUERY_BUCKETS.to_vec()), &["method"])?,
+            db_pool_connections: IntGaugeVec::new(Opts::new("db_pool_connections", "Connections of database pool"), &["state"])?,
+            db_pool_max_connections: IntGauge::new("db_pool_max_connections", "Maximum connections of database pool")?,
+            open_orders: IntGaugeVec::new(Opts::new("open_orders", "Open (ordered, cooking or ready) orders per table"), &["table_id"])?,
+            unfinished_orders: IntGauge::new("kitchen_unfinished_orders", "Unfinished (ordered or cooking) orders of all tables")?,
+            registry
+        };
+
+        metrics.registry.register(Box::new(metrics.http_requests.clone()))?;
+        metrics.registry.register(Box::new(metrics.http_request_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.dao_query_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.db_pool_connections.clone()))?;
+        metrics.registry.register(Box::new(metrics.db_pool_max_connections.clone()))?;
+        metrics.registry.register(Box::new(metrics.open_orders.clone()))?;
+        metrics.registry.register(Box::new(metrics.unfinished_orders.clone()))?;
+        Ok(metrics)
+    }
+
+    /// function to update gauges of database pool (None -> storage has no pool, e.g., in-memory storage)
+    pub fn set_pool_usage(&self, pool_usage: Option<PoolUsage>){
+        let Some(pool_usage) = pool_usage else{
+            return;
+        };
+
+        self.db_pool_connections.with_label_values(&["idle"]).set(i64::from(pool_usage.idle));
+        self.db_pool_connections.with_label_values(&["in_use"]).set(i64::from(pool_usage.size.saturating_sub(pool_usage.idle)));
+        self.db_pool_max_connections.set(i64::from(pool_usage.max_size));
+    }
+
+    /// function to replace open orders per table. Tables without open orders are removed, so that closed tables don't keep stale values
+    pub fn set_open_orders(&self, open_orders: &[(i16, i64)]){
+        self.open_orders.reset();
+        for (table_id, count) in open_orders{
+            self.open_orders.with_label_values(&[&table_id.to_string()]).set(*count);
+        }
+    }
+
+    /// function to encode all metrics in prometheus text format
+    pub fn encode(&self) -> Result<String, ApiError>{
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)
+            .map_err(|err| ApiError::ServerError(format!("Couldn't encode metrics: {err}")))?;
+
+        String::from_utf8(buffer)
+            .map_err(|err| ApiError::ServerError(format!("Couldn't encode metrics: {err}")))
+    }
+}
+
+
+/// function to get metrics of the server (created on first use)
+pub fn metrics() -> &'static Metrics{
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("metrics have unique names"))
+}
+
+
+/// helper function to time a DAO query. The latency is recorded when the returned timer is dropped, i.e., at the end of the function
+pub fn dao_query_timer(method: &str) -> HistogramTimer{
+    metrics().dao_query_duration.with_label_values(&[method]).start_timer()
+}
+
+
+/// middleware function to count requests and record their latency per route (path pattern, e.g., `/api/v1/tables/:table_id/orders`) and status code.
+/// Streaming responses are measured until their headers are sent
+pub async fn track_request_metrics(request: Request, next: Next) -> Response{
+    let route = request.extensions().get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    metrics().http_requests.with_label_values(&labels).inc();
+    metrics().http_request_duration.with_label_values(&labels).observe(start.elapsed().as_secs_f64());
+    response
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+
+    #[test]
+    fn test_encode_metrics(){
+        metrics().set_pool_usage(Some(PoolUsage{ size: 4, idle: 1, max_size: 10 }));
+        metrics().set_open_orders(&[(1, 3), (2, 1)]);
+        metrics().set_open_orders(&[(2, 2)]);
+        drop(dao_query_timer("test_query"));
+
+        let text = metrics().encode().unwrap();
+        assert!(text.contains(r#"db_pool_connections{state="in_use"} 3"#));
+        assert!(text.contains(r#"db_pool_connections{state="idle"} 1"#));
+        assert!(text.contains("db_pool_max_connections 10"));
+        assert!(text.contains(r#"open_orders{table_id="2"} 2"#));
+        assert!(!text.contains(r#"open_orders{table_id="1"}"#));
+        assert!(text.contains(r#"dao_query_duration_seconds_count{method="test_query"} 1"#));
+    }
+}
@@ -4,6 +4,114 @@ use axum::{response::IntoResponse, http::{StatusCode, header}};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use sqlx::prelude::FromRow;
+use std::fmt;
+
+
+/// Upper bound of quantity in a single order item (to catch typos such as 100 beers instead of 10)
+pub const MAX_ORDER_QUANTITY: i32 = 99;
+
+
+/// Lifecycle status of an order. The normal flow is ordered -> cooking -> ready -> served.
+/// Order can be cancelled at any point before it is served. served and cancelled are final states
+#[derive(Serialize, Deserialize, sqlx::Type, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "order_status", rename_all = "lowercase")]
+pub enum OrderStatus{
+    #[default]
+    Ordered,
+    Cooking,
+    Ready,
+    Served,
+    Cancelled
+}
+
+
+impl OrderStatus{
+
+    /// function to check whether changing status from self to `next` is allowed or not
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool{
+        matches!(
+            (self, next),
+            (OrderStatus::Ordered, OrderStatus::Cooking) | 
+            (OrderStatus::Cooking, OrderStatus::Ready) | 
+            (OrderStatus::Ready, OrderStatus::Served) | 
+            (OrderStatus::Ordered | OrderStatus::Cooking | OrderStatus::Ready, OrderStatus::Cancelled)
+        )
+    }
+
+    /// function to check whether order is still open (not yet served or cancelled), i.e., it is shown in the kitchen queue
+    pub fn is_open(&self) -> bool{
+        matches!(self, OrderStatus::Ordered | OrderStatus::Cooking | OrderStatus::Ready)
+    }
+
+    /// function to check whether order has reached `status` in the normal flow (e.g., served order has reached cooking).
+    /// Cancelled order has only reached cancelled, and no other status reaches cancelled
+    pub fn has_reached(&self, status: OrderStatus) -> bool{
+        match (self.lifecycle_step(), status.lifecycle_step()){
+            (Some(step), Some(threshold)) => step >= threshold,
+            _ => *self == status
+        }
+    }
+
+    /// helper function to get position of status in the normal flow (None for cancelled)
+    fn lifecycle_step(&self) -> Option<u8>{
+        match self{
+            OrderStatus::Ordered => Some(0),
+            OrderStatus::Cooking => Some(1),
+            OrderStatus::Ready => Some(2),
+            OrderStatus::Served => Some(3),
+            OrderStatus::Cancelled => None
+        }
+    }
+
+    /// function to get status name (same as json / db representation)
+    pub fn as_str(&self) -> &'static str{
+        match self{
+            OrderStatus::Ordered => "ordered",
+            OrderStatus::Cooking => "cooking",
+            OrderStatus::Ready => "ready",
+            OrderStatus::Served => "served",
+            OrderStatus::Cancelled => "cancelled"
+        }
+    }
+}
+
+
+impl fmt::Display for OrderStatus{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// Reason code of removing (voiding) an order, which is kept in order history for loss tracking
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum VoidReason{
+    CustomerChangedMind,
+    KitchenError,
+    Comp
+}
+
+
+impl VoidReason{
+
+    /// function to get reason code (same as json / db representation)
+    pub fn as_str(&self) -> &'static str{
+        match self{
+            VoidReason::CustomerChangedMind => "customer_changed_mind",
+            VoidReason::KitchenError => "kitchen_error",
+            VoidReason::Comp => "comp"
+        }
+    }
+}
+
+
+impl fmt::Display for VoidReason{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 
 /// This struct represents an order record in the database.
@@ -20,6 +128,9 @@ pub struct OrderItem{
     /// It is possible that restaurant may provides made to order dish (like in Thailand). 
     pub item_name: String,
 
+    /// number of identical items in this order (1..=MAX_ORDER_QUANTITY)
+    pub quantity: i32,
+
     ///  just a note from staff in case that customer has some  preference (e.g., not spicy)
     pub note: Option<String>,
 
@@ -27,7 +138,46 @@ pub struct OrderItem{
     pub creation_time:  DateTime<Utc>,
 
     /// estimated_arrival_time is the estimated arrival time (UTC)
-    pub estimated_arrival_time: DateTime<Utc>
+    pub estimated_arrival_time: DateTime<Utc>,
+
+    /// current lifecycle status of the order
+    pub status: OrderStatus,
+
+    /// device which acknowledged the order first (e.g., kitchen display), None if not acknowledged yet
+    #[serde(default)]
+    pub acknowledged_by: Option<String>,
+
+    /// time of the first acknowledgement (UTC)
+    #[serde(default)]
+    pub acknowledgement_time: Option<DateTime<Utc>>,
+
+    /// seating session (party) of the order. It is assigned by DAO when the order is added
+    #[serde(default)]
+    pub session_id: Option<i32>,
+
+    /// time when the order was removed (voided). Removed orders are only returned by order history
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+
+    /// staff or device which removed the order
+    #[serde(default)]
+    pub deleted_by: Option<String>,
+
+    /// reason code of the removal (e.g., customer_changed_mind)
+    #[serde(default)]
+    pub reason: Option<String>,
+
+    /// staff who created the order (None if the order wasn't created with staff login)
+    #[serde(default)]
+    pub created_by: Option<String>,
+
+    /// menu item of the order when it was added (None for orders added before menu snapshot)
+    #[serde(default)]
+    pub menu_item_id: Option<i32>,
+
+    /// menu price per unit when the order was added. The bill is priced from it, not from the current menu
+    #[serde(default)]
+    pub unit_price: Option<i32>
 }
 
 
@@ -37,6 +187,15 @@ pub struct OrderItemRequest{
     pub table_id: i16,
     pub item_name: String,
     pub note: Option<String>,
+
+    #[serde(default = "default_order_quantity")]
+    pub quantity: i32
+}
+
+
+/// quantity of order item (or decrement) is 1 if it is omitted from the request
+fn default_order_quantity() -> i32{
+    1
 }
 
 
@@ -51,31 +210,120 @@ pub struct TableOrdersRequest{
 
 }
 
+/// Request payload for changing status of an order (PATCH)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrderStatusUpdateRequest{
+    pub status: OrderStatus
+}
+
+
+/// Request payload for decrementing quantity of an order. The order is removed when its quantity reaches 0,
+/// which is a void and requires reason code (and approval, see RemoveOrderQuery)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrderQuantityDecrementRequest{
+    #[serde(default = "default_order_quantity")]
+    pub quantity: i32,
+
+    /// reason code of the removal (required only if the order is removed)
+    #[serde(default)]
+    pub reason: Option<VoidReason>,
+
+    /// staff or device which removes the order
+    #[serde(default)]
+    pub deleted_by: Option<String>
+}
+
+
+/// Request payload for moving specific orders to another table (e.g., a party moves from the bar to a table)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransferOrdersRequest{
+    pub target_table_id: i16,
+    pub order_ids: Vec<i32>
+}
+
+
+/// Request payload for merging all orders of a table into another table (e.g., two tables are pushed together)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergeTablesRequest{
+    pub target_table_id: i16
+}
+
+
+/// Query parameters for getting table's orders (e.g., `?status=cooking`). Orders of the current seating session are returned
+/// unless a past session is specified (e.g., `?session_id=3`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TableOrdersQuery{
+    pub status: Option<OrderStatus>,
+    pub session_id: Option<i32>
+}
+
+
+/// Query parameters for removing an order (e.g., `?reason=kitchen_error&deleted_by=waiter-1`), which are kept in order history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoveOrderQuery{
+    /// reason code of the removal (required)
+    pub reason: VoidReason,
+
+    /// staff or device which removes the order
+    pub deleted_by: Option<String>
+}
+
+
+/// Query parameters for getting kitchen queue (e.g., `?category=drink&limit=20`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct KitchenQueueQuery{
+    /// station / menu category filter
+    pub category: Option<String>,
+
+    /// maximum number of returned orders
+    pub limit: Option<i64>
+}
+
+
+/// Query parameters for connecting a device to WebSocket channel (e.g., `?device_id=kitchen-1`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeviceQuery{
+    /// name of the device, which is recorded when the device acknowledges orders
+    pub device_id: String
+}
+
+
 impl OrderItemRequest{
 
     /// Helper function to create OrderItemRequest struct
+    #[allow(clippy::redundant_field_names)]
     pub fn new(table_id: i16, item_name:&str, note:&str) -> Self{
         OrderItemRequest{
             table_id: table_id,
             item_name: item_name.to_string(),
-            note: Some(note.to_string())
+            note: Some(note.to_string()),
+            quantity: 1
         }
     }
 
     /// Helper function to create OrderItemRequest struct without note
+    #[allow(clippy::redundant_field_names)]
     pub fn new_wihout_note(table_id: i16, item_name:&str) -> Self{
         OrderItemRequest{
             table_id: table_id,
             item_name: item_name.to_string(),
-            note: None
+            note: None,
+            quantity: 1
         }
     }
+
+    /// Helper function to set quantity of OrderItemRequest struct (builder style)
+    pub fn with_quantity(mut self, quantity: i32) -> Self{
+        self.quantity = quantity;
+        self
+    }
 }
 
 
 impl TableOrdersRequest{
 
     /// Helper function to create table order struct
+    #[allow(clippy::redundant_field_names)]
     pub fn new(table_id: i16) -> Self{
         Self { table_id: table_id, orders: Vec::new() }
     }
@@ -121,39 +369,696 @@ pub struct TableOrdersResponse{
 
 impl TableOrdersResponse{
     /// Utility function for creating new TableOrdersResponse
+    #[allow(clippy::redundant_field_names)]
     pub fn new(status_code: u16, table_id: i16, orders: Vec<OrderItem>) -> Self{
         Self { status_code: status_code, table_id: table_id, orders: orders }
     }
 }
 
 
+/// utility function to serialize response payload to pretty json Axum response (200). 
+/// If serialization fails, json error object with status code 500 is returned instead
+fn to_json_response<T: Serialize>(payload: &T) -> axum::response::Response {
+    match serde_json::to_string_pretty(payload){
+        Ok(json) => {
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/json")],
+                json
+            ).into_response()
+        },
+        Err(_) => {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(header::CONTENT_TYPE, "application/json")],
+                "{\"status_code\":500,\"error_cause\":\"json serialization error (invalid status code)\"}".to_string(),
+            ).into_response()
+        }
+    }
+}
+
+
 impl IntoResponse for TableOrdersResponse{
     /// trait implementation to convert TableOrdersResponse to Axum response
     fn into_response(self) -> axum::response::Response {
-        match serde_json::to_string_pretty(&self){
-            Ok(json) => {
-                (
-                    StatusCode::OK,
-                    [(header::CONTENT_TYPE, "application/json")],
-                    json
-                ).into_response()
-            },
-            Err(_) => {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    [(header::CONTENT_TYPE, "application/json")],
-                    "{\"status_code\":500,\"error_cause\":\"json serialization error (invalid status code)\"}".to_string(),
-                ).into_response()
-            }
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents the payload of moving orders between tables returning back to client
+/// It contains the updated orders of both tables
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TableTransferResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// orders of the table which orders are moved from
+    pub source: TableOrdersResponse,
+
+    /// orders of the table which orders are moved to
+    pub target: TableOrdersResponse,
+
+    /// ETag of source table's orders (sent as `If-Match` of the next change)
+    pub source_etag: String,
+
+    /// ETag of target table's orders
+    pub target_etag: String
+}
+
+
+impl TableTransferResponse{
+    /// Utility function for creating new TableTransferResponse
+    pub fn new(status_code: u16, source: TableOrdersResponse, target: TableOrdersResponse, source_etag: String, target_etag: String) -> Self{
+        Self { status_code, source, target, source_etag, target_etag }
+    }
+}
+
+
+impl IntoResponse for TableTransferResponse{
+    /// trait implementation to convert TableTransferResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents the kitchen queue payload returning back to client
+/// It contains open orders of all tables sorted by estimated arrival time
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KitchenQueueResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// open orders (ordered, cooking or ready)
+    pub orders: Vec<OrderItem>
+}
+
+
+impl KitchenQueueResponse{
+    /// Utility function for creating new KitchenQueueResponse
+    pub fn new(status_code: u16, orders: Vec<OrderItem>) -> Self{
+        Self { status_code, orders }
+    }
+}
+
+
+impl IntoResponse for KitchenQueueResponse{
+    /// trait implementation to convert KitchenQueueResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents a menu item record in the database. Orders must reference an active menu item by name
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct MenuItem{
+    /// unique identifier of menu item
+    pub menu_item_id: i32,
+
+    /// unique menu item name (e.g., Kaprao). OrderItem.item_name refers to this name
+    pub name: String,
+
+    /// category of the item (e.g., main, drink, dessert)
+    pub category: String,
+
+    /// price in smallest currency unit (e.g., satang or cents) to avoid floating point rounding
+    pub price: i32,
+
+    /// default preparation time in minutes
+    pub prep_time_minutes: i32,
+
+    /// inactive items are kept for reference but can't be ordered
+    pub active: bool
+}
+
+
+/// Request payload for creating or updating a menu item
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MenuItemRequest{
+    pub name: String,
+    pub category: String,
+    pub price: i32,
+    pub prep_time_minutes: i32,
+
+    #[serde(default = "default_menu_item_active")]
+    pub active: bool
+}
+
+
+/// menu items are active (orderable) by default
+fn default_menu_item_active() -> bool{
+    true
+}
+
+
+impl MenuItemRequest{
+
+    /// Helper function to create active MenuItemRequest struct
+    pub fn new(name: &str, category: &str, price: i32, prep_time_minutes: i32) -> Self{
+        Self{
+            name: name.to_string(),
+            category: category.to_string(),
+            price,
+            prep_time_minutes,
+            active: true
         }
     }
 }
 
+
+/// This struct represents the menu payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MenuResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// menu items
+    pub menu_items: Vec<MenuItem>
+}
+
+
+impl MenuResponse{
+    /// Utility function for creating new MenuResponse
+    pub fn new(status_code: u16, menu_items: Vec<MenuItem>) -> Self{
+        Self { status_code, menu_items }
+    }
+}
+
+
+impl IntoResponse for MenuResponse{
+    /// trait implementation to convert MenuResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents a table record in the registry. Orders can only be added to (and read from) active tables
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct RestaurantTable{
+    /// unique identifier of table (table_id in order paths)
+    pub table_id: i16,
+
+    /// label shown to staff (e.g., Patio 3)
+    pub label: String,
+
+    /// number of seats
+    pub capacity: i16,
+
+    /// section of the restaurant (e.g., main, patio, bar)
+    pub section: String,
+
+    /// inactive tables are kept for reference but orders are rejected
+    pub active: bool
+}
+
+
+/// Request payload for creating or updating a table
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableRequest{
+    pub label: String,
+    pub capacity: i16,
+    pub section: String,
+
+    #[serde(default = "default_table_active")]
+    pub active: bool
+}
+
+
+/// tables are active (accept orders) by default
+fn default_table_active() -> bool{
+    true
+}
+
+
+impl TableRequest{
+
+    /// Helper function to create active TableRequest struct
+    pub fn new(label: &str, capacity: i16, section: &str) -> Self{
+        Self{
+            label: label.to_string(),
+            capacity,
+            section: section.to_string(),
+            active: true
+        }
+    }
+}
+
+
+/// This struct represents the table registry payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TablesResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// tables
+    pub tables: Vec<RestaurantTable>
+}
+
+
+impl TablesResponse{
+    /// Utility function for creating new TablesResponse
+    pub fn new(status_code: u16, tables: Vec<RestaurantTable>) -> Self{
+        Self { status_code, tables }
+    }
+}
+
+
+impl IntoResponse for TablesResponse{
+    /// trait implementation to convert TablesResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// Role of staff using a credential (API key), which decides what the client is allowed to do (see auth)
+#[derive(Serialize, Deserialize, sqlx::Type, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "staff_role", rename_all = "lowercase")]
+pub enum Role{
+    Waiter,
+    Kitchen,
+    Cashier,
+    Manager,
+    Admin
+}
+
+
+impl Role{
+
+    /// function to get role name (same as json / db representation)
+    pub fn as_str(&self) -> &'static str{
+        match self{
+            Role::Waiter => "waiter",
+            Role::Kitchen => "kitchen",
+            Role::Cashier => "cashier",
+            Role::Manager => "manager",
+            Role::Admin => "admin"
+        }
+    }
+}
+
+
+impl fmt::Display for Role{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+
+/// This struct represents an API key record. Only the hash of the key is stored, so the key itself is shown once when it is issued
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct ApiKey{
+    /// unique identifier of API key (used to revoke the key)
+    pub api_key_id: i32,
+
+    /// name of the client or device using the key (e.g., floor-tablet-1)
+    pub name: String,
+
+    /// role of staff using the key
+    pub role: Role,
+
+    /// SHA-256 hash (hex) of the key, which is never returned to client
+    #[serde(skip)]
+    pub key_hash: String,
+
+    /// time when the key was issued (UTC)
+    pub creation_time: DateTime<Utc>,
+
+    /// time when the key was revoked, None if the key is still valid
+    pub revocation_time: Option<DateTime<Utc>>
+}
+
+
+/// Request payload for issuing an API key
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKeyRequest{
+    pub name: String,
+    pub role: Role
+}
+
+
+/// This struct represents the API keys payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApiKeysResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// API keys (without key itself)
+    pub api_keys: Vec<ApiKey>,
+
+    /// the issued key, which is only returned when the key is issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>
+}
+
+
+impl ApiKeysResponse{
+    /// Utility function for creating new ApiKeysResponse
+    pub fn new(status_code: u16, api_keys: Vec<ApiKey>) -> Self{
+        Self { status_code, api_keys, api_key: None }
+    }
+
+    /// Utility function for creating ApiKeysResponse of a newly issued key
+    pub fn issued(api_key: ApiKey, key: String) -> Self{
+        Self { status_code: 200, api_keys: vec![api_key], api_key: Some(key) }
+    }
+}
+
+
+impl IntoResponse for ApiKeysResponse{
+    /// trait implementation to convert ApiKeysResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents a staff account which logs in from devices with PIN. Only salted hash of the PIN is stored
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct StaffMember{
+    /// unique identifier of staff (e.g., waiter-1), which is recorded on orders
+    pub staff_id: String,
+
+    /// display name of staff
+    pub name: String,
+
+    /// role of staff
+    pub role: Role,
+
+    /// PBKDF2 hash (hex) of PIN, which is never returned to client
+    #[serde(skip)]
+    pub pin_hash: String,
+
+    /// random salt (hex) of PIN hash
+    #[serde(skip)]
+    pub pin_salt: String,
+
+    /// inactive staff can't log in or refresh tokens anymore
+    pub active: bool,
+
+    /// time when the account was created (UTC)
+    pub creation_time: DateTime<Utc>
+}
+
+
+/// Request payload for creating a staff account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StaffRequest{
+    pub staff_id: String,
+    pub name: String,
+    pub role: Role,
+    pub pin: String
+}
+
+
+/// This struct represents the staff accounts payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StaffResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// staff accounts (without PIN)
+    pub staff: Vec<StaffMember>
+}
+
+
+impl StaffResponse{
+    /// Utility function for creating new StaffResponse
+    pub fn new(status_code: u16, staff: Vec<StaffMember>) -> Self{
+        Self { status_code, staff }
+    }
+}
+
+
+impl IntoResponse for StaffResponse{
+    /// trait implementation to convert StaffResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// Request payload for staff login from a device
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoginRequest{
+    pub staff_id: String,
+    pub pin: String
+}
+
+
+/// Request payload for refreshing tokens
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefreshTokenRequest{
+    pub refresh_token: String
+}
+
+
+/// This struct represents the tokens payload returning back to client after login or refresh
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// always `Bearer` (send access token in `Authorization: Bearer <token>` header)
+    pub token_type: String,
+
+    /// short-lived token for other endpoints
+    pub access_token: String,
+
+    /// longer-lived token which is only accepted by refresh endpoint
+    pub refresh_token: String,
+
+    /// lifetime of access token in seconds
+    pub expires_in: i64,
+
+    /// staff of the tokens
+    pub staff_id: String,
+
+    /// role of the staff
+    pub role: Role
+}
+
+
+impl IntoResponse for TokenResponse{
+    /// trait implementation to convert TokenResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// This struct represents a seating session (a party sitting at a table) in the database.
+/// Orders are attached to the open session of their table, so successive parties don't mix their orders
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct TableSession{
+    /// unique identifier of session
+    pub session_id: i32,
+
+    /// table of the session
+    pub table_id: i16,
+
+    /// number of guests (None if the session was opened implicitly by adding orders)
+    pub guest_count: Option<i16>,
+
+    /// time that the party is seated (UTC)
+    pub open_time: DateTime<Utc>,
+
+    /// time that the party left (UTC). None while the session is open
+    pub close_time: Option<DateTime<Utc>>
+}
+
+
+/// Request payload for opening a seating session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenSessionRequest{
+    pub guest_count: i16
+}
+
+
+/// This struct represents the seating sessions payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TableSessionsResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// sessions (oldest first)
+    pub sessions: Vec<TableSession>
+}
+
+
+impl TableSessionsResponse{
+    /// Utility function for creating new TableSessionsResponse
+    pub fn new(status_code: u16, sessions: Vec<TableSession>) -> Self{
+        Self { status_code, sessions }
+    }
+}
+
+
+impl IntoResponse for TableSessionsResponse{
+    /// trait implementation to convert TableSessionsResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+/// Service charge and tax rates used for computing bills, in basis points (1/100 of percent, e.g., 700 = 7%).
+/// Service charge is applied to subtotal, and tax is applied to subtotal plus service charge
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct BillCharges{
+    pub service_charge_bps: u16,
+    pub tax_bps: u16
+}
+
+
+/// This struct represents a single line of the bill (one order with price from menu)
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct BillLineItem{
+    /// id of the billed order (the order itself is removed at checkout)
+    pub order_id: i32,
+
+    /// item name of the order
+    pub item_name: String,
+
+    /// ordered quantity
+    pub quantity: i32,
+
+    /// menu price of a single item at checkout time
+    pub unit_price: i32,
+
+    /// unit_price * quantity
+    pub amount: i32
+}
+
+
+/// This struct represents a bill record created when a table is checked out. All amounts are in smallest currency unit
+#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, Clone)]
+pub struct Bill{
+    /// unique identifier of the bill
+    pub bill_id: i32,
+
+    /// checked out table
+    pub table_id: i16,
+
+    /// billed orders (cancelled orders are not billed)
+    #[sqlx(skip)]
+    pub line_items: Vec<BillLineItem>,
+
+    /// sum of line item amounts
+    pub subtotal: i32,
+
+    /// service charge computed from subtotal
+    pub service_charge: i32,
+
+    /// tax computed from subtotal plus service charge
+    pub tax: i32,
+
+    /// subtotal + service_charge + tax
+    pub total: i32,
+
+    /// checkout time (UTC)
+    pub creation_time: DateTime<Utc>
+}
+
+
+/// This struct represents the bill payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BillResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// the bill
+    pub bill: Bill
+}
+
+
+impl BillResponse{
+    /// Utility function for creating new BillResponse
+    pub fn new(status_code: u16, bill: Bill) -> Self{
+        Self { status_code, bill }
+    }
+}
+
+
+impl IntoResponse for BillResponse{
+    /// trait implementation to convert BillResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
+/// Request payload for splitting a table's bill. Either evenly by number of guests (`{"mode": "even", "guests": 3}`)
+/// or by assigning order ids to sub-bills (`{"mode": "items", "groups": [[1, 2], [3]]}`)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum SplitBillRequest{
+    Even{ guests: u16 },
+    Items{ groups: Vec<Vec<i32>> }
+}
+
+
+/// This struct represents a part of the table's bill paid by one guest (or group). All amounts are in smallest currency unit
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SubBill{
+    /// billed orders of this sub-bill (empty for even split)
+    pub line_items: Vec<BillLineItem>,
+
+    /// share of table subtotal
+    pub subtotal: i32,
+
+    /// share of table service charge
+    pub service_charge: i32,
+
+    /// share of table tax
+    pub tax: i32,
+
+    /// subtotal + service_charge + tax. Sum of all sub-bill totals is always equal to the table total
+    pub total: i32
+}
+
+
+/// This struct represents the split bill payload returning back to client
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SplitBillResponse{
+    /// status code (for future extension)
+    pub status_code: u16,
+
+    /// table id of the bill
+    pub table_id: i16,
+
+    /// total of the whole table bill
+    pub total: i32,
+
+    /// sub-bills (in the same order as guests or groups in the request)
+    pub sub_bills: Vec<SubBill>
+}
+
+
+impl SplitBillResponse{
+    /// Utility function for creating new SplitBillResponse
+    pub fn new(status_code: u16, table_id: i16, total: i32, sub_bills: Vec<SubBill>) -> Self{
+        Self { status_code, table_id, total, sub_bills }
+    }
+}
+
+
+impl IntoResponse for SplitBillResponse{
+    /// trait implementation to convert SplitBillResponse to Axum response
+    fn into_response(self) -> axum::response::Response {
+        to_json_response(&self)
+    }
+}
+
+
 #[cfg(test)]
  mod test{
     use chrono::Utc;
 
-    use crate::model::{TableOrdersRequest, OrderItemRequest, TableOrdersResponse, OrderItem};
+    use crate::model::{TableOrdersRequest, OrderItemRequest, TableOrdersResponse, OrderItem, OrderStatus, VoidReason, Role};
 
     
     #[test]
@@ -240,6 +1145,20 @@ impl IntoResponse for TableOrdersResponse{
         assert_eq!(order.table_id, 1);
         assert_eq!(order.item_name, "C");
         assert_eq!(order.note, None);
+        assert_eq!(order.quantity, 1);
+    }
+
+
+    #[test]
+    fn test_order_item_request_quantity(){
+        let order = OrderItemRequest::new_wihout_note(1, "Beer").with_quantity(3);
+        assert_eq!(order.quantity, 3);
+
+        // quantity is optional in json payload
+        let order: OrderItemRequest = serde_json::from_str(r#"{"table_id": 1, "item_name": "Beer", "note": null}"#).unwrap();
+        assert_eq!(order.quantity, 1);
+        let order: OrderItemRequest = serde_json::from_str(r#"{"table_id": 1, "item_name": "Beer", "note": null, "quantity": 3}"#).unwrap();
+        assert_eq!(order.quantity, 3);
     }
 
 
@@ -250,9 +1169,20 @@ impl IntoResponse for TableOrdersResponse{
         let item = OrderItem{ order_id: 1, 
             table_id: 123, 
             item_name: "A".to_string(), 
+            quantity: 2,
             note: Some("B".to_string()), 
             creation_time: time, 
-            estimated_arrival_time: time
+            estimated_arrival_time: time,
+            status: OrderStatus::Ordered,
+            acknowledged_by: None,
+            acknowledgement_time: None,
+            session_id: None,
+            deleted_at: None,
+            deleted_by: None,
+            reason: None,
+            created_by: None,
+            menu_item_id: None,
+            unit_price: None
         };
 
         let orders = vec![
@@ -269,5 +1199,67 @@ impl IntoResponse for TableOrdersResponse{
     }
 
 
-    
- }
\ No newline at end of file
+    #[test]
+    fn test_order_status_transitions(){
+        use OrderStatus::*;
+
+        assert!(Ordered.is_open() && Cooking.is_open() && Ready.is_open());
+        assert!(!Served.is_open() && !Cancelled.is_open());
+
+        assert!(Ordered.can_transition_to(Cooking));
+        assert!(Cooking.can_transition_to(Ready));
+        assert!(Ready.can_transition_to(Served));
+        assert!(Ordered.can_transition_to(Cancelled));
+        assert!(Cooking.can_transition_to(Cancelled));
+        assert!(Ready.can_transition_to(Cancelled));
+
+        // served / cancelled are final
+        assert!(!Served.can_transition_to(Cancelled));
+        assert!(!Cancelled.can_transition_to(Ordered));
+        assert!(!Cancelled.can_transition_to(Cancelled));
+
+        // no skipping or going back
+        assert!(!Ordered.can_transition_to(Ready));
+        assert!(!Ready.can_transition_to(Cooking));
+        assert!(!Cooking.can_transition_to(Cooking));
+    }
+
+
+    #[test]
+    fn test_order_status_json(){
+        assert_eq!(serde_json::to_string(&OrderStatus::Cooking).unwrap(), "\"cooking\"");
+        assert_eq!(serde_json::from_str::<OrderStatus>("\"cancelled\"").unwrap(), OrderStatus::Cancelled);
+        assert_eq!(OrderStatus::Served.to_string(), "served");
+    }
+
+
+    #[test]
+    fn test_order_status_has_reached(){
+        use OrderStatus::*;
+
+        assert!(Served.has_reached(Cooking));
+        assert!(Ready.has_reached(Ready));
+        assert!(!Cooking.has_reached(Ready));
+        assert!(Cancelled.has_reached(Cancelled));
+        assert!(!Cancelled.has_reached(Ordered));
+        assert!(!Served.has_reached(Cancelled));
+    }
+
+
+    #[test]
+    fn test_void_reason_json(){
+        assert_eq!(serde_json::to_string(&VoidReason::CustomerChangedMind).unwrap(), "\"customer_changed_mind\"");
+        assert_eq!(serde_json::from_str::<VoidReason>("\"comp\"").unwrap(), VoidReason::Comp);
+        assert!(serde_json::from_str::<VoidReason>("\"other\"").is_err());
+        assert_eq!(VoidReason::KitchenError.to_string(), "kitchen_error");
+    }
+
+
+    #[test]
+    fn test_role_json(){
+        for role in [Role::Waiter, Role::Kitchen, Role::Cashier, Role::Manager, Role::Admin]{
+            assert_eq!(serde_json::to_string(&role).unwrap(), format!("\"{role}\""));
+        }
+        assert!(serde_json::from_str::<Role>("\"chef\"").is_err());
+    }
+ }
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, NotUntil, Quota, RateLimiter};
+
+use crate::auth::Credentials;
+use crate::config::AppConfig;
+use crate::context::ApiContext;
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+
+
+/// number of tracked clients of a route group, above which buckets of idle clients (full again) are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+
+/// Route groups which have separate limits (see AppConfig rate_limit_*_per_minute)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RouteGroup{
+    /// GET requests (orders, menu, tables, streams, ...)
+    Reads,
+
+    /// requests which change anything (add orders, status changes, checkout, admin, ...)
+    Writes,
+
+    /// staff login and token refresh (limited separately against PIN guessing)
+    Auth
+}
+
+
+impl RouteGroup{
+
+    /// function to get route group of a request from its method and path
+    pub fn of(method: &Method, path: &str) -> Self{
+        if path.starts_with("/api/v1/auth/"){
+            RouteGroup::Auth
+        }else if method == Method::GET || method == Method::HEAD{
+            RouteGroup::Reads
+        }else{
+            RouteGroup::Writes
+        }
+    }
+}
+
+
+/// Token bucket rate limiters of each route group, keyed by client. Groups without configured limit are not limited
+pub struct RateLimits{
+    reads: Option<DefaultKeyedRateLimiter<String>>,
+    writes: Option<DefaultKeyedRateLimiter<String>>,
+    auth: Option<DefaultKeyedRateLimiter<String>>,
+
+    /// IP addresses whose failed authentications used up their auth bucket, and until when their requests are rejected before
+    /// credentials are checked (see limit_failed_authentication)
+    blocked_clients: Mutex<HashMap<String, NotUntil<<DefaultClock as Clock>::Instant>>>,
+
+    /// clock of the limiters (also used to compute when a rejected client can retry)
+    clock: DefaultClock
+}
+
+
+impl RateLimits{
+
+    /// function to create rate limiters from config. Each bucket holds a minute of requests, so clients can burst up to the limit
+    pub fn from_config(config: &AppConfig) -> Self{
+        let clock = DefaultClock::default();
+        let limiter = |per_minute: Option<NonZeroU32>| per_minute.map(|per_minute| RateLimiter::dashmap_with_clock(Quota::per_minute(per_minute), &clock));
+
+        Self{
+            reads: limiter(config.get_rate_limit_reads_per_minute()),
+            writes: limiter(config.get_rate_limit_writes_per_minute()),
+            auth: limiter(config.get_rate_limit_auth_per_minute()),
+            blocked_clients: Mutex::new(HashMap::new()),
+            clock
+        }
+    }
+
+    /// helper function to convert wait time to ApiError::TooManyRequests.
+    /// Retry-After is in whole seconds, so it is rounded up (at least 1 second)
+    fn too_many_requests(wait_time: Duration) -> ApiError{
+        ApiError::TooManyRequests((wait_time.as_secs() + u64::from(wait_time.subsec_nanos() > 0)).max(1))
+    }
+
+    /// function to take a token from bucket of the client in the route group.
+    /// It returns ApiError::TooManyRequests with seconds to wait if the bucket is empty
+    pub fn check(&self, group: RouteGroup, client_key: &str) -> Result<(), ApiError>{
+        let limiter = match group{
+            RouteGroup::Reads => &self.reads,
+            RouteGroup::Writes => &self.writes,
+            RouteGroup::Auth => &self.auth
+        };
+        let Some(limiter) = limiter else{
+            return Ok(());
+        };
+
+        if limiter.len() > MAX_TRACKED_CLIENTS{
+            limiter.retain_recent();
+        }
+
+        limiter.check_key(&client_key.to_string())
+            .map_err(|not_until| Self::too_many_requests(not_until.wait_time_from(self.clock.now())))
+    }
+
+    /// function to count failed authentication of a client (IP address) against its auth bucket, which staff login uses as well.
+    /// Once the bucket is empty, the client is blocked until the bucket has a token again
+    pub fn record_failed_authentication(&self, client_ip: &str){
+        let Some(limiter) = &self.auth else{
+            return;
+        };
+
+        if let Err(not_until) = limiter.check_key(&client_ip.to_string()){
+            let mut blocked_clients = self.blocked_clients.lock().unwrap_or_else(|err| err.into_inner());
+            if blocked_clients.len() > MAX_TRACKED_CLIENTS{
+                let now = self.clock.now();
+                blocked_clients.retain(|_, not_until| !not_until.wait_time_from(now).is_zero());
+            }
+            blocked_clients.insert(client_ip.to_string(), not_until);
+        }
+    }
+
+    /// function to check whether a client (IP address) is blocked by its failed authentications (see record_failed_authentication).
+    /// It returns ApiError::TooManyRequests with seconds to wait if so
+    pub fn check_failed_authentications(&self, client_ip: &str) -> Result<(), ApiError>{
+        let mut blocked_clients = self.blocked_clients.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(wait_time) = blocked_clients.get(client_ip).map(|not_until| not_until.wait_time_from(self.clock.now())) else{
+            return Ok(());
+        };
+
+        if wait_time.is_zero(){
+            blocked_clients.remove(client_ip);
+            Ok(())
+        }else{
+            Err(Self::too_many_requests(wait_time))
+        }
+    }
+}
+
+
+/// helper function to get IP address of client (None if the server isn't served with connect info, e.g., in tests)
+fn client_ip(request: &Request) -> String{
+    request.extensions().get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| format!("ip:{}", address.ip()))
+        .unwrap_or_else(|| "ip:unknown".to_string())
+}
+
+
+/// helper function to get key of client from request. Staff login is keyed by IP address only, since its credentials aren't verified yet.
+/// Other requests are keyed by verified credentials (see auth::require_credentials), so that tablets behind the same NAT don't share a bucket,
+/// and by IP address while authentication is disabled. Unverified headers are never used, otherwise each made-up key would get a fresh bucket
+fn client_key(group: RouteGroup, request: &Request) -> String{
+    if group == RouteGroup::Auth{
+        return client_ip(request);
+    }
+
+    match request.extensions().get::<Credentials>(){
+        Some(Credentials{ staff_id: Some(staff_id), .. }) => format!("staff:{staff_id}"),
+        Some(Credentials{ api_key_id: Some(api_key_id), .. }) => format!("key:{api_key_id}"),
+        Some(_) => "admin".to_string(),
+        None => client_ip(request)
+    }
+}
+
+
+/// middleware function to limit failed authentications of each client (IP address) with the auth limit (see RateLimits::record_failed_authentication).
+/// It must run before authentication, so that requests of a blocked client (e.g., a tablet looping with a revoked key) are rejected
+/// without checking their credentials against the database
+pub async fn limit_failed_authentication<D: StorageDAO>(State(context): State<ApiContext<D>>, request: Request, next: Next) -> Response{
+    let client_ip = client_ip(&request);
+    if let Err(err) = context.rate_limits.check_failed_authentications(&client_ip){
+        tracing::warn!("[rate limit] request to {} from {client_ip} is rejected after failed authentications: {err}", request.uri().path());
+        return err.into_response();
+    }
+
+    let response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED{
+        context.rate_limits.record_failed_authentication(&client_ip);
+    }
+    response
+}
+
+
+/// middleware function to limit request rate of each client per route group (see RateLimits).
+/// It must run after authentication on authenticated routes, and it is applied to staff login routes separately
+pub async fn limit_request_rate<D: StorageDAO>(State(context): State<ApiContext<D>>, request: Request, next: Next) -> Response{
+    let group = RouteGroup::of(request.method(), request.uri().path());
+    let client_key = client_key(group, &request);
+
+    match context.rate_limits.check(group, &client_key){
+        Ok(()) => next.run(request).await,
+        Err(err) => {
+            tracing::warn!("[rate limit] {group:?} request to {} from {client_key} is rejected: {err}", request.uri().path());
+            err.into_response()
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use axum::body::Body;
+
+    use super::*;
+    use crate::auth::API_KEY_HEADER;
+    use crate::model::Role;
+
+
+    #[test]
+    fn test_route_group(){
+        assert_eq!(RouteGroup::of(&Method::GET, "/api/v1/tables/1/orders"), RouteGroup::Reads);
+        assert_eq!(RouteGroup::of(&Method::POST, "/api/v1/tables/1/orders"), RouteGroup::Writes);
+        assert_eq!(RouteGroup::of(&Method::DELETE, "/api/v1/admin/staff/waiter-1"), RouteGroup::Writes);
+        assert_eq!(RouteGroup::of(&Method::POST, "/api/v1/auth/login"), RouteGroup::Auth);
+    }
+
+
+    #[test]
+    fn test_client_key(){
+        let credentials = |api_key_id: Option<i32>, staff_id: Option<&str>| Credentials{ role: Role::Waiter, api_key_id, staff_id: staff_id.map(str::to_string) };
+
+        // unverified API key header is ignored
+        let mut request = Request::builder().header(API_KEY_HEADER, "key-1").body(Body::empty()).unwrap();
+        assert_eq!(client_key(RouteGroup::Writes, &request), "ip:unknown");
+        request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([192, 168, 1, 20], 50000))));
+        assert_eq!(client_key(RouteGroup::Writes, &request), "ip:192.168.1.20");
+
+        request.extensions_mut().insert(credentials(Some(3), None));
+        assert_eq!(client_key(RouteGroup::Writes, &request), "key:3");
+        assert_eq!(client_key(RouteGroup::Auth, &request), "ip:192.168.1.20");
+        request.extensions_mut().insert(credentials(None, Some("waiter-1")));
+        assert_eq!(client_key(RouteGroup::Reads, &request), "staff:waiter-1");
+        request.extensions_mut().insert(credentials(None, None));
+        assert_eq!(client_key(RouteGroup::Reads, &request), "admin");
+    }
+
+
+    #[test]
+    fn test_check_rate_limits(){
+        let config = AppConfig{ rate_limit_writes_per_minute: Some(2), ..AppConfig::default() };
+        let rate_limits = RateLimits::from_config(&config);
+
+        assert!(rate_limits.check(RouteGroup::Writes, "key:a").is_ok());
+        assert!(rate_limits.check(RouteGroup::Writes, "key:a").is_ok());
+        assert!(matches!(rate_limits.check(RouteGroup::Writes, "key:a"), Err(ApiError::TooManyRequests(retry_after)) if (1..=30).contains(&retry_after)));
+
+        // other clients and groups have their own buckets (reads are not limited)
+        assert!(rate_limits.check(RouteGroup::Writes, "key:b").is_ok());
+        for _ in 0..10{
+            assert!(rate_limits.check(RouteGroup::Reads, "key:a").is_ok());
+        }
+    }
+
+
+    #[test]
+    fn test_failed_authentications(){
+        let config = AppConfig{ rate_limit_auth_per_minute: Some(2), ..AppConfig::default() };
+        let rate_limits = RateLimits::from_config(&config);
+
+        // client is blocked once failed authentications use up its auth bucket
+        for _ in 0..2{
+            rate_limits.record_failed_authentication("ip:192.168.1.20");
+            assert!(rate_limits.check_failed_authentications("ip:192.168.1.20").is_ok());
+        }
+        rate_limits.record_failed_authentication("ip:192.168.1.20");
+        assert!(matches!(rate_limits.check_failed_authentications("ip:192.168.1.20"), Err(ApiError::TooManyRequests(retry_after)) if (1..=30).contains(&retry_after)));
+        assert!(matches!(rate_limits.check(RouteGroup::Auth, "ip:192.168.1.20"), Err(ApiError::TooManyRequests(_))));
+        assert!(rate_limits.check_failed_authentications("ip:192.168.1.21").is_ok());
+
+        // failed authentications are not limited without auth limit
+        let rate_limits = RateLimits::from_config(&AppConfig::default());
+        for _ in 0..10{
+            rate_limits.record_failed_authentication("ip:192.168.1.20");
+        }
+        assert!(rate_limits.check_failed_authentications("ip:192.168.1.20").is_ok());
+    }
+}
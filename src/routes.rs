@@ -1,17 +1,80 @@
 use axum::Router;
-use axum::routing::{get, post, delete};
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::routing::{get, post, put, delete, patch};
 use crate::context::ApiContext;
-use crate::handlers::{handle_health_check, handle_add_orders, handle_get_all_orders_for_specific_table, handle_get_specific_table_order, handle_delete_table_order};
+use crate::dao::StorageDAO;
+use crate::handlers::{handle_health_check, handle_metrics, handle_add_orders, handle_get_all_orders_for_specific_table, handle_get_specific_table_order, handle_delete_table_order, handle_get_table_order_history, handle_update_order_status, handle_decrement_order_quantity, handle_transfer_orders, handle_merge_tables, handle_get_kitchen_queue};
+use crate::menu_handlers::{handle_get_menu, handle_add_menu_item, handle_get_menu_item, handle_update_menu_item, handle_delete_menu_item};
+use crate::bill_handlers::{handle_checkout_table, handle_split_bill, handle_get_bill};
+use crate::table_handlers::{handle_get_tables, handle_add_table, handle_get_table, handle_update_table, handle_delete_table};
+use crate::session_handlers::{handle_open_session, handle_get_table_sessions, handle_get_current_session, handle_close_current_session};
+use crate::stream_handlers::{handle_table_orders_stream, handle_kitchen_stream};
+use crate::ws_handlers::handle_device_websocket;
+use crate::api_key_handlers::{handle_get_api_keys, handle_issue_api_key, handle_revoke_api_key};
+use crate::staff_handlers::{handle_login, handle_refresh_token, handle_get_staff, handle_add_staff, handle_deactivate_staff};
+use crate::auth::require_credentials;
+use crate::rate_limit::{limit_request_rate, limit_failed_authentication};
+use crate::metrics::track_request_metrics;
 
 
-pub fn app(context: ApiContext) -> Router{
+/// function to build application router on top of any DAO implementation.
+/// Every route except health check, metrics and staff login requires API key or staff token (see auth), and each handler declares roles allowed to call it.
+/// Every route except health check and metrics is rate limited per client (see rate_limit), after credentials are checked.
+/// Clients failing authentication repeatedly are limited by IP address before credentials are checked.
+/// Requests of every route are counted and timed for Prometheus (see metrics)
+#[allow(clippy::let_and_return)]
+pub fn app<D: StorageDAO>(context: ApiContext<D>) -> Router{
+    // staff login is rate limited by IP address, since it has no verified credentials
+    let login_routes = Router::new()
+    .route("/api/v1/auth/login", post(handle_login::<D>))
+    .route("/api/v1/auth/refresh", post(handle_refresh_token::<D>))
+    .route_layer(from_fn_with_state(context.clone(), limit_request_rate::<D>));
+
     let router = Router::new()
-    .route("/api/v1/health", get(handle_health_check))
-    .route("/api/v1/tables/:table_id/orders", post(handle_add_orders))
-    .route("/api/v1/tables/:table_id/orders",  get(handle_get_all_orders_for_specific_table))
-    .route("/api/v1/tables/:table_id/orders/:order_id", get(handle_get_specific_table_order))
-    .route("/api/v1/tables/:table_id/orders/:order_id", delete(handle_delete_table_order))
+    .route("/api/v1/tables", get(handle_get_tables::<D>))
+    .route("/api/v1/tables", post(handle_add_table::<D>))
+    .route("/api/v1/tables/:table_id", get(handle_get_table::<D>))
+    .route("/api/v1/tables/:table_id", put(handle_update_table::<D>))
+    .route("/api/v1/tables/:table_id", delete(handle_delete_table::<D>))
+    .route("/api/v1/tables/:table_id/orders", post(handle_add_orders::<D>))
+    .route("/api/v1/tables/:table_id/orders",  get(handle_get_all_orders_for_specific_table::<D>))
+    .route("/api/v1/tables/:table_id/orders/stream", get(handle_table_orders_stream::<D>))
+    .route("/api/v1/tables/:table_id/orders/history", get(handle_get_table_order_history::<D>))
+    .route("/api/v1/tables/:table_id/orders/:order_id", get(handle_get_specific_table_order::<D>))
+    .route("/api/v1/tables/:table_id/orders/:order_id", delete(handle_delete_table_order::<D>))
+    .route("/api/v1/tables/:table_id/orders/:order_id", patch(handle_update_order_status::<D>))
+    .route("/api/v1/tables/:table_id/orders/:order_id/decrement", post(handle_decrement_order_quantity::<D>))
+    .route("/api/v1/tables/:table_id/transfer", post(handle_transfer_orders::<D>))
+    .route("/api/v1/tables/:table_id/merge", post(handle_merge_tables::<D>))
+    .route("/api/v1/tables/:table_id/sessions", post(handle_open_session::<D>))
+    .route("/api/v1/tables/:table_id/sessions", get(handle_get_table_sessions::<D>))
+    .route("/api/v1/tables/:table_id/sessions/current", get(handle_get_current_session::<D>))
+    .route("/api/v1/tables/:table_id/sessions/current/close", post(handle_close_current_session::<D>))
+    .route("/api/v1/tables/:table_id/checkout", post(handle_checkout_table::<D>))
+    .route("/api/v1/tables/:table_id/split", post(handle_split_bill::<D>))
+    .route("/api/v1/bills/:bill_id", get(handle_get_bill::<D>))
+    .route("/api/v1/kitchen/queue", get(handle_get_kitchen_queue::<D>))
+    .route("/api/v1/kitchen/stream", get(handle_kitchen_stream::<D>))
+    .route("/api/v1/devices/ws", get(handle_device_websocket::<D>))
+    .route("/api/v1/menu", get(handle_get_menu::<D>))
+    .route("/api/v1/menu", post(handle_add_menu_item::<D>))
+    .route("/api/v1/menu/:menu_item_id", get(handle_get_menu_item::<D>))
+    .route("/api/v1/menu/:menu_item_id", put(handle_update_menu_item::<D>))
+    .route("/api/v1/menu/:menu_item_id", delete(handle_delete_menu_item::<D>))
+    .route("/api/v1/admin/api_keys", get(handle_get_api_keys::<D>))
+    .route("/api/v1/admin/api_keys", post(handle_issue_api_key::<D>))
+    .route("/api/v1/admin/api_keys/:api_key_id", delete(handle_revoke_api_key::<D>))
+    .route("/api/v1/admin/staff", get(handle_get_staff::<D>))
+    .route("/api/v1/admin/staff", post(handle_add_staff::<D>))
+    .route("/api/v1/admin/staff/:staff_id", delete(handle_deactivate_staff::<D>))
+    .route_layer(from_fn_with_state(context.clone(), limit_request_rate::<D>))
+    .route_layer(from_fn_with_state(context.clone(), require_credentials::<D>))
+    .route_layer(from_fn_with_state(context.clone(), limit_failed_authentication::<D>))
+    .merge(login_routes)
+    .route("/api/v1/health", get(handle_health_check::<D>))
+    .route("/metrics", get(handle_metrics::<D>))
     .fallback(|| async{ "hello paidy restaurant"})        
+    .layer(from_fn(track_request_metrics))
     .with_state(context);
     router
 }
@@ -0,0 +1,96 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use chrono::Utc;
+
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::model::{OpenSessionRequest, TableSession, TableSessionsResponse};
+use crate::auth::{RequireRole, StaffAccess, WaiterAccess};
+use crate::context::ApiContext;
+use crate::events::OrderEventType;
+use crate::handlers::check_table;
+
+
+/// helper function to push opening or closing of a seating session to streaming clients (with orders of the table's open session afterwards)
+async fn publish_session_change<D: StorageDAO>(context: &ApiContext<D>, event_type: OrderEventType, table_id: i16){
+    match context.dbo.get_table_orders(table_id, None).await{
+        Ok(orders) => { context.events.publish(event_type, table_id, None, orders); },
+        Err(err) => tracing::error!("[session] failed to get orders of table {table_id}: {err}")
+    }
+}
+
+
+/// handler function for seating a new party at a table. New orders of the table are attached to this session until it is closed.
+/// `session_opened` event is pushed to streaming clients. It returns the opened session
+pub async fn handle_open_session<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<WaiterAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    WithRejection(Json(request), _): WithRejection<Json<OpenSessionRequest>, ApiError>) -> Response{
+
+    tracing::info!("[session open] table id from path = {table_id}, guests = {}", request.guest_count);
+    check_table!(context, table_id);
+
+    if request.guest_count < 1{
+        return ApiError::BadRequest("guest count must be positive".to_string()).into_response();
+    }
+
+    let session = match context.dbo.open_session(table_id, request.guest_count, Utc::now()).await{
+        Ok(session) => session,
+        Err(err) => return err.into_response()
+    };
+
+    publish_session_change(&context, OrderEventType::SessionOpened, table_id).await;
+    TableSessionsResponse::new(200, vec![session]).into_response() // generate TableSessionsResponse from session
+}
+
+
+/// handler function for getting all (current and past) seating sessions of a table
+pub async fn handle_get_table_sessions<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[session get all] table id from path = {table_id}");
+    check_table!(context, table_id);
+
+    context.dbo.get_table_sessions(table_id)
+        .await
+        .map( |sessions: Vec<TableSession>| TableSessionsResponse::new(200, sessions).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for getting the open seating session of a table
+pub async fn handle_get_current_session<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[session get current] table id from path = {table_id}");
+    check_table!(context, table_id);
+
+    context.dbo.get_current_session(table_id)
+        .await
+        .map( |session: TableSession| TableSessionsResponse::new(200, vec![session]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for closing the open seating session of a table (the party left without unbilled orders, otherwise checkout closes the session).
+/// Its removed orders are kept and can be read with order history, while the table starts empty for the next party. `session_closed` event is pushed to streaming clients.
+/// It returns the closed session (409 if the session still has unbilled orders)
+pub async fn handle_close_current_session<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<WaiterAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[session close] table id from path = {table_id}");
+    check_table!(context, table_id);
+
+    let session = match context.dbo.close_current_session(table_id, Utc::now()).await{
+        Ok(session) => session,
+        Err(err) => return err.into_response()
+    };
+
+    publish_session_change(&context, OrderEventType::SessionClosed, table_id).await;
+    TableSessionsResponse::new(200, vec![session]).into_response()
+}
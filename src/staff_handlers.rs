@@ -0,0 +1,217 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use chrono::Utc;
+
+use crate::auth::{generate_pin_salt, hash_pin, RequireRole, AdminAccess};
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::jwt::{JwtKeys, TokenType};
+use crate::model::{StaffMember, StaffRequest, StaffResponse, LoginRequest, RefreshTokenRequest};
+use crate::context::ApiContext;
+
+
+/// maximum length of staff id and name (same as the columns)
+const MAX_STAFF_TEXT_LENGTH: usize = 64;
+
+/// allowed number of PIN digits
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_PIN_LENGTH: usize = 12;
+
+
+/// helper function to validate staff request (id and name are required, PIN is 4 to 12 digits)
+fn validate_staff_request(request: &StaffRequest) -> Result<(), ApiError>{
+    for (field, value) in [("staff_id", &request.staff_id), ("name", &request.name)]{
+        if value.trim().is_empty() || value.chars().count() > MAX_STAFF_TEXT_LENGTH{
+            return Err(ApiError::BadRequest(format!("staff {field} must be 1 to {MAX_STAFF_TEXT_LENGTH} characters")));
+        }
+    }
+
+    if !(MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&request.pin.len()) || !request.pin.chars().all(|c| c.is_ascii_digit()){
+        return Err(ApiError::BadRequest(format!("PIN must be {MIN_PIN_LENGTH} to {MAX_PIN_LENGTH} digits")));
+    }
+
+    Ok(())
+}
+
+
+/// helper function to get keys of staff login tokens. Login is unavailable if they are not configured
+fn jwt_keys<D: StorageDAO>(context: &ApiContext<D>) -> Result<&JwtKeys, ApiError>{
+    context.jwt.as_deref()
+        .ok_or_else(|| ApiError::ServerError("staff login is not configured (JWT_SECRET or JWT key files)".to_string()))
+}
+
+
+/// salt of the PIN hash computed for unknown staff, so that login of unknown staff takes as long as a wrong PIN
+const DUMMY_PIN_SALT: &str = "00000000000000000000000000000000";
+
+
+/// helper function to check that staff can log in (or refresh tokens). Unknown and inactive staff are unauthorized as well as wrong PIN,
+/// so that client can't tell which one is wrong. The PIN is hashed in every case, so response time doesn't tell it either
+fn check_active_staff(staff: Result<StaffMember, ApiError>, pin: Option<&str>) -> Result<StaffMember, ApiError>{
+    let staff = match staff{
+        Ok(staff) => Some(staff),
+        Err(ApiError::StaffNotFound) => None,
+        Err(err) => return Err(err)
+    };
+
+    let pin_matches = match (pin, &staff){
+        (Some(pin), Some(staff)) => hash_pin(pin, &staff.pin_salt) == staff.pin_hash,
+        (Some(pin), None) => {
+            std::hint::black_box(hash_pin(pin, DUMMY_PIN_SALT));
+            false
+        },
+        (None, _) => true
+    };
+
+    match staff{
+        Some(staff) if staff.active && pin_matches => Ok(staff),
+        _ => Err(ApiError::Unauthorized)
+    }
+}
+
+
+/// handler function for staff login from a device with PIN. It returns access and refresh tokens (TokenResponse)
+pub async fn handle_login<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    WithRejection(Json(request), _): WithRejection<Json<LoginRequest>, ApiError>) -> Response{
+
+    tracing::info!("[login] staff_id = {}", request.staff_id);
+
+    let jwt = match jwt_keys(&context){
+        Ok(jwt) => jwt,
+        Err(err) => return err.into_response()
+    };
+
+    let staff = context.dbo.get_staff_member(&request.staff_id).await;
+    check_active_staff(staff, Some(&request.pin))
+        .and_then(|staff| jwt.issue_tokens(&staff, Utc::now()))
+        .map(IntoResponse::into_response)
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for issuing new tokens with refresh token. Role is read again, so that role changes and deactivation take effect
+pub async fn handle_refresh_token<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    WithRejection(Json(request), _): WithRejection<Json<RefreshTokenRequest>, ApiError>) -> Response{
+
+    tracing::info!("[refresh token]");
+
+    let jwt = match jwt_keys(&context){
+        Ok(jwt) => jwt,
+        Err(err) => return err.into_response()
+    };
+
+    let claims = match jwt.verify_token(&request.refresh_token, TokenType::Refresh){
+        Ok(claims) => claims,
+        Err(err) => return err.into_response()
+    };
+
+    let staff = context.dbo.get_staff_member(&claims.sub).await;
+    check_active_staff(staff, None)
+        .and_then(|staff| jwt.issue_tokens(&staff, Utc::now()))
+        .map(IntoResponse::into_response)
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for getting all staff accounts (including inactive ones). PINs are never returned
+pub async fn handle_get_staff<D: StorageDAO>(State(context): State<ApiContext<D>>, _role: RequireRole<AdminAccess>) -> Response{
+    tracing::info!("[staff get all]");
+
+    context.dbo.get_staff()
+        .await
+        .map( |staff: Vec<StaffMember>| StaffResponse::new(200, staff).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for creating a staff account with PIN. It returns the created account
+pub async fn handle_add_staff<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<AdminAccess>,
+    WithRejection(Json(request), _): WithRejection<Json<StaffRequest>, ApiError>) -> Response{
+
+    tracing::info!("[staff add] staff_id = {}, role = {}", request.staff_id, request.role);
+
+    if let Err(err) = validate_staff_request(&request){
+        tracing::error!("[staff] invalid staff request: {err}");
+        return err.into_response();
+    }
+
+    let pin_salt = generate_pin_salt();
+    let staff = StaffMember{
+        staff_id: request.staff_id.trim().to_string(),
+        name: request.name.trim().to_string(),
+        role: request.role,
+        pin_hash: hash_pin(&request.pin, &pin_salt),
+        pin_salt,
+        active: true,
+        creation_time: Utc::now()
+    };
+
+    context.dbo.add_staff(&staff)
+        .await
+        .map( |staff: StaffMember| StaffResponse::new(200, vec![staff]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for deactivating a staff account (it can't log in or refresh tokens anymore). It returns the deactivated account
+pub async fn handle_deactivate_staff<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<AdminAccess>,
+    WithRejection(Path(staff_id), _): WithRejection<Path<String>, ApiError>) -> Response{
+
+    tracing::info!("[staff deactivate] staff_id = {staff_id}");
+
+    context.dbo.deactivate_staff(&staff_id)
+        .await
+        .map( |staff: StaffMember| StaffResponse::new(200, vec![staff]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use crate::model::Role;
+
+    use super::*;
+
+
+    #[test]
+    fn test_validate_staff_request(){
+        let request = |staff_id: &str, pin: &str| StaffRequest{ staff_id: staff_id.to_string(), name: "Somchai".to_string(), role: Role::Waiter, pin: pin.to_string() };
+
+        assert!(validate_staff_request(&request("waiter-1", "1234")).is_ok());
+        assert!(validate_staff_request(&request("waiter-1", "123456789012")).is_ok());
+        assert!(validate_staff_request(&request(" ", "1234")).is_err());
+        assert!(validate_staff_request(&request("waiter-1", "123")).is_err());
+        assert!(validate_staff_request(&request("waiter-1", "1234567890123")).is_err());
+        assert!(validate_staff_request(&request("waiter-1", "12a4")).is_err());
+    }
+
+
+    #[test]
+    fn test_check_active_staff(){
+        let pin_salt = generate_pin_salt();
+        let staff = StaffMember{
+            staff_id: "waiter-1".to_string(),
+            name: "Somchai".to_string(),
+            role: Role::Waiter,
+            pin_hash: hash_pin("1234", &pin_salt),
+            pin_salt,
+            active: true,
+            creation_time: Utc::now()
+        };
+
+        assert!(check_active_staff(Ok(staff.clone()), Some("1234")).is_ok());
+        assert!(check_active_staff(Ok(staff.clone()), None).is_ok());
+        assert!(matches!(check_active_staff(Ok(staff.clone()), Some("4321")), Err(ApiError::Unauthorized)));
+        assert!(matches!(check_active_staff(Err(ApiError::StaffNotFound), Some("1234")), Err(ApiError::Unauthorized)));
+        assert!(matches!(check_active_staff(Err(ApiError::StaffNotFound), None), Err(ApiError::Unauthorized)));
+
+        let inactive = StaffMember{ active: false, ..staff };
+        assert!(matches!(check_active_staff(Ok(inactive.clone()), Some("1234")), Err(ApiError::Unauthorized)));
+        assert!(matches!(check_active_staff(Ok(inactive), None), Err(ApiError::Unauthorized)));
+    }
+}
@@ -0,0 +1,137 @@
+use std::convert::Infallible;
+
+use axum::extract::{State, Path};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum_extra::extract::WithRejection;
+use futures::{stream, Stream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::auth::{RequireRole, StaffAccess};
+use crate::context::ApiContext;
+use crate::handlers::check_table;
+use crate::events::{LastEventId, OrderEvent, OrderEventSubscription};
+
+
+/// SSE event name which tells client that some events are lost (client should re-fetch the orders)
+const RESYNC_EVENT: &str = "resync";
+
+
+/// helper function to read event id of the last received event from `Last-Event-ID` header (sent by reconnecting EventSource)
+fn parse_last_event_id(headers: &HeaderMap) -> Option<LastEventId>{
+    headers.get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+
+/// helper function to convert order event to SSE event (event name is the event type, id is `{epoch}:{event_id}`, data is OrderEvent json)
+fn to_sse_event(event: &OrderEvent) -> Event{
+    match serde_json::to_string(event){
+        Ok(json) => Event::default()
+            .id(LastEventId::from(event).to_string())
+            .event(event.event_type.as_str())
+            .data(json),
+        Err(err) => {
+            tracing::error!("[stream] failed to serialize event {}: {err}", event.event_id);
+            resync_event()
+        }
+    }
+}
+
+
+/// helper function to generate resync event (no id, so that client resumes from its last received event)
+fn resync_event() -> Event{
+    Event::default()
+        .event(RESYNC_EVENT)
+        .data("{}")
+}
+
+
+/// function to convert subscription to stream of SSE events of a table (or all tables if table_id is None).
+/// Missed events are sent first, followed by new events. Resync event is sent whenever events are lost
+fn order_event_stream(subscription: OrderEventSubscription, table_id: Option<i16>) -> impl Stream<Item = Result<Event, Infallible>>{
+    let is_subscribed = move |event: &OrderEvent| match table_id{
+        None => true,
+        Some(table_id) => event.is_for_table(table_id)
+    };
+    let OrderEventSubscription{ missed, complete, receiver } = subscription;
+
+    let missed_events: Vec<Event> = (!complete).then(resync_event).into_iter()
+        .chain(missed.iter().filter(|event| is_subscribed(event)).map(to_sse_event))
+        .collect();
+
+    let new_events = stream::unfold(receiver, move |mut receiver| async move {
+        loop{
+            match receiver.recv().await{
+                Ok(event) if is_subscribed(&event) => return Some((to_sse_event(&event), receiver)),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("[stream] slow client skipped {skipped} events");
+                    return Some((resync_event(), receiver));
+                },
+                Err(RecvError::Closed) => return None
+            }
+        }
+    });
+
+    stream::iter(missed_events)
+        .chain(new_events)
+        .map(Ok)
+}
+
+
+/// handler function for streaming order changes of a specific table as Server-Sent Events.
+/// Reconnecting clients receive the events after `Last-Event-ID` first
+pub async fn handle_table_orders_stream<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    headers: HeaderMap) -> Response{
+
+    let last_event_id = parse_last_event_id(&headers);
+    tracing::info!("[table stream] table id from path = {table_id}, last event id = {last_event_id:?}");
+
+    check_table!(context, table_id);
+
+    let subscription = context.events.subscribe(last_event_id);
+    Sse::new(order_event_stream(subscription, Some(table_id)))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+
+/// handler function for streaming order changes of all tables (kitchen display) as Server-Sent Events.
+/// Reconnecting clients receive the events after `Last-Event-ID` first
+pub async fn handle_kitchen_stream<D: StorageDAO>(State(context): State<ApiContext<D>>, _role: RequireRole<StaffAccess>, headers: HeaderMap) -> Response{
+    let last_event_id = parse_last_event_id(&headers);
+    tracing::info!("[kitchen stream] last event id = {last_event_id:?}");
+
+    let subscription = context.events.subscribe(last_event_id);
+    Sse::new(order_event_stream(subscription, None))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_last_event_id(){
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_last_event_id(&headers), None);
+
+        headers.insert("last-event-id", HeaderValue::from_static("0123abcd:42"));
+        assert_eq!(parse_last_event_id(&headers), Some(LastEventId{ epoch: "0123abcd".to_string(), event_id: 42 }));
+
+        headers.insert("last-event-id", HeaderValue::from_static("abc"));
+        assert_eq!(parse_last_event_id(&headers), None);
+    }
+}
@@ -0,0 +1,172 @@
+use axum::Json;
+use axum::extract::{State, Path};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use futures::TryFutureExt;
+
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::model::{RestaurantTable, TableRequest, TablesResponse};
+use crate::auth::{RequireRole, ManagerAccess, StaffAccess};
+use crate::context::ApiContext;
+
+
+/// maximum length of table label and section (same as table columns)
+const MAX_TABLE_TEXT_LENGTH: usize = 64;
+
+
+/// function to check that table is registered and active. Order handlers use it to reject unknown or deactivated tables
+pub(crate) async fn check_active_table<D: StorageDAO>(context: &ApiContext<D>, table_id: i16) -> Result<(), ApiError>{
+    if table_id < 1{
+        return Err(ApiError::TableNotFound);
+    }
+
+    match context.dbo.get_table(table_id).await?{
+        table if table.active => Ok(()),
+        _ => Err(ApiError::TableNotFound)
+    }
+}
+
+
+/// helper function to validate table request (label and section are required, capacity must be positive)
+fn validate_table_request(request: &TableRequest) -> Result<(), ApiError>{
+    for (field, value) in [("label", &request.label), ("section", &request.section)]{
+        if value.trim().is_empty() || value.chars().count() > MAX_TABLE_TEXT_LENGTH{
+            return Err(ApiError::BadRequest(format!("table {field} must be 1 to {MAX_TABLE_TEXT_LENGTH} characters")));
+        }
+    }
+
+    if request.capacity < 1{
+        return Err(ApiError::BadRequest("table capacity must be positive".to_string()));
+    }
+
+    Ok(())
+}
+
+
+/// validation macro for table request. Error is converted to Axum's response and returned to client
+macro_rules! validate_table_request {
+    ($request: expr) => {
+        if let Err(err) = validate_table_request($request){
+            tracing::error!("[table] invalid table request: {err}");
+            return err.into_response();
+        }
+    };
+}
+
+
+/// utility macro to check table id from path (ids start from 1)
+macro_rules! check_table_id {
+    ($table_id: expr) => {
+        if $table_id < 1{
+            tracing::error!("out of range table id={}", $table_id);
+            return ApiError::TableNotFound.into_response();
+        }
+    };
+}
+
+
+/// handler function for getting all tables (including inactive tables)
+pub async fn handle_get_tables<D: StorageDAO>(State(context): State<ApiContext<D>>, _role: RequireRole<StaffAccess>) -> Response{
+    tracing::info!("[table get all]");
+
+    context.dbo.get_tables()
+        .await
+        .map( |tables: Vec<RestaurantTable>| TablesResponse::new(200, tables).into_response()) // generate TablesResponse from tables
+        .unwrap_or_else(ApiError::into_response) // generate error response in case of error
+}
+
+
+/// handler function for registering a new table. It returns the created table (including generated id)
+pub async fn handle_add_table<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Json(request), _): WithRejection<Json<TableRequest>, ApiError>) -> Response{
+
+    tracing::info!("[table add] label = {}", request.label);
+    validate_table_request!(&request);
+
+    context.dbo.add_table(&request)
+        .await
+        .map( |table: RestaurantTable| TablesResponse::new(200, vec![table]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for getting a specific table
+pub async fn handle_get_table<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[table get specific] table_id = {table_id}");
+    check_table_id!(table_id);
+
+    context.dbo.get_table(table_id)
+        .await
+        .map( |table: RestaurantTable| TablesResponse::new(200, vec![table]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for replacing a specific table (e.g., change capacity or deactivate it). It returns the updated table
+pub async fn handle_update_table<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>,
+    WithRejection(Json(request), _): WithRejection<Json<TableRequest>, ApiError>) -> Response{
+
+    tracing::info!("[table update] table_id = {table_id}, label = {}, active = {}", request.label, request.active);
+    check_table_id!(table_id);
+    validate_table_request!(&request);
+
+    context.dbo.update_table(table_id, &request)
+        .await
+        .map( |table: RestaurantTable| TablesResponse::new(200, vec![table]).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+/// handler function for deleting a specific table. Then returns the updated tables
+pub async fn handle_delete_table<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<ManagerAccess>,
+    WithRejection(Path(table_id), _): WithRejection<Path<i16>, ApiError>) -> Response{
+
+    tracing::info!("[table delete] table_id = {table_id}");
+    check_table_id!(table_id);
+
+    context.dbo.remove_table(table_id) // remove table
+        .and_then( |_| context.dbo.get_tables()) // get updated tables
+        .await
+        .map( |tables: Vec<RestaurantTable>| TablesResponse::new(200, tables).into_response())
+        .unwrap_or_else(ApiError::into_response)
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+    use crate::dao::table_dao::TableDAO;
+
+    #[test]
+    fn test_validate_table_request(){
+        assert!(validate_table_request(&TableRequest::new("Patio 3", 4, "patio")).is_ok());
+
+        assert!(matches!(validate_table_request(&TableRequest::new(" ", 4, "patio")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_table_request(&TableRequest::new("Patio 3", 4, "")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_table_request(&TableRequest::new(&"x".repeat(65), 4, "patio")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_table_request(&TableRequest::new("Patio 3", 0, "patio")), Err(ApiError::BadRequest(_))));
+    }
+
+
+    #[tokio::test]
+    async fn test_check_active_table(){
+        let context = ApiContext::new_in_memory();
+        assert!(check_active_table(&context, 1).await.is_ok());
+        assert!(matches!(check_active_table(&context, 0).await, Err(ApiError::TableNotFound)));
+        assert!(matches!(check_active_table(&context, 1000).await, Err(ApiError::TableNotFound)));
+
+        let mut request = TableRequest::new("Table 1", 4, "main");
+        request.active = false;
+        context.dbo.update_table(1, &request).await.unwrap();
+        assert!(matches!(check_active_table(&context, 1).await, Err(ApiError::TableNotFound)));
+    }
+}
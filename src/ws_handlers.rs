@@ -0,0 +1,277 @@
+use std::collections::BTreeSet;
+
+use axum::extract::{State, Query};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::WithRejection;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::dao::StorageDAO;
+use crate::error::ApiError;
+use crate::auth::{RequireRole, StaffAccess};
+use crate::context::ApiContext;
+use crate::table_handlers::check_active_table;
+use crate::events::{LastEventId, OrderEvent, OrderEventType};
+use crate::model::DeviceQuery;
+
+
+/// maximum length of device id (same as `acknowledged_by` column)
+const MAX_DEVICE_ID_LENGTH: usize = 64;
+
+
+/// Message sent by device over WebSocket (json object with `type` field)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeviceRequest{
+    /// replace subscribed tables. If last_event_id is given, events after it are sent first (same as SSE `Last-Event-ID`).
+    /// epoch is the epoch of the last received event. Resync is sent instead if it doesn't match (e.g., server restarted)
+    Subscribe{
+        table_ids: Vec<i16>,
+
+        #[serde(default)]
+        last_event_id: Option<u64>,
+
+        #[serde(default)]
+        epoch: Option<String>
+    },
+
+    /// record that the device has seen the order
+    Acknowledge{
+        table_id: i16,
+        order_id: i32
+    }
+}
+
+
+/// Message sent by server over WebSocket (json object with `type` field)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeviceMessage{
+    /// subscription is changed
+    Subscribed{
+        table_ids: Vec<i16>
+    },
+
+    /// acknowledgement is recorded
+    Acknowledged{
+        table_id: i16,
+        order_id: i32
+    },
+
+    /// order change of a subscribed table
+    Event(OrderEvent),
+
+    /// some events are lost, so device should re-fetch the orders
+    Resync,
+
+    /// device request is rejected (same fields as error object of REST API)
+    Error{
+        status_code: u16,
+        error_cause: String
+    }
+}
+
+
+impl From<OrderEvent> for DeviceMessage{
+    /// resync event (e.g., after events of other server instances are lost) is sent as resync message
+    fn from(event: OrderEvent) -> Self {
+        match event.event_type{
+            OrderEventType::Resync => Self::Resync,
+            _ => Self::Event(event)
+        }
+    }
+}
+
+
+impl From<ApiError> for DeviceMessage{
+    fn from(err: ApiError) -> Self {
+        Self::Error{
+            status_code: err.status_code(),
+            error_cause: err.to_string()
+        }
+    }
+}
+
+
+/// state of a connected device
+struct DeviceSession<D: StorageDAO>{
+    context: ApiContext<D>,
+    device_id: String,
+    table_ids: BTreeSet<i16>,
+    receiver: broadcast::Receiver<OrderEvent>
+}
+
+
+impl<D: StorageDAO> DeviceSession<D>{
+    /// function to check whether the event is of a subscribed table
+    fn is_subscribed(&self, event: &OrderEvent) -> bool{
+        self.table_ids.iter().any(|table_id| event.is_for_table(*table_id))
+    }
+
+
+    /// function to handle subscribe request. It returns the reply and missed events of the subscribed tables
+    async fn subscribe(&mut self, table_ids: Vec<i16>, last_event_id: Option<LastEventId>) -> Result<Vec<DeviceMessage>, ApiError>{
+        for table_id in &table_ids{
+            check_active_table(&self.context, *table_id).await?;
+        }
+
+        self.table_ids = table_ids.into_iter().collect();
+        let mut messages = vec![DeviceMessage::Subscribed{ table_ids: self.table_ids.iter().copied().collect() }];
+
+        if last_event_id.is_some(){
+            let subscription = self.context.events.subscribe(last_event_id);
+            self.receiver = subscription.receiver;
+
+            if !subscription.complete{
+                messages.push(DeviceMessage::Resync);
+            }
+            messages.extend(subscription.missed.into_iter()
+                .filter(|event| self.is_subscribed(event))
+                .map(DeviceMessage::from));
+        }
+        Ok(messages)
+    }
+
+
+    /// function to handle acknowledge request. Acknowledgement is stored on the order and published to other devices
+    async fn acknowledge(&self, table_id: i16, order_id: i32) -> Result<DeviceMessage, ApiError>{
+        check_active_table(&self.context, table_id).await?;
+        if order_id < 1{
+            return Err(ApiError::OrderNotFound);
+        }
+
+        self.context.dbo.acknowledge_order(table_id, order_id, &self.device_id, Utc::now()).await?;
+        let orders = self.context.dbo.get_table_orders(table_id, None).await?;
+        self.context.events.publish(OrderEventType::OrderAcknowledged, table_id, Some(order_id), orders);
+
+        Ok(DeviceMessage::Acknowledged{ table_id, order_id })
+    }
+
+
+    /// function to handle text message from device. Invalid messages are answered with error message
+    async fn handle_request(&mut self, text: &str) -> Vec<DeviceMessage>{
+        let result = match serde_json::from_str(text){
+            Ok(DeviceRequest::Subscribe{ table_ids, last_event_id, epoch }) => {
+                let last_event_id = last_event_id.map(|event_id| LastEventId{ epoch: epoch.unwrap_or_default(), event_id });
+                self.subscribe(table_ids, last_event_id).await
+            },
+            Ok(DeviceRequest::Acknowledge{ table_id, order_id }) => self.acknowledge(table_id, order_id).await.map(|reply| vec![reply]),
+            Err(err) => Err(ApiError::BadRequest(format!("invalid device message: {err}")))
+        };
+
+        result.unwrap_or_else(|err| {
+            tracing::error!("[ws] device {} request failed: {err}", self.device_id);
+            vec![err.into()]
+        })
+    }
+}
+
+
+/// helper function to send messages to device. It returns false if the connection is closed
+async fn send_messages(socket: &mut WebSocket, messages: Vec<DeviceMessage>) -> bool{
+    for message in messages{
+        let json = match serde_json::to_string(&message){
+            Ok(json) => json,
+            Err(err) => {
+                tracing::error!("[ws] failed to serialize message: {err}");
+                continue;
+            }
+        };
+
+        if socket.send(Message::Text(json)).await.is_err(){
+            return false;
+        }
+    }
+    true
+}
+
+
+/// function to serve a connected device until either side closes the connection
+async fn run_device_session<D: StorageDAO>(mut socket: WebSocket, mut session: DeviceSession<D>){
+    tracing::info!("[ws] device {} connected", session.device_id);
+
+    loop{
+        let messages = tokio::select!{
+            request = socket.recv() => match request{
+                Some(Ok(Message::Text(text))) => session.handle_request(&text).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue // ping / pong are answered by axum, binary messages are ignored
+            },
+            event = session.receiver.recv() => match event{
+                Ok(event) if session.is_subscribed(&event) => vec![event.into()],
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("[ws] device {} skipped {skipped} events", session.device_id);
+                    vec![DeviceMessage::Resync]
+                },
+                Err(RecvError::Closed) => break
+            }
+        };
+
+        if !send_messages(&mut socket, messages).await{
+            break;
+        }
+    }
+
+    tracing::info!("[ws] device {} disconnected", session.device_id);
+}
+
+
+/// handler function for connecting a device (e.g., kitchen display or floor tablet) to WebSocket channel.
+/// Device subscribes to tables, receives their order changes, and acknowledges orders over the same connection
+pub async fn handle_device_websocket<D: StorageDAO>(State(context): State<ApiContext<D>>,
+    _role: RequireRole<StaffAccess>,
+    WithRejection(Query(query), _): WithRejection<Query<DeviceQuery>, ApiError>,
+    upgrade: WebSocketUpgrade) -> Response{
+
+    tracing::info!("[ws] device id = {}", query.device_id);
+
+    let device_id = query.device_id.trim().to_string();
+    if device_id.is_empty() || device_id.chars().count() > MAX_DEVICE_ID_LENGTH{
+        return ApiError::BadRequest(format!("device id must be 1 to {MAX_DEVICE_ID_LENGTH} characters")).into_response();
+    }
+
+    upgrade.on_upgrade(move |socket| {
+        let session = DeviceSession{
+            receiver: context.events.subscribe(None).receiver,
+            context,
+            device_id,
+            table_ids: BTreeSet::new()
+        };
+        run_device_session(socket, session)
+    })
+}
+
+
+
+#[cfg(test)]
+mod test{
+    use super::*;
+
+    #[test]
+    fn test_device_message_json(){
+        let request: DeviceRequest = serde_json::from_str(r#"{"type": "subscribe", "table_ids": [1, 2]}"#).unwrap();
+        assert_eq!(request, DeviceRequest::Subscribe{ table_ids: vec![1, 2], last_event_id: None, epoch: None });
+
+        let request: DeviceRequest = serde_json::from_str(r#"{"type": "subscribe", "table_ids": [1], "last_event_id": 3, "epoch": "0123abcd"}"#).unwrap();
+        assert_eq!(request, DeviceRequest::Subscribe{ table_ids: vec![1], last_event_id: Some(3), epoch: Some("0123abcd".to_string()) });
+
+        let request: DeviceRequest = serde_json::from_str(r#"{"type": "acknowledge", "table_id": 1, "order_id": 42}"#).unwrap();
+        assert_eq!(request, DeviceRequest::Acknowledge{ table_id: 1, order_id: 42 });
+
+        let message = serde_json::to_value(DeviceMessage::from(ApiError::OrderNotFound)).unwrap();
+        assert_eq!(message["type"], "error");
+        assert_eq!(message["status_code"], 404);
+
+        let message = serde_json::to_value(DeviceMessage::Resync).unwrap();
+        assert_eq!(message, serde_json::json!({"type": "resync"}));
+
+        // resync event of the event bus is sent as resync message
+        let bus = crate::events::OrderEventBus::new(2);
+        let event = bus.publish(OrderEventType::OrdersAdded, 1, None, Vec::new());
+        assert_eq!(DeviceMessage::from(event.clone()), DeviceMessage::Event(event));
+        assert_eq!(DeviceMessage::from(bus.publish_resync()), DeviceMessage::Resync);
+    }
+}
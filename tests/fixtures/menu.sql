@@ -0,0 +1,6 @@
+INSERT INTO menu_items(name, category, price, prep_time_minutes, active) VALUES
+    ('Kapao', 'main', 12000, 10, TRUE),
+    ('Ramen', 'main', 15000, 15, TRUE),
+    ('Pizza', 'main', 25000, 20, TRUE),
+    ('Sushi', 'main', 30000, 15, TRUE),
+    ('Seasonal soup', 'soup', 8000, 5, FALSE);
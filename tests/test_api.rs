@@ -9,11 +9,27 @@ use axum::{
 use serde_json::{json, Value};
 use tower::ServiceExt; // for `call`, `oneshot`, and `ready`
 
-use restaurant_server::model::TableOrdersResponse;
+use restaurant_server::model::{TableOrdersResponse, OrderStatus, MenuResponse, MenuItemRequest, BillResponse, SplitBillResponse, TableOrdersRequest, KitchenQueueResponse, TablesResponse, TableSessionsResponse, TableTransferResponse};
+use restaurant_server::dao::mem_order_dao::MemTableOrderDAO;
+use restaurant_server::handlers::process_order_requests;
+use restaurant_server::estimation::KitchenLoadEstimation;
+use std::sync::Arc;
+use restaurant_server::config::AppConfig;
+use restaurant_server::dao::pg_order_dao::PgTableOrderDAO;
 use restaurant_server::context::ApiContext;
+use restaurant_server::dao::StorageDAO;
+use restaurant_server::dao::order_dao::TableOrderDAO;
+use restaurant_server::dao::menu_dao::MenuDAO;
 use restaurant_server::routes::app;
+use restaurant_server::events::{LastEventId, OrderEventType};
+use restaurant_server::event_relay::spawn_order_event_relay;
+use restaurant_server::auth::{API_KEY_HEADER, hash_api_key};
+use restaurant_server::dao::api_key_dao::ApiKeyDAO;
+use restaurant_server::model::Role;
+use restaurant_server::metrics::metrics;
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test(fixtures("orders"))]
 async fn test_get_all_valid_orders(db: PgPool) {
     let context: ApiContext = ApiContext::new(db);
@@ -132,6 +148,7 @@ async fn test_get_specific_order(db: PgPool) {
 
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test(fixtures("orders"))]
 async fn test_get_specific_non_existence_order(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -167,7 +184,7 @@ async fn test_get_specific_non_existence_order(db: PgPool) {
 
 
 
-#[sqlx::test(fixtures("orders"))]
+#[sqlx::test(fixtures("menu", "orders"))]
 async fn test_add_orders(db: PgPool) {
     let context: ApiContext = ApiContext::new(db);
 
@@ -186,12 +203,12 @@ async fn test_add_orders(db: PgPool) {
                         "orders": [
                           {
                               "table_id": 44,
-                              "item_name": "A",
+                              "item_name": "Pizza",
                               "note": "Some note"
                           },
                           {
                               "table_id": 44,
-                              "item_name": "C"
+                              "item_name": "Sushi"
                           }
                         ]
                     }
@@ -211,11 +228,11 @@ async fn test_add_orders(db: PgPool) {
     assert_eq!(orders.status_code, 200);
     assert_eq!(orders.orders.len(), 2);
     assert_eq!(orders.orders[0].table_id, 44);
-    assert_eq!(orders.orders[0].item_name, "A");
+    assert_eq!(orders.orders[0].item_name, "Pizza");
     assert_eq!(orders.orders[0].note.as_deref(), Some("Some note"));
 
     assert_eq!(orders.orders[1].table_id, 44);
-    assert_eq!(orders.orders[1].item_name, "C");
+    assert_eq!(orders.orders[1].item_name, "Sushi");
     assert_eq!(orders.orders[1].note.as_deref(), None);
 }
 
@@ -318,6 +335,7 @@ async fn test_add_orders_with_mismatch_table_id_in_path(db: PgPool) {
 }
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_add_orders_with_out_of_range_path_table_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -370,6 +388,7 @@ async fn test_add_orders_with_out_of_range_path_table_id(db: PgPool) {
 
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_get_all_orders_with_out_of_range_path_table_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -402,6 +421,7 @@ async fn test_get_all_orders_with_out_of_range_path_table_id(db: PgPool) {
 
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_get_specific_order_with_out_of_range_path_table_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -434,6 +454,7 @@ async fn test_get_specific_order_with_out_of_range_path_table_id(db: PgPool) {
 
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_get_specific_order_with_out_of_range_path_order_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -465,6 +486,7 @@ async fn test_get_specific_order_with_out_of_range_path_order_id(db: PgPool) {
 }
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_get_specific_order_with_out_of_range_path_ids(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -496,6 +518,7 @@ async fn test_get_specific_order_with_out_of_range_path_ids(db: PgPool) {
 }
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_remove_order_with_out_of_range_path_table_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -527,6 +550,7 @@ async fn test_remove_order_with_out_of_range_path_table_id(db: PgPool) {
 }
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_remove_order_with_out_of_range_path_order_id(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -558,6 +582,7 @@ async fn test_remove_order_with_out_of_range_path_order_id(db: PgPool) {
 }
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test]
 async fn test_remove_order_with_out_of_range_path_ids(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -590,6 +615,7 @@ async fn test_remove_order_with_out_of_range_path_ids(db: PgPool) {
 
 
 
+#[allow(clippy::useless_format)]
 #[sqlx::test(fixtures("orders"))]
 async fn test_remove_non_existence_order(db: PgPool) {    
     let context: ApiContext = ApiContext::new(db);
@@ -597,7 +623,7 @@ async fn test_remove_non_existence_order(db: PgPool) {
     let response = app(context.clone())
                         .oneshot(
                             Request::builder()
-                            .uri(format!("/api/v1/tables/1/orders/1"))
+                            .uri(format!("/api/v1/tables/1/orders/1?reason=customer_changed_mind"))
                                 .method(http::Method::DELETE)
                                 .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
                                 .body(Body::empty())
@@ -622,3 +648,2077 @@ async fn test_remove_non_existence_order(db: PgPool) {
     
 }
 
+
+
+
+#[tokio::test]
+async fn test_add_get_and_remove_orders_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Pizza", "main", 25000, 20)).await.unwrap();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Sushi", "main", 30000, 15)).await.unwrap();
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/44/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "table_id": 44,
+                        "orders": [
+                          {
+                              "table_id": 44,
+                              "item_name": "Pizza",
+                              "note": "Some note"
+                          },
+                          {
+                              "table_id": 44,
+                              "item_name": "Sushi"
+                          }
+                        ]
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(orders.orders.len(), 2);
+    assert_eq!(orders.orders[0].order_id, 1);
+    assert_eq!(orders.orders[1].order_id, 2);
+
+    // remove first order
+    let response = app(context.clone())
+                        .oneshot(
+                            Request::builder()
+                            .uri("/api/v1/tables/44/orders/1?reason=customer_changed_mind")
+                                .method(http::Method::DELETE)
+                                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                                .body(Body::empty())
+                                .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(orders.orders.len(), 1);
+    assert_eq!(orders.orders[0].item_name, "Sushi");
+
+    // removed order is not found anymore
+    let response = app(context.clone())
+                        .oneshot(
+                            Request::builder()
+                            .uri("/api/v1/tables/44/orders/1")
+                                .method(http::Method::GET)
+                                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                                .body(Body::empty())
+                                .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_health_check_in_memory() {
+    let context = ApiContext::new_in_memory();
+
+    let response = app(context)
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/health")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_update_order_status(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let table_order = get_table_orders_for_test(&context, 11).await;
+    let order_id = table_order.orders[0].order_id;
+    assert_eq!(table_order.orders[0].status, OrderStatus::Ordered);
+
+    // ordered -> cooking
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/11/orders/{order_id}"))
+            .method(http::Method::PATCH)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"status": "cooking"})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let table_order: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(table_order.orders.len(), 2);
+    assert_eq!(table_order.orders[0].status, OrderStatus::Cooking);
+    assert_eq!(table_order.orders[1].status, OrderStatus::Ordered);
+
+    // cooking -> served is not allowed
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/11/orders/{order_id}"))
+            .method(http::Method::PATCH)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"status": "served"})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let check_json_value: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(check_json_value, json!{
+        {
+            "status_code": 409,
+            "error_cause": "Conflict -> order status cannot be changed from cooking to served"
+        }
+    });
+
+    // unknown status
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/11/orders/{order_id}"))
+            .method(http::Method::PATCH)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"status": "eaten"})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // unknown order
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/12/orders/1")
+            .method(http::Method::PATCH)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"status": "cooking"})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_get_orders_filtered_by_status(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let table_order = get_table_orders_for_test(&context, 11).await;
+    context.dbo.update_order_status(11, table_order.orders[1].order_id, OrderStatus::Cooking, &[]).await.unwrap();
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11/orders?status=cooking")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let filtered_order: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(filtered_order.orders.len(), 1);
+    assert_eq!(filtered_order.orders[0].item_name, "Ramen");
+    assert_eq!(filtered_order.orders[0].status, OrderStatus::Cooking);
+
+    // invalid status in query
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11/orders?status=unknown")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_add_orders_with_unavailable_menu_items(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "table_id": 11,
+                        "orders": [
+                          {
+                              "table_id": 11,
+                              "item_name": "Pizza"
+                          },
+                          {
+                              "table_id": 11,
+                              "item_name": "Seasonal soup"
+                          },
+                          {
+                              "table_id": 11,
+                              "item_name": "Burger"
+                          }
+                        ]
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    let error_cause = error["error_cause"].as_str().unwrap();
+    assert!(error_cause.contains("Seasonal soup (inactive)"));
+    assert!(error_cause.contains("Burger (unknown)"));
+    assert!(!error_cause.contains("Pizza"));
+
+    // no order is added when any item is rejected
+    assert_eq!(get_table_orders_for_test(&context, 11).await.orders.len(), 2);
+}
+
+
+#[sqlx::test(fixtures("menu"))]
+async fn test_menu_crud(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/menu")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let menu: MenuResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(menu.menu_items.len(), 5);
+    assert_eq!(menu.menu_items[0].name, "Kapao");
+    assert_eq!(menu.menu_items[0].price, 12000);
+    assert!(!menu.menu_items[4].active);
+
+    // add new item
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/menu")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "name": "Thai tea",
+                        "category": "drink",
+                        "price": 6000,
+                        "prep_time_minutes": 3
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let menu: MenuResponse = serde_json::from_slice(&body).unwrap();
+    let menu_item_id = menu.menu_items[0].menu_item_id;
+    assert_eq!(menu.menu_items[0].name, "Thai tea");
+    assert!(menu.menu_items[0].active);
+
+    // duplicate name is rejected
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/menu")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "name": "Thai tea",
+                        "category": "drink",
+                        "price": 7000,
+                        "prep_time_minutes": 3
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    // update (deactivate) item
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/menu/{menu_item_id}"))
+            .method(http::Method::PUT)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "name": "Thai tea",
+                        "category": "drink",
+                        "price": 6500,
+                        "prep_time_minutes": 3,
+                        "active": false
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/menu/{menu_item_id}"))
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let menu: MenuResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(menu.menu_items[0].price, 6500);
+    assert!(!menu.menu_items[0].active);
+
+    // delete item
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/menu/{menu_item_id}"))
+            .method(http::Method::DELETE)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let menu: MenuResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(menu.menu_items.len(), 5);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/menu/{menu_item_id}"))
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_add_menu_item_with_negative_price_in_memory() {
+    let context = ApiContext::new_in_memory();
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/menu")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "name": "Thai tea",
+                        "category": "drink",
+                        "price": -1,
+                        "prep_time_minutes": 3
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(context.dbo.get_menu_items().await.unwrap().is_empty());
+}
+
+
+#[tokio::test]
+async fn test_add_and_delete_ordered_menu_item_in_memory() {
+    let context = ApiContext::new_in_memory();
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/menu")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "name": " Thai tea ",
+                        "category": "drink",
+                        "price": 7000,
+                        "prep_time_minutes": 3
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // name is stored trimmed, so orders can refer to it
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let menu: MenuResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(menu.menu_items[0].name, "Thai tea");
+    let menu_item_id = menu.menu_items[0].menu_item_id;
+
+    let mut table_orders = TableOrdersRequest::new(5);
+    table_orders.add_order_wihtout_note("Thai tea");
+    context.dbo.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+    // ordered item can't be deleted (it can be deactivated instead)
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/menu/{menu_item_id}"))
+            .method(http::Method::DELETE)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(context.dbo.get_menu_items().await.unwrap().len(), 1);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_add_orders_with_quantity_and_decrement(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/44/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "table_id": 44,
+                        "orders": [
+                          {
+                              "table_id": 44,
+                              "item_name": "Sushi",
+                              "quantity": 3
+                          }
+                        ]
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(orders.orders.len(), 1);
+    assert_eq!(orders.orders[0].quantity, 3);
+    let order_id = orders.orders[0].order_id;
+
+    // decrement by 2
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/44/orders/{order_id}/decrement"))
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"quantity": 2})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(orders.orders[0].quantity, 1);
+
+    // removing the order by decrement requires a void reason
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/44/orders/{order_id}/decrement"))
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // decrement by default quantity (1) removes the order
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/44/orders/{order_id}/decrement"))
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"reason": "customer_changed_mind"})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+    assert!(orders.orders.is_empty());
+
+    // quantity out of range is rejected
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/44/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "table_id": 44,
+                        "orders": [
+                          {
+                              "table_id": 44,
+                              "item_name": "Sushi",
+                              "quantity": 0
+                          }
+                        ]
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_checkout_table(db: PgPool) {
+    let mut config = AppConfig::new_from_env().unwrap();
+    config.service_charge_bps = Some(1000);
+    config.tax_rate_bps = Some(700);
+    let context = ApiContext::with_dao(config, PgTableOrderDAO::new(db));
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11/checkout")
+            .method(http::Method::POST)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let bill: BillResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(bill.bill.table_id, 11);
+    assert_eq!(bill.bill.line_items.len(), 2);
+    assert_eq!(bill.bill.subtotal, 27000);
+    assert_eq!(bill.bill.total, 31779);
+
+    // table is cleared
+    assert!(get_table_orders_for_test(&context, 11).await.orders.is_empty());
+
+    // bill can be read later
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/bills/{}", bill.bill.bill_id))
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let stored_bill: BillResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stored_bill.bill.line_items, bill.bill.line_items);
+
+    // nothing left to checkout
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11/checkout")
+            .method(http::Method::POST)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+
+#[tokio::test]
+async fn test_checkout_out_of_range_table_and_unknown_bill_in_memory() {
+    let context = ApiContext::new_in_memory();
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/1000/checkout")
+            .method(http::Method::POST)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/bills/1")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_split_bill_in_memory() {
+    let mut config = AppConfig::new_from_env().unwrap();
+    config.service_charge_bps = Some(1000);
+    config.tax_rate_bps = Some(700);
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Pizza", "main", 25001, 20)).await.unwrap();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Sushi", "main", 30000, 15)).await.unwrap();
+
+    let mut table_orders = TableOrdersRequest::new(5);
+    table_orders.add_order_wihtout_note("Pizza");
+    table_orders.add_order_wihtout_note("Sushi");
+    context.dbo.add_table_orders(&process_order_requests(table_orders), &[]).await.unwrap();
+
+    for payload in [json!({"mode": "even", "guests": 3}), json!({"mode": "items", "groups": [[2], [1]]})]{
+        let response = app(context.clone())
+        .oneshot(
+            Request::builder()
+            .uri("/api/v1/tables/5/split")
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let split: SplitBillResponse = serde_json::from_slice(&body).unwrap();
+
+        // subtotal 55001, service charge 5500, tax 4235
+        assert_eq!(split.total, 64736);
+        assert_eq!(split.sub_bills.iter().map(|sub_bill| sub_bill.total).sum::<i32>(), split.total);
+    }
+
+    // orders are kept after split
+    assert_eq!(get_table_orders_for_test(&context, 5).await.orders.len(), 2);
+
+    // order 1 is not assigned
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/5/split")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&json!({"mode": "items", "groups": [[2]]})).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_add_orders_with_kitchen_load_estimation(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db).with_estimator(Arc::new(KitchenLoadEstimation::new(10, 5, 2)));
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/44/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_vec(&json!(
+                    {
+                        "table_id": 44,
+                        "orders": [
+                          {
+                              "table_id": 44,
+                              "item_name": "Pizza"
+                          },
+                          {
+                              "table_id": 44,
+                              "item_name": "Sushi"
+                          }
+                        ]
+                    }
+                )).unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let orders: TableOrdersResponse = serde_json::from_slice(&body).unwrap();
+
+    // 2 orders are already in the queue (fixture), kitchen cooks 2 orders at a time and each takes 5 minutes
+    let minutes: Vec<i64> = orders.orders.iter().map(|order| (order.estimated_arrival_time - order.creation_time).num_minutes()).collect();
+    assert_eq!(minutes, vec![20 + 5, 15 + 5]);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_get_kitchen_queue(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/kitchen/queue?category=main&limit=1")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let queue: KitchenQueueResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(queue.orders.len(), 1);
+    assert_eq!(queue.orders[0].item_name, "Kapao");
+
+    for uri in ["/api/v1/kitchen/queue?limit=0", "/api/v1/kitchen/queue?limit=abc"]{
+        let response = app(context.clone())
+        .oneshot(
+            Request::builder()
+            .uri(uri)
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+
+#[sqlx::test(fixtures("orders", "menu"))]
+async fn test_table_registry(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+
+    // register new table, and add orders to it
+    let (status, body) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables",
+        json!({"label": "Patio 3", "capacity": 2, "section": "patio"})).await;
+    assert_eq!(status, StatusCode::OK);
+    let tables: TablesResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(tables.tables[0].table_id, 101);
+    assert!(tables.tables[0].active);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/101/orders",
+        json!({"table_id": 101, "orders": [{"table_id": 101, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // invalid table
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables",
+        json!({"label": " ", "capacity": 2, "section": "patio"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // table with orders can't be deactivated or removed
+    let (status, _) = send_json_request_for_test(&context, http::Method::PUT, "/api/v1/tables/101",
+        json!({"label": "Patio 3", "capacity": 2, "section": "patio", "active": false})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/11")
+            .method(http::Method::DELETE)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    // deactivated table rejects orders
+    let (status, body) = send_json_request_for_test(&context, http::Method::PUT, "/api/v1/tables/12",
+        json!({"label": "Window 12", "capacity": 6, "section": "main", "active": false})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tables"][0]["label"], "Window 12");
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/12/orders",
+        json!({"table_id": 12, "orders": [{"table_id": 12, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // removed table is not found
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/12")
+            .method(http::Method::DELETE)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let tables: TablesResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(tables.tables.len(), 100);
+    assert!(tables.tables.iter().all(|table| table.table_id != 12));
+
+    for uri in ["/api/v1/tables/12", "/api/v1/tables/12/orders"]{
+        let response = app(context.clone())
+        .oneshot(
+            Request::builder()
+            .uri(uri)
+                .method(http::Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+
+#[tokio::test]
+async fn test_order_event_streams_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    // subscribe to table 1 before any change
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/1/orders/stream")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], mime::TEXT_EVENT_STREAM.as_ref());
+    let mut table_stream = response.into_body();
+
+    // event 1 (table 2), event 2 (table 1) and event 3 (status of table 1's order)
+    for table_id in [2, 1]{
+        let mut table_orders = TableOrdersRequest::new(table_id);
+        table_orders.add_order_wihtout_note("Kapao");
+
+        let response = app(context.clone())
+        .oneshot(
+            Request::builder()
+            .uri(format!("/api/v1/tables/{table_id}/orders"))
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&table_orders).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let order_id = get_table_orders_for_test(&context, 1).await.orders[0].order_id;
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/1/orders/{order_id}"))
+            .method(http::Method::PATCH)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json!({"status": "cooking"}).to_string()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // table stream only receives events of table 1
+    let (event_type, event) = next_sse_event_for_test(&mut table_stream).await;
+    assert_eq!(event_type, "orders_added");
+    assert_eq!(event["epoch"], context.events.epoch());
+    assert_eq!(event["event_id"], 2);
+    assert_eq!(event["table_id"], 1);
+    assert_eq!(event["orders"][0]["item_name"], "Kapao");
+
+    let (event_type, event) = next_sse_event_for_test(&mut table_stream).await;
+    assert_eq!(event_type, "order_status_changed");
+    assert_eq!(event["event_id"], 3);
+    assert_eq!(event["order_id"], order_id);
+    assert_eq!(event["orders"][0]["status"], "cooking");
+
+    // reconnecting kitchen client resumes after the last received event
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/kitchen/stream")
+            .method(http::Method::GET)
+            .header("last-event-id", format!("{}:1", context.events.epoch()))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut kitchen_stream = response.into_body();
+    let (_, event) = next_sse_event_for_test(&mut kitchen_stream).await;
+    assert_eq!(event["event_id"], 2);
+    let (_, event) = next_sse_event_for_test(&mut kitchen_stream).await;
+    assert_eq!(event["event_id"], 3);
+
+    // id of another epoch (e.g., before server restart) gets resync instead of unrelated events
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/kitchen/stream")
+            .method(http::Method::GET)
+            .header("last-event-id", "0123456789abcdef:1")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let (event_type, _) = next_sse_event_for_test(&mut response.into_body()).await;
+    assert_eq!(event_type, "resync");
+}
+
+
+#[tokio::test]
+async fn test_order_stream_with_out_of_range_table_in_memory() {
+    let context = ApiContext::new_in_memory();
+
+    let response = app(context)
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/1000/orders/stream")
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[sqlx::test(fixtures("menu"))]
+async fn test_order_events_relayed_across_instances(db: PgPool) {
+    // two server instances sharing the same database
+    let context_a: ApiContext = ApiContext::new(db.clone());
+    let context_b: ApiContext = ApiContext::new(db);
+    spawn_order_event_relay(context_a.clone()).await.unwrap();
+    spawn_order_event_relay(context_b.clone()).await.unwrap();
+
+    let mut subscription_a = context_a.events.subscribe(None);
+    let mut subscription_b = context_b.events.subscribe(None);
+
+    let mut table_orders = TableOrdersRequest::new(5);
+    table_orders.add_order_wihtout_note("Kapao");
+    table_orders.add_order_wihtout_note("Ramen");
+
+    let response = app(context_a.clone())
+    .oneshot(
+        Request::builder()
+        .uri("/api/v1/tables/5/orders")
+            .method(http::Method::POST)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&table_orders).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // instance b receives the change through postgres notification
+    let event = tokio::time::timeout(std::time::Duration::from_secs(5), subscription_b.receiver.recv()).await.unwrap().unwrap();
+    assert_eq!(event.event_type, OrderEventType::OrdersAdded);
+    assert_eq!(event.table_id, 5);
+    assert_eq!(event.orders.len(), 2);
+
+    // instance a publishes its own change only once
+    let event_a = tokio::time::timeout(std::time::Duration::from_secs(5), subscription_a.receiver.recv()).await.unwrap().unwrap();
+    assert_eq!(event_a.event_type, OrderEventType::OrdersAdded);
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(subscription_a.receiver.try_recv().is_err());
+
+    // event ids of an instance are meaningless to another one, so client reconnecting to another instance gets resync
+    assert_ne!(event_a.epoch, event.epoch);
+    assert!(context_a.events.subscribe(Some(LastEventId::from(&event_a))).complete);
+    let subscription = context_b.events.subscribe(Some(LastEventId::from(&event_a)));
+    assert!(!subscription.complete);
+    assert!(subscription.missed.is_empty());
+}
+
+
+#[tokio::test]
+async fn test_device_websocket_in_memory() {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let router = app(context.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{address}/api/v1/devices/ws?device_id=kitchen-1")).await.unwrap();
+    socket.send(Message::Text(json!({"type": "subscribe", "table_ids": [1]}).to_string())).await.unwrap();
+    let message = next_ws_message_for_test(&mut socket).await;
+    assert_eq!(message, json!({"type": "subscribed", "table_ids": [1]}));
+
+    // orders of other tables are not pushed
+    for table_id in [2, 1]{
+        let mut table_orders = TableOrdersRequest::new(table_id);
+        table_orders.add_order_wihtout_note("Kapao");
+
+        let response = app(context.clone())
+        .oneshot(
+            Request::builder()
+            .uri(format!("/api/v1/tables/{table_id}/orders"))
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&table_orders).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let message = next_ws_message_for_test(&mut socket).await;
+    assert_eq!(message["type"], "event");
+    assert_eq!(message["event_type"], "orders_added");
+    assert_eq!(message["table_id"], 1);
+    let order_id = message["orders"][0]["order_id"].as_i64().unwrap();
+
+    // acknowledgement is stored on the order and pushed to subscribed devices
+    socket.send(Message::Text(json!({"type": "acknowledge", "table_id": 1, "order_id": order_id}).to_string())).await.unwrap();
+    let message = next_ws_message_for_test(&mut socket).await;
+    assert_eq!(message, json!({"type": "acknowledged", "table_id": 1, "order_id": order_id}));
+
+    let message = next_ws_message_for_test(&mut socket).await;
+    assert_eq!(message["event_type"], "order_acknowledged");
+    assert_eq!(message["orders"][0]["acknowledged_by"], "kitchen-1");
+
+    let orders = get_table_orders_for_test(&context, 1).await;
+    assert_eq!(orders.orders[0].acknowledged_by, Some("kitchen-1".to_string()));
+
+    // resubscribing device gets the events after its last received event, or resync if the epoch doesn't match
+    let epoch = context.events.epoch();
+    socket.send(Message::Text(json!({"type": "subscribe", "table_ids": [1], "last_event_id": 2, "epoch": epoch}).to_string())).await.unwrap();
+    assert_eq!(next_ws_message_for_test(&mut socket).await["type"], "subscribed");
+    let message = next_ws_message_for_test(&mut socket).await;
+    assert_eq!(message["event_type"], "order_acknowledged");
+    assert_eq!(message["event_id"], 3);
+
+    socket.send(Message::Text(json!({"type": "subscribe", "table_ids": [1], "last_event_id": 2}).to_string())).await.unwrap();
+    assert_eq!(next_ws_message_for_test(&mut socket).await["type"], "subscribed");
+    assert_eq!(next_ws_message_for_test(&mut socket).await, json!({"type": "resync"}));
+
+    // invalid requests are answered with error messages
+    socket.send(Message::Text(json!({"type": "acknowledge", "table_id": 1, "order_id": 1000}).to_string())).await.unwrap();
+    assert_eq!(next_ws_message_for_test(&mut socket).await["status_code"], 404);
+
+    socket.send(Message::Text("hello".to_string())).await.unwrap();
+    assert_eq!(next_ws_message_for_test(&mut socket).await["status_code"], 400);
+}
+
+
+#[tokio::test]
+async fn test_seating_sessions_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+    let mut subscription = context.events.subscribe(None);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions", json!({"guest_count": 0})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions", json!({"guest_count": 2})).await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions: TableSessionsResponse = serde_json::from_value(body).unwrap();
+    let first = sessions.sessions[0].clone();
+    assert_eq!(first.guest_count, Some(2));
+    let event = subscription.receiver.recv().await.unwrap();
+    assert_eq!((event.event_type, event.table_id), (OrderEventType::SessionOpened, 1));
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions", json!({"guest_count": 3})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/orders",
+        json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders[0].session_id, Some(first.session_id));
+    assert_eq!(subscription.receiver.recv().await.unwrap().event_type, OrderEventType::OrdersAdded);
+
+    // the session can't be closed while it has unbilled orders
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions/current/close", Value::Null).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders.len(), 1);
+
+    // the next party starts with an empty table, orders of the previous party are kept in its session
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/checkout", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(get_table_orders_for_test(&context, 1).await.orders.is_empty());
+    assert_eq!(subscription.receiver.recv().await.unwrap().event_type, OrderEventType::TableCheckedOut);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1/sessions/current", Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // a party leaving without orders closes its session
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions", json!({"guest_count": 3})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(subscription.receiver.recv().await.unwrap().event_type, OrderEventType::SessionOpened);
+    let (status, body) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/sessions/current/close", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["sessions"][0]["close_time"].is_string());
+    let event = subscription.receiver.recv().await.unwrap();
+    assert_eq!((event.event_type, event.table_id), (OrderEventType::SessionClosed, 1));
+    assert!(event.orders.is_empty());
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::GET,
+        &format!("/api/v1/tables/1/orders?session_id={}", first.session_id), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"].as_array().unwrap().len(), 1);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::GET,
+        &format!("/api/v1/tables/2/orders?session_id={}", first.session_id), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1/sessions", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["sessions"].as_array().unwrap().len(), 2);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1000/sessions", Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_session_orders_after_checkout_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/orders",
+        json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}, {"table_id": 1, "item_name": "Kapao", "quantity": 2}]})).await;
+    assert_eq!(status, StatusCode::OK);
+    let orders = get_table_orders_for_test(&context, 1).await.orders;
+    let session_id = orders[0].session_id.unwrap();
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::DELETE,
+        &format!("/api/v1/tables/1/orders/{}?reason=customer_changed_mind", orders[0].order_id), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/checkout", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(get_table_orders_for_test(&context, 1).await.orders.is_empty());
+
+    // billed orders of the checked out party are still in its session (removed orders are not)
+    let (status, body) = send_json_request_for_test(&context, http::Method::GET,
+        &format!("/api/v1/tables/1/orders?session_id={session_id}"), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"].as_array().unwrap().len(), 1);
+    assert_eq!(body["orders"][0]["order_id"], orders[1].order_id);
+    assert_eq!(body["orders"][0]["quantity"], 2);
+}
+
+
+#[tokio::test]
+async fn test_transfer_and_merge_tables_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+    let mut subscription = context.events.subscribe(None);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/orders",
+        json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}, {"table_id": 1, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::OK);
+    subscription.receiver.recv().await.unwrap();
+
+    // invalid transfers
+    for (payload, expected) in [
+        (json!({"target_table_id": 1, "order_ids": [1]}), StatusCode::BAD_REQUEST),
+        (json!({"target_table_id": 2, "order_ids": []}), StatusCode::BAD_REQUEST),
+        (json!({"target_table_id": 1000, "order_ids": [1]}), StatusCode::NOT_FOUND),
+        (json!({"target_table_id": 2, "order_ids": [1, 3]}), StatusCode::NOT_FOUND)]{
+
+        let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/transfer", payload).await;
+        assert_eq!(status, expected);
+    }
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/transfer",
+        json!({"target_table_id": 2, "order_ids": [1]})).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: TableTransferResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(response.source.table_id, 1);
+    assert_eq!(response.source.orders.len(), 1);
+    assert_eq!(response.target.table_id, 2);
+    assert_eq!(response.target.orders[0].order_id, 1);
+
+    // both tables are notified
+    for table_id in [1, 2]{
+        let event = subscription.receiver.recv().await.unwrap();
+        assert_eq!(event.event_type, OrderEventType::OrdersTransferred);
+        assert_eq!(event.table_id, table_id);
+    }
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/2/merge",
+        json!({"target_table_id": 1})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["source"]["orders"].as_array().unwrap().is_empty());
+    assert_eq!(body["target"]["orders"].as_array().unwrap().len(), 2);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/2/merge",
+        json!({"target_table_id": 1})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+
+#[sqlx::test]
+async fn test_add_orders_with_idempotency_key(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let add_orders = |table_id: i16, idempotency_key: &'static str, payload: Value| {
+        let context = context.clone();
+        async move {
+            app(context)
+            .oneshot(
+                Request::builder()
+                .uri(format!("/api/v1/tables/{table_id}/orders"))
+                    .method(http::Method::POST)
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header("Idempotency-Key", idempotency_key)
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+    let payload = json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}]});
+
+    let response = add_orders(1, "order-1", payload.clone()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("idempotent-replayed").is_none());
+    let first_etag = response.headers()[http::header::ETAG].clone();
+    let first_body = response.into_body().collect().await.unwrap().to_bytes();
+
+    // retry gets the same response (and ETag), and the order is added only once
+    let response = add_orders(1, "order-1", payload.clone()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["idempotent-replayed"], "true");
+    assert_eq!(response.headers()[http::header::ETAG], first_etag);
+    assert_eq!(response.into_body().collect().await.unwrap().to_bytes(), first_body);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders.len(), 1);
+
+    // reused key with different payload (or table) is rejected
+    let response = add_orders(1, "order-1", json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao", "quantity": 2}]})).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = add_orders(2, "order-1", json!({"table_id": 2, "orders": [{"table_id": 2, "item_name": "Kapao"}]})).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // another key adds orders again
+    let response = add_orders(1, "order-2", payload.clone()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders.len(), 2);
+
+    let response = add_orders(1, " ", payload).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_table_orders_etag(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let send_request = |method: http::Method, uri: String, header: (http::HeaderName, String), payload: Option<Value>| {
+        let context = context.clone();
+        async move {
+            let body = payload.map(|payload| Body::from(payload.to_string())).unwrap_or_else(Body::empty);
+            app(context)
+            .oneshot(
+                Request::builder()
+                .uri(uri)
+                    .method(method)
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(header.0, header.1)
+                    .body(body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    let orders = get_table_orders_for_test(&context, 11).await;
+    let order_id = orders.orders[0].order_id;
+    let order_uri = format!("/api/v1/tables/11/orders/{order_id}");
+    let delete_uri = format!("{order_uri}?reason=kitchen_error");
+    let orders_uri = "/api/v1/tables/11/orders".to_string();
+
+    // unchanged orders are not sent again
+    let response = send_request(http::Method::GET, orders_uri.clone(), (http::header::IF_NONE_MATCH, "\"0-0\"".to_string()), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[http::header::ETAG].to_str().unwrap().to_string();
+
+    let response = send_request(http::Method::GET, orders_uri.clone(), (http::header::IF_NONE_MATCH, etag.clone()), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[http::header::ETAG], etag.as_str());
+
+    // change with current ETag succeeds and returns the new ETag
+    let response = send_request(http::Method::PATCH, order_uri.clone(), (http::header::IF_MATCH, etag.clone()), Some(json!({"status": "cooking"}))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_etag = response.headers()[http::header::ETAG].to_str().unwrap().to_string();
+    assert_ne!(new_etag, etag);
+
+    // other client with the old ETag is rejected
+    let response = send_request(http::Method::DELETE, delete_uri.clone(), (http::header::IF_MATCH, etag.clone()), None).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(get_table_orders_for_test(&context, 11).await.orders.len(), orders.orders.len());
+
+    let response = send_request(http::Method::POST, orders_uri.clone(), (http::header::IF_MATCH, etag),
+        Some(json!({"table_id": 11, "orders": [{"table_id": 11, "item_name": "Kapao"}]}))).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+    let response = send_request(http::Method::GET, orders_uri.clone(), (http::header::IF_NONE_MATCH, new_etag.clone()), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = send_request(http::Method::DELETE, delete_uri, (http::header::IF_MATCH, new_etag), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[http::header::ETAG].to_str().unwrap().to_string();
+    assert_eq!(get_table_orders_for_test(&context, 11).await.orders.len(), orders.orders.len() - 1);
+
+    // transfer compares ETags of both tables, and returns their new ETags
+    let response = send_request(http::Method::GET, "/api/v1/tables/12/orders".to_string(), (http::header::IF_NONE_MATCH, "\"0-0\"".to_string()), None).await;
+    let target_etag = response.headers()[http::header::ETAG].to_str().unwrap().to_string();
+    let transfer = json!({"target_table_id": 12, "order_ids": [orders.orders[1].order_id]});
+
+    let response = send_request(http::Method::POST, "/api/v1/tables/11/transfer".to_string(),
+        (http::header::IF_MATCH, format!("{etag}, \"12-1000\"")), Some(transfer.clone())).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(get_table_orders_for_test(&context, 11).await.orders.len(), orders.orders.len() - 1);
+
+    let response = send_request(http::Method::POST, "/api/v1/tables/11/transfer".to_string(),
+        (http::header::IF_MATCH, format!("{etag}, {target_etag}")), Some(transfer)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_etag = response.headers()[http::header::ETAG].to_str().unwrap().to_string();
+    let body: TableTransferResponse = serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body.source_etag, new_etag);
+    assert_ne!(body.source_etag, etag);
+    assert_ne!(body.target_etag, target_etag);
+
+    let response = send_request(http::Method::GET, "/api/v1/tables/12/orders".to_string(), (http::header::IF_NONE_MATCH, body.target_etag), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
+
+
+#[tokio::test]
+async fn test_order_history_in_memory() {
+    let context = ApiContext::new_in_memory();
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/orders",
+        json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}, {"table_id": 1, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::OK);
+    let order_id = get_table_orders_for_test(&context, 1).await.orders[0].order_id;
+
+    let long_name = "a".repeat(65);
+    let (status, _) = send_json_request_for_test(&context, http::Method::DELETE,
+        &format!("/api/v1/tables/1/orders/{order_id}?reason=comp&deleted_by={long_name}"), Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send_json_request_for_test(&context, http::Method::DELETE,
+        &format!("/api/v1/tables/1/orders/{order_id}?reason=customer_changed_mind&deleted_by=waiter-1"), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"].as_array().unwrap().len(), 1);
+
+    // removed order is kept in history (with who removed it and why), but not in table's orders
+    let (status, body) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1/orders/history", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let history: TableOrdersResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(history.orders.len(), 2);
+    assert_eq!(history.orders[0].order_id, order_id);
+    assert_eq!(history.orders[0].deleted_by, Some("waiter-1".to_string()));
+    assert_eq!(history.orders[0].reason, Some("customer_changed_mind".to_string()));
+    assert!(history.orders[0].deleted_at.is_some());
+    assert!(history.orders[1].deleted_at.is_none());
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::GET, &format!("/api/v1/tables/1/orders/{order_id}"), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1000/orders/history", Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_void_order_with_manager_approval_in_memory() {
+    let config = AppConfig{ manager_approval_token: Some("secret".to_string()), ..AppConfig::default() };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let (status, _) = send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/1/orders",
+        json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}]})).await;
+    assert_eq!(status, StatusCode::OK);
+    let order_id = get_table_orders_for_test(&context, 1).await.orders[0].order_id;
+    let order_uri = format!("/api/v1/tables/1/orders/{order_id}");
+
+    // cancelling by status change would skip reason code and approval
+    let (status, _) = send_json_request_for_test(&context, http::Method::PATCH, &order_uri, json!({"status": "cancelled"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders[0].status, OrderStatus::Ordered);
+
+    for status in ["cooking", "ready", "served"]{
+        let (status, _) = send_json_request_for_test(&context, http::Method::PATCH, &order_uri, json!({"status": status})).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let void_order = |reason: &'static str, approval: Option<&'static str>| {
+        let context = context.clone();
+        let mut request = Request::builder()
+            .uri(format!("/api/v1/tables/1/orders/{order_id}?reason={reason}"))
+            .method(http::Method::DELETE);
+        if let Some(approval) = approval{
+            request = request.header(restaurant_server::handlers::MANAGER_APPROVAL_HEADER, approval);
+        }
+        async move {
+            app(context).oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    // reason code is required
+    let (status, _) = send_json_request_for_test(&context, http::Method::DELETE, &order_uri, Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(void_order("other", None).await.status(), StatusCode::BAD_REQUEST);
+
+    // served order can only be voided with manager approval
+    let response = void_order("comp", None).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let check_json_value: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(check_json_value["status_code"], 403);
+
+    assert_eq!(void_order("comp", Some("wrong")).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders.len(), 1);
+
+    assert_eq!(void_order("comp", Some("secret")).await.status(), StatusCode::OK);
+    assert!(get_table_orders_for_test(&context, 1).await.orders.is_empty());
+
+    let (_, body) = send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/1/orders/history", Value::Null).await;
+    assert_eq!(body["orders"][0]["reason"], "comp");
+}
+
+
+#[tokio::test]
+async fn test_api_key_authentication_in_memory() {
+    let config = AppConfig{ admin_api_key: Some("admin-secret".to_string()), ..AppConfig::default() };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+
+    let send_request = |method: http::Method, uri: &'static str, api_key: Option<String>, payload: Value| {
+        let context = context.clone();
+        let mut request = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref());
+        if let Some(api_key) = api_key{
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        async move {
+            let response = app(context).oneshot(request.body(Body::from(payload.to_string())).unwrap()).await.unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (status, serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null))
+        }
+    };
+
+    // health check is open, but other routes require API key
+    let (status, _) = send_request(http::Method::GET, "/api/v1/health", None, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send_request(http::Method::GET, "/api/v1/tables", None, Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["status_code"], 401);
+    let (status, _) = send_request(http::Method::GET, "/api/v1/tables", Some("wrong".to_string()), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_request(http::Method::GET, "/api/v1/tables", Some("admin-secret".to_string()), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // only admin key can issue keys
+    let (status, _) = send_request(http::Method::POST, "/api/v1/admin/api_keys", None, json!({"name": "floor-tablet-1", "role": "waiter"})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_request(http::Method::POST, "/api/v1/admin/api_keys", Some("admin-secret".to_string()), json!({"name": " ", "role": "waiter"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = send_request(http::Method::POST, "/api/v1/admin/api_keys", Some("admin-secret".to_string()), json!({"name": "floor-tablet-1", "role": "waiter"})).await;
+    assert_eq!(status, StatusCode::OK);
+    let api_key = body["api_key"].as_str().unwrap().to_string();
+    let api_key_id = body["api_keys"][0]["api_key_id"].as_i64().unwrap();
+    assert_eq!(body["api_keys"][0]["name"], "floor-tablet-1");
+    assert_eq!(body["api_keys"][0]["role"], "waiter");
+    assert!(body["api_keys"][0].get("key_hash").is_none());
+
+    // issued waiter key works for other routes, but not for admin routes
+    let (status, _) = send_request(http::Method::GET, "/api/v1/tables", Some(api_key.clone()), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_request(http::Method::GET, "/api/v1/admin/api_keys", Some(api_key.clone()), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = send_request(http::Method::GET, "/api/v1/admin/api_keys", Some("admin-secret".to_string()), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["api_keys"].as_array().unwrap().len(), 1);
+    assert!(body.get("api_key").is_none());
+
+    // revoked key is rejected
+    assert_eq!(api_key_id, 1);
+    let (status, body) = send_request(http::Method::DELETE, "/api/v1/admin/api_keys/1", Some("admin-secret".to_string()), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["api_keys"][0]["revocation_time"].is_string());
+    let (status, _) = send_request(http::Method::GET, "/api/v1/tables", Some(api_key), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_request(http::Method::DELETE, "/api/v1/admin/api_keys/1000", Some("admin-secret".to_string()), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_role_permissions_in_memory() {
+    let config = AppConfig{ admin_api_key: Some("admin-secret".to_string()), ..AppConfig::default() };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    let roles = [Role::Waiter, Role::Kitchen, Role::Cashier, Role::Manager, Role::Admin];
+    for role in roles{
+        context.dbo.add_api_key(role.as_str(), role, &hash_api_key(&format!("{role}-key")), chrono::Utc::now()).await.unwrap();
+    }
+
+    let staff = &[Role::Waiter, Role::Kitchen, Role::Cashier, Role::Manager, Role::Admin][..];
+    let waiter = &[Role::Waiter, Role::Manager, Role::Admin][..];
+    let kitchen = &[Role::Kitchen, Role::Waiter, Role::Manager, Role::Admin][..];
+    let cashier = &[Role::Cashier, Role::Manager, Role::Admin][..];
+    let manager = &[Role::Manager, Role::Admin][..];
+    let admin = &[Role::Admin][..];
+
+    // every route (except health check) with roles allowed to call it. Ids don't exist, so allowed requests don't change anything
+    let routes = [
+        (http::Method::GET, "/api/v1/tables", staff),
+        (http::Method::POST, "/api/v1/tables", manager),
+        (http::Method::GET, "/api/v1/tables/0", staff),
+        (http::Method::PUT, "/api/v1/tables/0", manager),
+        (http::Method::DELETE, "/api/v1/tables/0", manager),
+        (http::Method::POST, "/api/v1/tables/0/orders", waiter),
+        (http::Method::GET, "/api/v1/tables/0/orders", staff),
+        (http::Method::GET, "/api/v1/tables/0/orders/stream", staff),
+        (http::Method::GET, "/api/v1/tables/0/orders/history", staff),
+        (http::Method::GET, "/api/v1/tables/0/orders/1", staff),
+        (http::Method::DELETE, "/api/v1/tables/0/orders/1?reason=comp", waiter),
+        (http::Method::PATCH, "/api/v1/tables/0/orders/1", kitchen),
+        (http::Method::POST, "/api/v1/tables/0/orders/1/decrement", waiter),
+        (http::Method::POST, "/api/v1/tables/0/transfer", waiter),
+        (http::Method::POST, "/api/v1/tables/0/merge", waiter),
+        (http::Method::POST, "/api/v1/tables/0/sessions", waiter),
+        (http::Method::GET, "/api/v1/tables/0/sessions", staff),
+        (http::Method::GET, "/api/v1/tables/0/sessions/current", staff),
+        (http::Method::POST, "/api/v1/tables/0/sessions/current/close", waiter),
+        (http::Method::POST, "/api/v1/tables/0/checkout", cashier),
+        (http::Method::POST, "/api/v1/tables/0/split", cashier),
+        (http::Method::GET, "/api/v1/bills/0", cashier),
+        (http::Method::GET, "/api/v1/kitchen/queue", staff),
+        (http::Method::GET, "/api/v1/kitchen/stream", staff),
+        (http::Method::GET, "/api/v1/devices/ws?device_id=tablet-1", staff),
+        (http::Method::GET, "/api/v1/menu", staff),
+        (http::Method::POST, "/api/v1/menu", manager),
+        (http::Method::GET, "/api/v1/menu/0", staff),
+        (http::Method::PUT, "/api/v1/menu/0", manager),
+        (http::Method::DELETE, "/api/v1/menu/0", manager),
+        (http::Method::GET, "/api/v1/admin/api_keys", admin),
+        (http::Method::POST, "/api/v1/admin/api_keys", admin),
+        (http::Method::DELETE, "/api/v1/admin/api_keys/1000", admin),
+        (http::Method::GET, "/api/v1/admin/staff", admin),
+        (http::Method::POST, "/api/v1/admin/staff", admin),
+        (http::Method::DELETE, "/api/v1/admin/staff/nobody", admin),
+    ];
+
+    for (method, uri, allowed_roles) in routes{
+        for role in roles.iter().copied().map(Some).chain([None]){
+            let mut request = Request::builder()
+                .uri(uri)
+                .method(method.clone())
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref());
+            if let Some(role) = role{
+                request = request.header(API_KEY_HEADER, format!("{role}-key"));
+            }
+            let status = app(context.clone()).oneshot(request.body(Body::from("null")).unwrap()).await.unwrap().status();
+
+            match role{
+                None => assert_eq!(status, StatusCode::UNAUTHORIZED, "{method} {uri} without key"),
+                Some(role) if allowed_roles.contains(&role) => assert!(status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN, "{method} {uri} as {role} -> {status}"),
+                Some(role) => assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri} as {role}")
+            }
+        }
+    }
+}
+
+
+#[tokio::test]
+async fn test_void_served_order_by_role_in_memory() {
+    let config = AppConfig{ admin_api_key: Some("admin-secret".to_string()), ..AppConfig::default() };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+    for role in [Role::Waiter, Role::Kitchen, Role::Manager]{
+        context.dbo.add_api_key(role.as_str(), role, &hash_api_key(&format!("{role}-key")), chrono::Utc::now()).await.unwrap();
+    }
+
+    let send_request = |method: http::Method, uri: String, role: Role, payload: Value| {
+        let context = context.clone();
+        let request = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(API_KEY_HEADER, format!("{role}-key"))
+            .body(Body::from(payload.to_string()))
+            .unwrap();
+        async move {
+            app(context).oneshot(request).await.unwrap().status()
+        }
+    };
+
+    // kitchen can't create orders
+    let orders = json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}]});
+    assert_eq!(send_request(http::Method::POST, "/api/v1/tables/1/orders".to_string(), Role::Kitchen, orders.clone()).await, StatusCode::FORBIDDEN);
+    assert_eq!(send_request(http::Method::POST, "/api/v1/tables/1/orders".to_string(), Role::Waiter, orders).await, StatusCode::OK);
+
+    let order_id = context.dbo.get_table_orders(1, None).await.unwrap()[0].order_id;
+    let order_uri = format!("/api/v1/tables/1/orders/{order_id}");
+    for (role, status) in [(Role::Kitchen, "cooking"), (Role::Kitchen, "ready"), (Role::Waiter, "served")]{
+        assert_eq!(send_request(http::Method::PATCH, order_uri.clone(), role, json!({"status": status})).await, StatusCode::OK);
+    }
+
+    // waiter can't void served order (without manager approval token), but manager can
+    let void_uri = format!("{order_uri}?reason=comp");
+    assert_eq!(send_request(http::Method::DELETE, void_uri.clone(), Role::Kitchen, Value::Null).await, StatusCode::FORBIDDEN);
+    assert_eq!(send_request(http::Method::DELETE, void_uri.clone(), Role::Waiter, Value::Null).await, StatusCode::FORBIDDEN);
+
+    // decrementing served order to 0 is a void too
+    let decrement_uri = format!("{order_uri}/decrement");
+    assert_eq!(send_request(http::Method::POST, decrement_uri.clone(), Role::Waiter, json!({"quantity": 1})).await, StatusCode::BAD_REQUEST);
+    assert_eq!(send_request(http::Method::POST, decrement_uri, Role::Waiter, json!({"quantity": 1, "reason": "comp"})).await, StatusCode::FORBIDDEN);
+    assert_eq!(context.dbo.get_table_orders(1, None).await.unwrap().len(), 1);
+    assert_eq!(send_request(http::Method::DELETE, void_uri, Role::Manager, Value::Null).await, StatusCode::OK);
+    assert!(context.dbo.get_table_orders(1, None).await.unwrap().is_empty());
+}
+
+
+#[tokio::test]
+async fn test_staff_login_in_memory() {
+    let config = AppConfig{
+        admin_api_key: Some("admin-secret".to_string()),
+        jwt_secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+        ..AppConfig::default()
+    };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let send_request = |method: http::Method, uri: &str, credential: Option<(&str, String)>, payload: Value| {
+        let context = context.clone();
+        let mut request = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref());
+        if let Some((name, value)) = credential{
+            request = request.header(name, value);
+        }
+        let request = request.body(Body::from(payload.to_string())).unwrap();
+        async move {
+            let response = app(context).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (status, serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null))
+        }
+    };
+    let admin = || Some((API_KEY_HEADER, "admin-secret".to_string()));
+    let bearer = |token: &Value| Some(("authorization", format!("Bearer {}", token.as_str().unwrap())));
+
+    // admin creates staff accounts (PIN is never returned)
+    for (staff_id, role) in [("waiter-1", "waiter"), ("chef-1", "kitchen")]{
+        let (status, body) = send_request(http::Method::POST, "/api/v1/admin/staff", admin(), json!({"staff_id": staff_id, "name": staff_id, "role": role, "pin": "1234"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["staff"][0].get("pin_hash").is_none());
+    }
+    let (status, _) = send_request(http::Method::POST, "/api/v1/admin/staff", admin(), json!({"staff_id": "waiter-2", "name": "waiter", "role": "waiter", "pin": "12"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // login with wrong PIN or unknown staff is unauthorized
+    for (staff_id, pin) in [("waiter-1", "9999"), ("nobody", "1234")]{
+        let (status, _) = send_request(http::Method::POST, "/api/v1/auth/login", None, json!({"staff_id": staff_id, "pin": pin})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let (status, tokens) = send_request(http::Method::POST, "/api/v1/auth/login", None, json!({"staff_id": "waiter-1", "pin": "1234"})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tokens["role"], "waiter");
+
+    // orders created and voided with staff token record the staff id
+    let orders = json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}, {"table_id": 1, "item_name": "Kapao"}]});
+    let (status, body) = send_request(http::Method::POST, "/api/v1/tables/1/orders", bearer(&tokens["access_token"]), orders.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"][0]["created_by"], "waiter-1");
+
+    let order_id = body["orders"][0]["order_id"].as_i64().unwrap();
+    let (status, _) = send_request(http::Method::DELETE, &format!("/api/v1/tables/1/orders/{order_id}?reason=kitchen_error&deleted_by=someone"), bearer(&tokens["access_token"]), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, history) = send_request(http::Method::GET, "/api/v1/tables/1/orders/history", bearer(&tokens["access_token"]), Value::Null).await;
+    assert!(history.to_string().contains(r#""deleted_by":"waiter-1""#));
+
+    // kitchen staff can't create orders
+    let (_, chef_tokens) = send_request(http::Method::POST, "/api/v1/auth/login", None, json!({"staff_id": "chef-1", "pin": "1234"})).await;
+    let (status, _) = send_request(http::Method::POST, "/api/v1/tables/1/orders", bearer(&chef_tokens["access_token"]), orders).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // refresh token can't be used as access token (and the other way around)
+    let (status, _) = send_request(http::Method::GET, "/api/v1/tables/1/orders", bearer(&tokens["refresh_token"]), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_request(http::Method::POST, "/api/v1/auth/refresh", None, json!({"refresh_token": tokens["access_token"]})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, refreshed) = send_request(http::Method::POST, "/api/v1/auth/refresh", None, json!({"refresh_token": tokens["refresh_token"]})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(refreshed["staff_id"], "waiter-1");
+
+    // deactivated staff can't refresh tokens or log in again
+    let (status, _) = send_request(http::Method::DELETE, "/api/v1/admin/staff/waiter-1", admin(), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_request(http::Method::POST, "/api/v1/auth/refresh", None, json!({"refresh_token": refreshed["refresh_token"]})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_request(http::Method::POST, "/api/v1/auth/login", None, json!({"staff_id": "waiter-1", "pin": "1234"})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+
+#[tokio::test]
+async fn test_rate_limit_in_memory() {
+    let config = AppConfig{
+        admin_api_key: Some("admin-secret".to_string()),
+        rate_limit_writes_per_minute: Some(2),
+        rate_limit_auth_per_minute: Some(1),
+        ..AppConfig::default()
+    };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+    for api_key in ["tablet-1", "tablet-2"]{
+        context.dbo.add_api_key(api_key, Role::Waiter, &hash_api_key(api_key), chrono::Utc::now()).await.unwrap();
+    }
+
+    let send_request = |method: http::Method, uri: &str, api_key: Option<&str>, payload: Value| {
+        let context = context.clone();
+        let mut request = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref());
+        if let Some(api_key) = api_key{
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        let request = request.body(Body::from(payload.to_string())).unwrap();
+        async move {
+            app(context).oneshot(request).await.unwrap()
+        }
+    };
+
+    // each tablet can add orders twice per minute
+    let orders = json!({"table_id": 1, "orders": [{"table_id": 1, "item_name": "Kapao"}]});
+    for _ in 0..2{
+        assert_eq!(send_request(http::Method::POST, "/api/v1/tables/1/orders", Some("tablet-1"), orders.clone()).await.status(), StatusCode::OK);
+    }
+    let response = send_request(http::Method::POST, "/api/v1/tables/1/orders", Some("tablet-1"), orders.clone()).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()[http::header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!((1..=30).contains(&retry_after));
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["status_code"], 429);
+    assert_eq!(context.dbo.get_table_orders(1, None).await.unwrap().len(), 2);
+
+    // other tablets, reads and health check are not limited by it
+    assert_eq!(send_request(http::Method::POST, "/api/v1/tables/1/orders", Some("tablet-2"), orders).await.status(), StatusCode::OK);
+    assert_eq!(send_request(http::Method::GET, "/api/v1/tables/1/orders", Some("tablet-1"), Value::Null).await.status(), StatusCode::OK);
+    for _ in 0..3{
+        assert_eq!(send_request(http::Method::GET, "/api/v1/health", None, Value::Null).await.status(), StatusCode::OK);
+    }
+
+    // staff login has its own limit (login is not configured, so the first attempt fails with server error)
+    let login = json!({"staff_id": "waiter-1", "pin": "1234"});
+    assert_eq!(send_request(http::Method::POST, "/api/v1/auth/login", None, login.clone()).await.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(send_request(http::Method::POST, "/api/v1/auth/login", None, login.clone()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // made-up API keys don't get buckets of their own
+    for api_key in ["random-1", "random-2"]{
+        assert_eq!(send_request(http::Method::POST, "/api/v1/auth/login", Some(api_key), login.clone()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
+
+
+#[tokio::test]
+async fn test_rate_limit_failed_authentication_in_memory() {
+    let config = AppConfig{
+        admin_api_key: Some("admin-secret".to_string()),
+        rate_limit_auth_per_minute: Some(2),
+        ..AppConfig::default()
+    };
+    let context = ApiContext::with_dao(config, MemTableOrderDAO::new());
+    context.dbo.add_api_key("tablet-1", Role::Waiter, &hash_api_key("tablet-1"), chrono::Utc::now()).await.unwrap();
+
+    let send_request = |api_key: &str| {
+        let context = context.clone();
+        let request = Request::builder()
+            .uri("/api/v1/tables/1/orders")
+            .header(API_KEY_HEADER, api_key)
+            .body(Body::empty())
+            .unwrap();
+        async move {
+            app(context).oneshot(request).await.unwrap()
+        }
+    };
+
+    // valid key is not limited by failed authentications of other clients
+    assert_eq!(send_request("tablet-1").await.status(), StatusCode::OK);
+
+    // a tablet looping with a bad key is rejected before its key is checked once the auth bucket is used up
+    for _ in 0..3{
+        assert_eq!(send_request("revoked-key").await.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = send_request("revoked-key").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()[http::header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!((1..=30).contains(&retry_after));
+
+    // clients are told apart by IP address, so every request of the blocked address is rejected, and health check is not limited
+    assert_eq!(send_request("tablet-1").await.status(), StatusCode::TOO_MANY_REQUESTS);
+    let health_check = Request::builder().uri("/api/v1/health").body(Body::empty()).unwrap();
+    assert_eq!(app(context.clone()).oneshot(health_check).await.unwrap().status(), StatusCode::OK);
+}
+
+
+#[tokio::test]
+async fn test_metrics_in_memory() {
+    let context = ApiContext::with_dao(AppConfig::default(), MemTableOrderDAO::new());
+    context.dbo.add_menu_item(&MenuItemRequest::new("Kapao", "main", 12000, 10)).await.unwrap();
+
+    let orders = json!({"table_id": 7, "orders": [{"table_id": 7, "item_name": "Kapao"}, {"table_id": 7, "item_name": "Kapao"}]});
+    assert_eq!(send_json_request_for_test(&context, http::Method::POST, "/api/v1/tables/7/orders", orders).await.0, StatusCode::OK);
+    assert_eq!(send_json_request_for_test(&context, http::Method::GET, "/api/v1/tables/9999/orders", Value::Null).await.0, StatusCode::NOT_FOUND);
+
+    let response = app(context.clone())
+        .oneshot(Request::builder().uri("/metrics").method(http::Method::GET).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[http::header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    // requests are labeled by route pattern (not by path) and status code
+    assert!(text.contains(r#"http_requests_total{method="POST",route="/api/v1/tables/:table_id/orders",status="200"}"#));
+    assert!(text.contains(r#"http_requests_total{method="GET",route="/api/v1/tables/:table_id/orders",status="404"}"#));
+    assert!(text.contains(r#"http_request_duration_seconds_bucket{method="POST",route="/api/v1/tables/:table_id/orders",status="200""#));
+    assert!(!text.contains("/api/v1/tables/7/orders"));
+
+    // business gauges are read from storage when scraped
+    assert!(text.contains(r#"open_orders{table_id="7"} 2"#));
+    assert!(text.contains("kitchen_unfinished_orders 2"));
+}
+
+
+#[sqlx::test]
+async fn test_metrics_of_database(db: PgPool) {
+    let context: ApiContext = ApiContext::new(db);
+    assert_eq!(get_table_orders_for_test(&context, 1).await.orders.len(), 0);
+
+    // gauges are not scraped through the API here, so that they don't race with the in-memory test
+    metrics().set_pool_usage(context.dbo.pool_usage());
+    let text = metrics().encode().unwrap();
+
+    assert!(text.contains(r#"dao_query_duration_seconds_count{method="get_table_orders"}"#));
+    assert!(text.contains(r#"db_pool_connections{state="idle"}"#));
+    assert!(text.contains("db_pool_max_connections"));
+}
+
+
+/// helper function to read next SSE event (event name and json data) from streaming response body
+async fn next_sse_event_for_test(body: &mut Body) -> (String, Value) {
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), body.frame())
+        .await
+        .expect("no event received")
+        .unwrap()
+        .unwrap();
+    let text = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+
+    let field = |name: &str| text.lines()
+        .find_map(|line| line.strip_prefix(name))
+        .unwrap_or_default()
+        .to_string();
+    (field("event: "), serde_json::from_str(&field("data: ")).unwrap())
+}
+
+
+/// helper function to get all orders of a table through the API
+async fn get_table_orders_for_test<D: StorageDAO>(context: &ApiContext<D>, table_id: i16) -> TableOrdersResponse {
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(format!("/api/v1/tables/{table_id}/orders"))
+            .method(http::Method::GET)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+
+/// helper function to send json payload to the API. It returns status code and json body
+async fn send_json_request_for_test<D: StorageDAO>(context: &ApiContext<D>, method: http::Method, uri: &str, payload: Value) -> (StatusCode, Value) {
+    let response = app(context.clone())
+    .oneshot(
+        Request::builder()
+        .uri(uri)
+            .method(method)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(payload.to_string()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+
+/// helper function to read next json message from WebSocket connection
+async fn next_ws_message_for_test<S>(socket: &mut tokio_tungstenite::WebSocketStream<S>) -> Value
+where S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin {
+    use futures::StreamExt;
+
+    let message = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+        .await
+        .expect("no message received")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
@@ -1,10 +1,20 @@
 
-use sqlx::postgres::PgPool;
+use sqlx::postgres::{PgPool, PgListener};
 
 use restaurant_server::model::*;
-use restaurant_server::dao::pg_order_dao::PgTableOrderDAO;
+use restaurant_server::dao::pg_order_dao::{PgTableOrderDAO, OrderNotification, ORDER_EVENTS_CHANNEL};
+use restaurant_server::events::OrderEventType;
 use restaurant_server::dao::order_dao::TableOrderDAO;
+use restaurant_server::dao::menu_dao::MenuDAO;
+use restaurant_server::dao::bill_dao::{BillDAO, BILLED_ORDER_REASON};
+use restaurant_server::dao::table_dao::{TableDAO, DEFAULT_TABLE_COUNT};
+use restaurant_server::dao::session_dao::SessionDAO;
+use restaurant_server::dao::idempotency_dao::{IdempotencyDAO, IdempotencyReservation, IDEMPOTENCY_KEY_TTL_HOURS, IDEMPOTENCY_LEASE_SECONDS};
+use restaurant_server::dao::api_key_dao::ApiKeyDAO;
+use restaurant_server::dao::staff_dao::StaffDAO;
+use restaurant_server::auth::hash_api_key;
 use restaurant_server::handlers::process_order_requests;
+use restaurant_server::error::ApiError;
 
 
 #[sqlx::test]
@@ -18,9 +28,9 @@ async fn test_add_one_order_record(pool: PgPool) {
     let order_items: Vec<OrderItem> = process_order_requests(table_orders);
 
     // insert
-    dao.add_table_orders(&order_items).await.expect("error in DAO impl");
+    dao.add_table_orders(&order_items, &[]).await.expect("error in DAO impl");
 
-    let last_order = sqlx::query_as!(OrderItem, "SELECT * FROM orders ORDER BY order_id DESC LIMIT 1")
+    let last_order = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders ORDER BY order_id DESC LIMIT 1")
         .fetch_one(&pool)
         .await
         .expect("cannot execute checking query");
@@ -45,9 +55,9 @@ async fn test_add_multiple_order_records(pool: PgPool) {
     let order_items: Vec<OrderItem> = process_order_requests(table_orders);
     
     
-    dao.add_table_orders(&order_items).await.expect("error in DAO impl");
+    dao.add_table_orders(&order_items, &[]).await.expect("error in DAO impl");
     
-    let orders: Vec<OrderItem> = sqlx::query_as!(OrderItem, "SELECT * FROM orders ORDER BY order_id ASC")
+    let orders: Vec<OrderItem> = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders ORDER BY order_id ASC")
         .fetch_all(&pool)
         .await        
         .expect("cannot execute checking query");
@@ -81,7 +91,7 @@ async fn test_add_order_with_invalid_records(pool: PgPool)  {
     let mut table_orders = TableOrdersRequest::new(6);
     table_orders.add_order_wihtout_note(&"k".repeat(256));
     let order_items: Vec<OrderItem> = process_order_requests(table_orders);
-    dao.add_table_orders(&order_items).await.unwrap();
+    dao.add_table_orders(&order_items, &[]).await.unwrap();
 }
 
 
@@ -89,7 +99,7 @@ async fn test_add_order_with_invalid_records(pool: PgPool)  {
 async fn test_get_all_orders(pool: PgPool) {
     
     let dao = PgTableOrderDAO::new(pool.clone());
-    let orders = dao.get_table_orders(11).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
 
     assert_eq!(orders.len(), 2);
 
@@ -114,7 +124,7 @@ async fn test_get_all_orders(pool: PgPool) {
 #[sqlx::test(fixtures("orders"))]
 async fn test_get_all_orders_with_empty_table(pool: PgPool) {
     let dao = PgTableOrderDAO::new(pool.clone());
-    let orders = dao.get_table_orders(100).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(100, None).await.expect("error in DAO impl");
 
     assert_eq!(orders.len(), 0);
 }
@@ -124,7 +134,7 @@ async fn test_get_all_orders_with_empty_table(pool: PgPool) {
 async fn test_get_specic_order(pool: PgPool) {
     let dao = PgTableOrderDAO::new(pool.clone());
 
-    let check_order = sqlx::query_as!(OrderItem, "SELECT * FROM orders ORDER BY order_id ASC LIMIT 1").fetch_one(&pool).await.expect("cannot execute check query");
+    let check_order = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders ORDER BY order_id ASC LIMIT 1").fetch_one(&pool).await.expect("cannot execute check query");
     let orders = dao.get_specific_table_order(check_order.table_id, check_order.order_id).await.expect("error in DAO impl");
     
     assert_eq!(orders.len(), 1);
@@ -151,11 +161,11 @@ async fn test_get_order_with_invalid_ids(pool: PgPool) {
 async fn test_remove_order(pool: PgPool) {
     let dao = PgTableOrderDAO::new(pool.clone());
 
-    let check_order = sqlx::query_as!(OrderItem, "SELECT * FROM orders ORDER BY order_id ASC LIMIT 1").fetch_one(&pool).await.expect("cannot execute check query");
-    dao.remove_order(check_order.table_id, check_order.order_id).await.expect("error in DAO impl");
+    let check_order = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders ORDER BY order_id ASC LIMIT 1").fetch_one(&pool).await.expect("cannot execute check query");
+    dao.remove_order(check_order.table_id, check_order.order_id, None, None, chrono::Utc::now(), &[]).await.expect("error in DAO impl");
 
     
-    let remaining_orders = sqlx::query_as!(OrderItem, "SELECT * FROM orders ORDER BY order_id ASC").fetch_all(&pool).await.expect("cannot execute check query");
+    let remaining_orders = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders WHERE deleted_at IS NULL ORDER BY order_id ASC").fetch_all(&pool).await.expect("cannot execute check query");
     assert_eq!(remaining_orders.len(), 1);
 
     let order = &remaining_orders[0];
@@ -166,3 +176,636 @@ async fn test_remove_order(pool: PgPool) {
     assert_eq!(order.estimated_arrival_time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true), "2024-01-11T15:40:00.000000Z");   
 }
 
+
+
+#[sqlx::test]
+async fn test_health_check(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    dao.health_check().await.expect("error in DAO impl");
+
+    pool.close().await;
+    assert!(dao.health_check().await.is_err());
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_update_order_status(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let check_order = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders ORDER BY order_id ASC LIMIT 1").fetch_one(&pool).await.expect("cannot execute check query");
+    assert_eq!(check_order.status, OrderStatus::Ordered);
+
+    dao.update_order_status(check_order.table_id, check_order.order_id, OrderStatus::Cooking, &[]).await.expect("error in DAO impl");
+    let orders = dao.get_specific_table_order(check_order.table_id, check_order.order_id).await.expect("error in DAO impl");
+    assert_eq!(orders[0].status, OrderStatus::Cooking);
+
+    // filter by status
+    let orders = dao.get_table_orders(11, Some(OrderStatus::Cooking)).await.expect("error in DAO impl");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].order_id, check_order.order_id);
+    assert_eq!(dao.get_table_orders(11, Some(OrderStatus::Ordered)).await.expect("error in DAO impl").len(), 1);
+    assert_eq!(dao.get_table_orders(11, Some(OrderStatus::Served)).await.expect("error in DAO impl").len(), 0);
+
+    // illegal transition is rejected and status is unchanged
+    let result = dao.update_order_status(check_order.table_id, check_order.order_id, OrderStatus::Served, &[]).await;
+    assert!(matches!(result, Err(ApiError::IllegalStatusTransition(OrderStatus::Cooking, OrderStatus::Served))));
+    let orders = dao.get_specific_table_order(check_order.table_id, check_order.order_id).await.expect("error in DAO impl");
+    assert_eq!(orders[0].status, OrderStatus::Cooking);
+
+    // unknown order
+    let result = dao.update_order_status(1, check_order.order_id, OrderStatus::Ready, &[]).await;
+    assert!(matches!(result, Err(ApiError::OrderNotFound)));
+
+    // both orders are still in the kitchen queue (ordered or cooking)
+    assert_eq!(dao.count_unfinished_orders().await.expect("error in DAO impl"), 2);
+    dao.update_order_status(check_order.table_id, check_order.order_id, OrderStatus::Ready, &[]).await.expect("error in DAO impl");
+    assert_eq!(dao.count_unfinished_orders().await.expect("error in DAO impl"), 1);
+
+    // ready orders are still open until they are served
+    assert_eq!(dao.count_open_orders_by_table().await.expect("error in DAO impl"), vec![(11, 2)]);
+    dao.update_order_status(check_order.table_id, check_order.order_id, OrderStatus::Served, &[]).await.expect("error in DAO impl");
+    assert_eq!(dao.count_open_orders_by_table().await.expect("error in DAO impl"), vec![(11, 1)]);
+
+    let pool_usage = dao.pool_usage().expect("postgres DAO has pool");
+    assert!(pool_usage.idle <= pool_usage.size && pool_usage.size <= pool_usage.max_size);
+}
+
+
+#[sqlx::test(fixtures("menu"))]
+async fn test_menu_items(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let items = dao.get_menu_items().await.expect("error in DAO impl");
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[0].name, "Kapao");
+    assert!(!items[4].active);
+
+    let items = dao.get_menu_items_by_names(&["Ramen".to_string(), "Seasonal soup".to_string(), "Burger".to_string()]).await.expect("error in DAO impl");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].name, "Ramen");
+    assert_eq!(items[1].name, "Seasonal soup");
+    assert!(dao.get_menu_items_by_names(&[]).await.expect("error in DAO impl").is_empty());
+
+    let added = dao.add_menu_item(&MenuItemRequest::new("Thai tea", "drink", 6000, 3)).await.expect("error in DAO impl");
+    assert_eq!(added.name, "Thai tea");
+    assert!(added.active);
+    assert_eq!(dao.get_menu_item(added.menu_item_id).await.expect("error in DAO impl"), added);
+    assert!(matches!(dao.add_menu_item(&MenuItemRequest::new("Kapao", "main", 1, 1)).await, Err(ApiError::MenuItemAlreadyExists)));
+
+    let mut request = MenuItemRequest::new("Thai milk tea", "drink", 6500, 3);
+    request.active = false;
+    let updated = dao.update_menu_item(added.menu_item_id, &request).await.expect("error in DAO impl");
+    assert_eq!(updated.menu_item_id, added.menu_item_id);
+    assert_eq!(updated.price, 6500);
+    assert!(!updated.active);
+    assert!(matches!(dao.update_menu_item(added.menu_item_id, &MenuItemRequest::new("Ramen", "main", 1, 1)).await, Err(ApiError::MenuItemAlreadyExists)));
+
+    dao.remove_menu_item(added.menu_item_id).await.expect("error in DAO impl");
+    assert!(matches!(dao.get_menu_item(added.menu_item_id).await, Err(ApiError::MenuItemNotFound)));
+    assert!(matches!(dao.remove_menu_item(added.menu_item_id).await, Err(ApiError::MenuItemNotFound)));
+    assert!(matches!(dao.update_menu_item(added.menu_item_id, &request).await, Err(ApiError::MenuItemNotFound)));
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_remove_menu_item_with_open_orders(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    // Kapao (1) is ordered by table 11 (by name, without menu snapshot)
+    assert!(matches!(dao.remove_menu_item(1).await, Err(ApiError::MenuItemInUse)));
+    assert!(dao.get_menu_item(1).await.is_ok());
+
+    // Pizza (3) is still referenced by id after it is renamed
+    let mut table_orders = TableOrdersRequest::new(12);
+    table_orders.add_order_wihtout_note("Pizza");
+    let order_items: Vec<OrderItem> = process_order_requests(table_orders).into_iter()
+        .map(|order| OrderItem{ menu_item_id: Some(3), unit_price: Some(25000), ..order })
+        .collect();
+    dao.add_table_orders(&order_items, &[]).await.expect("error in DAO impl");
+    dao.update_menu_item(3, &MenuItemRequest::new("Pizza margherita", "main", 25000, 20)).await.expect("error in DAO impl");
+    assert!(matches!(dao.remove_menu_item(3).await, Err(ApiError::MenuItemInUse)));
+
+    // billed orders don't block removal
+    dao.checkout_table(11, BillCharges::default(), chrono::Utc::now()).await.expect("error in DAO impl");
+    dao.remove_menu_item(1).await.expect("error in DAO impl");
+    assert!(matches!(dao.get_menu_item(1).await, Err(ApiError::MenuItemNotFound)));
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_table_registry(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    // default tables and tables of existing orders are registered by migration
+    let tables = dao.get_tables().await.expect("error in DAO impl");
+    assert_eq!(tables.len(), DEFAULT_TABLE_COUNT as usize);
+    assert_eq!(dao.get_table(11).await.expect("error in DAO impl").label, "Table 11");
+
+    let patio = dao.add_table(&TableRequest::new("Patio 3", 2, "patio")).await.expect("error in DAO impl");
+    assert_eq!(patio.table_id, DEFAULT_TABLE_COUNT + 1);
+    assert!(patio.active);
+
+    let mut request = TableRequest::new("Patio 3", 6, "patio");
+    request.active = false;
+    let updated = dao.update_table(patio.table_id, &request).await.expect("error in DAO impl");
+    assert_eq!(updated.capacity, 6);
+    assert!(!updated.active);
+    assert_eq!(dao.get_table(patio.table_id).await.expect("error in DAO impl"), updated);
+
+    // table with orders can't be deactivated or removed
+    assert!(matches!(dao.update_table(11, &request).await, Err(ApiError::TableInUse)));
+    assert!(matches!(dao.remove_table(11).await, Err(ApiError::TableInUse)));
+    assert!(dao.get_table(11).await.expect("error in DAO impl").active);
+
+    dao.remove_table(patio.table_id).await.expect("error in DAO impl");
+    assert!(matches!(dao.get_table(patio.table_id).await, Err(ApiError::TableNotFound)));
+    assert!(matches!(dao.remove_table(patio.table_id).await, Err(ApiError::TableNotFound)));
+    assert!(matches!(dao.update_table(patio.table_id, &request).await, Err(ApiError::TableNotFound)));
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_acknowledge_order(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let order_id = dao.get_table_orders(11, None).await.expect("error in DAO impl")[0].order_id;
+    assert_eq!(dao.get_specific_table_order(11, order_id).await.expect("error in DAO impl")[0].acknowledged_by, None);
+
+    let time = chrono::Utc::now();
+    dao.acknowledge_order(11, order_id, "kitchen-1", time).await.expect("error in DAO impl");
+
+    // the first acknowledgement is kept
+    dao.acknowledge_order(11, order_id, "kitchen-2", time + chrono::Duration::minutes(1)).await.expect("error in DAO impl");
+    let order = dao.get_specific_table_order(11, order_id).await.expect("error in DAO impl").remove(0);
+    assert_eq!(order.acknowledged_by, Some("kitchen-1".to_string()));
+    assert_eq!(order.acknowledgement_time.map(|time| time.timestamp_micros()), Some(time.timestamp_micros()));
+
+    assert!(matches!(dao.acknowledge_order(12, order_id, "kitchen-1", time).await, Err(ApiError::OrderNotFound)));
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_order_change_notification(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let mut listener = PgListener::connect_with(&pool).await.unwrap();
+    listener.listen(ORDER_EVENTS_CHANNEL).await.unwrap();
+
+    let order_id = dao.get_table_orders(11, None).await.expect("error in DAO impl")[0].order_id;
+
+    // failed change is rolled back without notification
+    assert!(matches!(dao.update_order_status(11, order_id, OrderStatus::Served, &[]).await, Err(ApiError::IllegalStatusTransition(_, _))));
+    dao.update_order_status(11, order_id, OrderStatus::Cooking, &[]).await.expect("error in DAO impl");
+
+    let notification = listener.recv().await.unwrap();
+    let notification: OrderNotification = serde_json::from_str(notification.payload()).unwrap();
+    assert_eq!(notification, OrderNotification{
+        instance_id: dao.instance_id.clone(),
+        event_type: OrderEventType::OrderStatusChanged,
+        table_id: 11,
+        order_id: Some(order_id)
+    });
+
+    dao.remove_order(11, order_id, None, None, chrono::Utc::now(), &[]).await.expect("error in DAO impl");
+    let notification: OrderNotification = serde_json::from_str(listener.recv().await.unwrap().payload()).unwrap();
+    assert_eq!(notification.event_type, OrderEventType::OrderRemoved);
+}
+
+
+#[sqlx::test]
+async fn test_decrement_order_quantity(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let mut table_orders = TableOrdersRequest::new(6);
+    table_orders.orders.push(OrderItemRequest::new_wihout_note(6, "Beer").with_quantity(3));
+    table_orders.add_order_wihtout_note("Ramen");
+    dao.add_table_orders(&process_order_requests(table_orders), &[]).await.expect("error in DAO impl");
+
+    let orders = dao.get_table_orders(6, None).await.expect("error in DAO impl");
+    assert_eq!(orders[0].quantity, 3);
+    assert_eq!(orders[1].quantity, 1);
+    let beer_order_id = orders[0].order_id;
+
+    dao.decrement_order_quantity(6, beer_order_id, 2, None, None, &[]).await.expect("error in DAO impl");
+    let orders = dao.get_specific_table_order(6, beer_order_id).await.expect("error in DAO impl");
+    assert_eq!(orders[0].quantity, 1);
+
+    // decrement more than ordered quantity is rejected and quantity is unchanged
+    assert!(matches!(dao.decrement_order_quantity(6, beer_order_id, 2, None, None, &[]).await, Err(ApiError::BadRequest(_))));
+    assert!(matches!(dao.decrement_order_quantity(7, beer_order_id, 1, None, None, &[]).await, Err(ApiError::OrderNotFound)));
+    assert_eq!(dao.get_specific_table_order(6, beer_order_id).await.expect("error in DAO impl")[0].quantity, 1);
+
+    // order is removed when quantity reaches 0, which requires a void reason
+    assert!(matches!(dao.decrement_order_quantity(6, beer_order_id, 1, None, None, &[]).await, Err(ApiError::BadRequest(_))));
+    dao.decrement_order_quantity(6, beer_order_id, 1, Some("waiter-1"), Some("customer_changed_mind"), &[]).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(6, None).await.expect("error in DAO impl");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].item_name, "Ramen");
+    let removed = dao.get_table_order_history(6).await.expect("error in DAO impl").into_iter().find(|order| order.order_id == beer_order_id).unwrap();
+    assert_eq!(removed.deleted_by, Some("waiter-1".to_string()));
+    assert_eq!(removed.reason, Some("customer_changed_mind".to_string()));
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_checkout_table(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let mut table_orders = TableOrdersRequest::new(12);
+    table_orders.add_order_wihtout_note("Pizza");
+    dao.add_table_orders(&process_order_requests(table_orders), &[]).await.expect("error in DAO impl");
+
+    // Kapao (12000) + Ramen (15000), 10% service charge, 7% tax
+    let session = dao.get_current_session(11).await.expect("error in DAO impl");
+    let charges = BillCharges{ service_charge_bps: 1000, tax_bps: 700 };
+    let bill = dao.checkout_table(11, charges, chrono::Utc::now()).await.expect("error in DAO impl");
+    assert_eq!(bill.table_id, 11);
+    assert_eq!(bill.line_items.len(), 2);
+    assert_eq!(bill.line_items[0].item_name, "Kapao");
+    assert_eq!(bill.line_items[0].unit_price, 12000);
+    assert_eq!(bill.subtotal, 27000);
+    assert_eq!(bill.service_charge, 2700);
+    assert_eq!(bill.tax, 2079);
+    assert_eq!(bill.total, 31779);
+
+    let stored_bill = dao.get_bill(bill.bill_id).await.expect("error in DAO impl");
+    assert_eq!(stored_bill.line_items, bill.line_items);
+    assert_eq!(stored_bill.total, bill.total);
+
+    // table is cleared, other tables are untouched
+    assert!(dao.get_table_orders(11, None).await.expect("error in DAO impl").is_empty());
+    assert_eq!(dao.get_table_orders(12, None).await.expect("error in DAO impl").len(), 1);
+
+    // billed orders are kept (soft deleted)
+    let billed_orders = sqlx::query_as::<_, OrderItem>("SELECT * FROM orders WHERE table_id = $1 ORDER BY order_id ASC").bind(11_i16).fetch_all(&pool).await.expect("cannot execute check query");
+    assert_eq!(billed_orders.len(), 2);
+    assert!(billed_orders.iter().all(|order| order.deleted_at.is_some() && order.reason.as_deref() == Some(BILLED_ORDER_REASON)));
+
+    // billed orders are still orders of the closed session
+    assert!(matches!(dao.get_current_session(11).await, Err(ApiError::SessionNotFound)));
+    assert_eq!(dao.get_session_orders(11, session.session_id, None).await.expect("error in DAO impl"), billed_orders);
+
+    assert!(matches!(dao.checkout_table(11, charges, chrono::Utc::now()).await, Err(ApiError::BadRequest(_))));
+    assert!(matches!(dao.get_bill(bill.bill_id + 1).await, Err(ApiError::BillNotFound)));
+}
+
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_checkout_table_with_snapshot_price(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    // Pizza was 20000 when it was ordered, the menu price (25000) changed afterwards
+    let mut table_orders = TableOrdersRequest::new(12);
+    table_orders.add_order_wihtout_note("Pizza");
+    let order_items: Vec<OrderItem> = process_order_requests(table_orders).into_iter()
+        .map(|order| OrderItem{ menu_item_id: Some(3), unit_price: Some(20000), ..order })
+        .collect();
+    dao.add_table_orders(&order_items, &[]).await.expect("error in DAO impl");
+
+    let orders = dao.get_table_orders(12, None).await.expect("error in DAO impl");
+    assert_eq!(orders[0].menu_item_id, Some(3));
+    assert_eq!(orders[0].unit_price, Some(20000));
+
+    let bill = dao.checkout_table(12, BillCharges::default(), chrono::Utc::now()).await.expect("error in DAO impl");
+    assert_eq!(bill.line_items[0].unit_price, 20000);
+    assert_eq!(bill.total, 20000);
+}
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_checkout_table_without_menu_prices(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    // menu is empty, so nothing can be billed and orders are kept
+    let result = dao.checkout_table(11, BillCharges::default(), chrono::Utc::now()).await;
+    assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    assert_eq!(dao.get_table_orders(11, None).await.expect("error in DAO impl").len(), 2);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_get_kitchen_queue(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let mut table_orders = TableOrdersRequest::new(12);
+    table_orders.add_order_wihtout_note("Pizza");
+    table_orders.add_order_wihtout_note("Seasonal soup");
+    let mut orders = process_order_requests(table_orders);
+    orders[1].menu_item_id = Some(5);
+    dao.add_table_orders(&orders, &[]).await.expect("error in DAO impl");
+
+    // fixture orders (Kapao, Ramen) were estimated in 2024, so they come first
+    let queue = dao.get_kitchen_queue(None, 10).await.expect("error in DAO impl");
+    assert_eq!(queue.len(), 4);
+    assert_eq!(queue[0].item_name, "Kapao");
+    assert_eq!(queue[1].item_name, "Ramen");
+    assert!(queue.windows(2).all(|pair| pair[0].estimated_arrival_time <= pair[1].estimated_arrival_time));
+
+    // cancelled orders are not in the queue
+    dao.update_order_status(11, queue[0].order_id, OrderStatus::Cancelled, &[]).await.expect("error in DAO impl");
+    let queue = dao.get_kitchen_queue(None, 2).await.expect("error in DAO impl");
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue[0].item_name, "Ramen");
+
+    let queue = dao.get_kitchen_queue(Some("soup"), 10).await.expect("error in DAO impl");
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].item_name, "Seasonal soup");
+    assert!(dao.get_kitchen_queue(Some("drink"), 10).await.expect("error in DAO impl").is_empty());
+
+    // order with menu item snapshot stays in the category after the item is renamed
+    dao.update_menu_item(5, &MenuItemRequest::new("Pumpkin soup", "soup", 8000, 5)).await.expect("error in DAO impl");
+    let queue = dao.get_kitchen_queue(Some("soup"), 10).await.expect("error in DAO impl");
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].item_name, "Seasonal soup");
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_seating_sessions(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    // orders of the fixture belong to the open session of table 11
+    let first = dao.get_current_session(11).await.expect("error in DAO impl");
+    assert_eq!(first.guest_count, Some(2));
+    assert!(matches!(dao.open_session(11, 4, chrono::Utc::now()).await, Err(ApiError::SessionAlreadyOpen)));
+    assert!(matches!(dao.get_current_session(12).await, Err(ApiError::SessionNotFound)));
+    let previous_orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
+    let order_count = previous_orders.len();
+
+    // the session can't be closed while it has unbilled orders
+    assert!(matches!(dao.close_current_session(11, chrono::Utc::now()).await, Err(ApiError::SessionHasOrders)));
+    assert_eq!(dao.get_table_orders(11, None).await.expect("error in DAO impl").len(), order_count);
+
+    // next party doesn't see orders of the previous party (e.g., session closed before its orders were billed)
+    sqlx::query("UPDATE table_sessions SET close_time = $1 WHERE session_id = $2").bind(chrono::Utc::now()).bind(first.session_id).execute(&pool).await.expect("cannot execute close query");
+    let closed = dao.get_table_sessions(11).await.expect("error in DAO impl").remove(0);
+    assert!(closed.close_time.is_some());
+    assert!(dao.get_table_orders(11, None).await.expect("error in DAO impl").is_empty());
+    assert!(matches!(dao.close_current_session(11, chrono::Utc::now()).await, Err(ApiError::SessionNotFound)));
+
+    // orders of the closed session can't be changed anymore, and they are not open orders of the kitchen or the table
+    let order_id = previous_orders[0].order_id;
+    assert!(matches!(dao.get_specific_table_order(11, order_id).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.update_order_status(11, order_id, OrderStatus::Cooking, &[]).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.decrement_order_quantity(11, order_id, 1, None, None, &[]).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.remove_order(11, order_id, None, None, chrono::Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.acknowledge_order(11, order_id, "kitchen-1", chrono::Utc::now()).await, Err(ApiError::OrderNotFound)));
+    assert_eq!(dao.count_unfinished_orders().await.expect("error in DAO impl"), 0);
+    assert!(dao.get_kitchen_queue(None, 10).await.expect("error in DAO impl").is_empty());
+    assert!(dao.count_open_orders_by_table().await.expect("error in DAO impl").is_empty());
+
+    let second = dao.open_session(11, 4, chrono::Utc::now()).await.expect("error in DAO impl");
+    let mut table_orders = TableOrdersRequest::new(11);
+    table_orders.add_order_wihtout_note("Kapao");
+    dao.add_table_orders(&process_order_requests(table_orders), &[]).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].session_id, Some(second.session_id));
+
+    // past sessions are still queryable
+    assert_eq!(dao.get_table_sessions(11).await.expect("error in DAO impl"), vec![closed, second]);
+    assert_eq!(dao.get_session_orders(11, first.session_id, None).await.expect("error in DAO impl").len(), order_count);
+    assert!(matches!(dao.get_session_orders(12, first.session_id, None).await, Err(ApiError::SessionNotFound)));
+
+    // adding orders without open session opens a session implicitly
+    let mut table_orders = TableOrdersRequest::new(12);
+    table_orders.add_order_wihtout_note("Kapao");
+    dao.add_table_orders(&process_order_requests(table_orders), &[]).await.expect("error in DAO impl");
+    assert_eq!(dao.get_current_session(12).await.expect("error in DAO impl").guest_count, None);
+
+    // the session can be closed once its orders are removed
+    let order_id = dao.get_table_orders(12, None).await.expect("error in DAO impl")[0].order_id;
+    dao.remove_order(12, order_id, None, Some("customer_changed_mind"), chrono::Utc::now(), &[]).await.expect("error in DAO impl");
+    assert!(dao.close_current_session(12, chrono::Utc::now()).await.expect("error in DAO impl").close_time.is_some());
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_add_orders_while_closing_session(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    let closing = dao.get_current_session(11).await.expect("error in DAO impl");
+
+    // checkout (or merge / close) locks the open session before reading its orders and closing it
+    let mut transaction = pool.begin().await.unwrap();
+    sqlx::query("SELECT session_id FROM table_sessions WHERE table_id = 11 and close_time IS NULL FOR UPDATE")
+        .execute(&mut *transaction).await.unwrap();
+
+    let mut table_orders = TableOrdersRequest::new(11);
+    table_orders.add_order_wihtout_note("Kapao");
+    let adding = tokio::spawn({
+        let dao = dao.clone();
+        async move { dao.add_table_orders(&process_order_requests(table_orders), &[]).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!adding.is_finished());
+
+    sqlx::query("UPDATE table_sessions SET close_time = $1 WHERE session_id = $2")
+        .bind(chrono::Utc::now())
+        .bind(closing.session_id)
+        .execute(&mut *transaction).await.unwrap();
+    transaction.commit().await.unwrap();
+
+    // the order isn't added to the closed session, but to a new one
+    adding.await.unwrap().expect("error in DAO impl");
+    let orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
+    assert_eq!(orders.len(), 1);
+    assert_ne!(orders[0].session_id, Some(closing.session_id));
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_transfer_and_merge_orders(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let order_ids: Vec<i32> = dao.get_table_orders(11, None).await.expect("error in DAO impl")
+        .iter().map(|order| order.order_id).collect();
+    assert!(order_ids.len() > 1);
+
+    // nothing is moved if any order is not in the source table
+    assert!(matches!(dao.transfer_orders(11, 12, &[order_ids[0], 100000], chrono::Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+    assert_eq!(dao.get_table_orders(11, None).await.expect("error in DAO impl").len(), order_ids.len());
+
+    dao.transfer_orders(11, 12, &order_ids[..1], chrono::Utc::now(), &[]).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(12, None).await.expect("error in DAO impl");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].order_id, order_ids[0]);
+    assert_eq!(orders[0].session_id, Some(dao.get_current_session(12).await.expect("error in DAO impl").session_id));
+    assert_eq!(dao.get_table_orders(11, None).await.expect("error in DAO impl").len(), order_ids.len() - 1);
+
+    // merge moves the remaining orders and closes the source session
+    dao.merge_tables(11, 12, chrono::Utc::now(), &[]).await.expect("error in DAO impl");
+    assert_eq!(dao.get_table_orders(12, None).await.expect("error in DAO impl").len(), order_ids.len());
+    assert!(dao.get_table_orders(11, None).await.expect("error in DAO impl").is_empty());
+    assert!(matches!(dao.get_current_session(11).await, Err(ApiError::SessionNotFound)));
+    assert!(matches!(dao.merge_tables(11, 12, chrono::Utc::now(), &[]).await, Err(ApiError::BadRequest(_))));
+}
+
+
+#[sqlx::test]
+async fn test_idempotency_keys(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    let time = chrono::Utc::now();
+
+    assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.expect("error in DAO impl"), IdempotencyReservation::Reserved);
+    assert!(matches!(dao.reserve_idempotency_key("key-1", "request", time).await, Err(ApiError::IdempotentRequestInProgress)));
+    assert!(matches!(dao.reserve_idempotency_key("key-1", "other request", time).await, Err(ApiError::IdempotencyKeyReused)));
+
+    // released key can be reserved again
+    dao.release_idempotency_key("key-1").await.expect("error in DAO impl");
+    assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.expect("error in DAO impl"), IdempotencyReservation::Reserved);
+
+    dao.complete_idempotency_key("key-1", 200, Some("\"1-1\""), "{}").await.expect("error in DAO impl");
+    dao.release_idempotency_key("key-1").await.expect("error in DAO impl");
+    assert_eq!(dao.reserve_idempotency_key("key-1", "request", time).await.expect("error in DAO impl"),
+        IdempotencyReservation::Completed{ status_code: 200, etag: Some("\"1-1\"".to_string()), response_body: "{}".to_string() });
+
+    // unfinished request is taken over by a retry after its lease, but not by a different request
+    assert_eq!(dao.reserve_idempotency_key("key-2", "request", time).await.expect("error in DAO impl"), IdempotencyReservation::Reserved);
+    let lease_expired = time + chrono::Duration::seconds(IDEMPOTENCY_LEASE_SECONDS + 1);
+    assert!(matches!(dao.reserve_idempotency_key("key-2", "other request", lease_expired).await, Err(ApiError::IdempotencyKeyReused)));
+    assert_eq!(dao.reserve_idempotency_key("key-2", "request", lease_expired).await.expect("error in DAO impl"), IdempotencyReservation::Reserved);
+    assert!(matches!(dao.reserve_idempotency_key("key-2", "request", lease_expired).await, Err(ApiError::IdempotentRequestInProgress)));
+
+    // expired key is forgotten
+    let later = time + chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS + 1);
+    assert_eq!(dao.reserve_idempotency_key("key-1", "other request", later).await.expect("error in DAO impl"), IdempotencyReservation::Reserved);
+}
+
+
+#[sqlx::test(fixtures("orders"))]
+async fn test_orders_version(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    assert!(matches!(dao.get_orders_version(1000).await, Err(ApiError::TableNotFound)));
+
+    // every change of table's orders increases the version
+    let mut version = dao.get_orders_version(11).await.expect("error in DAO impl");
+    let orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
+    let (order_id, other_order_id) = (orders[0].order_id, orders[1].order_id);
+    for change in 0..5{
+        match change{
+            0 => dao.update_order_status(11, order_id, OrderStatus::Cooking, &[]).await,
+            1 => dao.acknowledge_order(11, order_id, "kitchen-1", chrono::Utc::now()).await,
+            2 => dao.transfer_orders(11, 12, &[order_id], chrono::Utc::now(), &[]).await,
+            3 => dao.remove_order(11, other_order_id, None, Some("comp"), chrono::Utc::now(), &[]).await,
+            _ => dao.close_current_session(11, chrono::Utc::now()).await.map(|_| ())
+        }.expect("error in DAO impl");
+
+        let changed_version = dao.get_orders_version(11).await.expect("error in DAO impl");
+        assert!(changed_version > version);
+        version = changed_version;
+    }
+    assert!(dao.get_orders_version(12).await.expect("error in DAO impl") > 0);
+
+    // change with an outdated version is rejected without changing anything, and the same version matches only once
+    let version = dao.get_orders_version(12).await.expect("error in DAO impl");
+    assert!(matches!(dao.update_order_status(12, order_id, OrderStatus::Ready, &[(12, version - 1)]).await, Err(ApiError::PreconditionFailed)));
+    assert_eq!(dao.get_orders_version(12).await.expect("error in DAO impl"), version);
+    assert_eq!(dao.get_specific_table_order(12, order_id).await.expect("error in DAO impl")[0].status, OrderStatus::Cooking);
+
+    dao.update_order_status(12, order_id, OrderStatus::Ready, &[(12, version)]).await.expect("error in DAO impl");
+    assert!(matches!(dao.update_order_status(12, order_id, OrderStatus::Served, &[(12, version)]).await, Err(ApiError::PreconditionFailed)));
+
+    // versions of both tables are compared when orders are moved
+    let version = dao.get_orders_version(12).await.expect("error in DAO impl");
+    let target_version = dao.get_orders_version(13).await.expect("error in DAO impl");
+    assert!(matches!(dao.transfer_orders(12, 13, &[order_id], chrono::Utc::now(), &[(12, version), (13, target_version + 1)]).await,
+        Err(ApiError::PreconditionFailed)));
+    dao.transfer_orders(12, 13, &[order_id], chrono::Utc::now(), &[(12, version), (13, target_version)]).await.expect("error in DAO impl");
+    assert!(dao.get_orders_version(13).await.expect("error in DAO impl") > target_version);
+}
+
+
+#[sqlx::test(fixtures("menu", "orders"))]
+async fn test_order_history(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+
+    let orders = dao.get_table_orders(11, None).await.expect("error in DAO impl");
+    let removed_order_id = orders[0].order_id;
+    dao.remove_order(11, removed_order_id, Some("waiter-1"), Some("customer changed mind"), chrono::Utc::now(), &[]).await.expect("error in DAO impl");
+    assert!(matches!(dao.remove_order(11, removed_order_id, None, None, chrono::Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+
+    // removed order is excluded from other queries
+    assert_eq!(dao.get_table_orders(11, None).await.expect("error in DAO impl").len(), orders.len() - 1);
+    assert!(matches!(dao.get_specific_table_order(11, removed_order_id).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.update_order_status(11, removed_order_id, OrderStatus::Cooking, &[]).await, Err(ApiError::OrderNotFound)));
+    assert!(matches!(dao.transfer_orders(11, 12, &[removed_order_id], chrono::Utc::now(), &[]).await, Err(ApiError::OrderNotFound)));
+
+    // but kept in order history with who removed it and why
+    let history = dao.get_table_order_history(11).await.expect("error in DAO impl");
+    assert_eq!(history.len(), orders.len());
+    assert_eq!(history[0].order_id, removed_order_id);
+    assert_eq!(history[0].deleted_by, Some("waiter-1".to_string()));
+    assert_eq!(history[0].reason, Some("customer changed mind".to_string()));
+    assert!(history[0].deleted_at.is_some());
+    assert!(history[1..].iter().all(|order| order.deleted_at.is_none()));
+
+    // removed order isn't billed
+    let bill = dao.checkout_table(11, BillCharges::default(), chrono::Utc::now()).await.expect("error in DAO impl");
+    assert_eq!(bill.line_items.len(), orders.len() - 1);
+    assert!(bill.line_items.iter().all(|line| line.order_id != removed_order_id));
+}
+
+
+#[sqlx::test]
+async fn test_api_keys(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    let time = chrono::Utc::now();
+
+    let first = dao.add_api_key("front desk", Role::Waiter, &hash_api_key("key-1"), time).await.expect("error in DAO impl");
+    let second = dao.add_api_key("kitchen", Role::Kitchen, &hash_api_key("key-2"), time).await.expect("error in DAO impl");
+    assert!(second.api_key_id > first.api_key_id);
+    assert!(dao.add_api_key("duplicate", Role::Waiter, &hash_api_key("key-1"), time).await.is_err());
+
+    assert_eq!(dao.authenticate_api_key(&hash_api_key("key-2")).await.expect("error in DAO impl").role, Role::Kitchen);
+    assert!(matches!(dao.authenticate_api_key(&hash_api_key("unknown")).await, Err(ApiError::Unauthorized)));
+
+    // revoking again keeps the first revocation time
+    let revoked = dao.revoke_api_key(second.api_key_id, time).await.expect("error in DAO impl");
+    assert!(revoked.revocation_time.is_some());
+    let revoked_again = dao.revoke_api_key(second.api_key_id, time + chrono::Duration::minutes(1)).await.expect("error in DAO impl");
+    assert_eq!(revoked_again.revocation_time, revoked.revocation_time);
+
+    assert!(matches!(dao.authenticate_api_key(&hash_api_key("key-2")).await, Err(ApiError::Unauthorized)));
+    assert!(matches!(dao.revoke_api_key(1000, time).await, Err(ApiError::ApiKeyNotFound)));
+
+    let api_keys = dao.get_api_keys().await.expect("error in DAO impl");
+    assert_eq!(api_keys.iter().map(|api_key| api_key.api_key_id).collect::<Vec<_>>(), vec![first.api_key_id, second.api_key_id]);
+}
+
+
+#[sqlx::test]
+async fn test_staff(pool: PgPool) {
+    let dao = PgTableOrderDAO::new(pool.clone());
+    let staff_member = |staff_id: &str, role: Role| StaffMember{
+        staff_id: staff_id.to_string(),
+        name: staff_id.to_string(),
+        role,
+        pin_hash: "a".repeat(64),
+        pin_salt: "b".repeat(32),
+        active: true,
+        creation_time: chrono::Utc::now()
+    };
+
+    let added = dao.add_staff(&staff_member("waiter-1", Role::Waiter)).await.expect("error in DAO impl");
+    assert_eq!(added.pin_hash, "a".repeat(64));
+    dao.add_staff(&staff_member("chef-1", Role::Kitchen)).await.expect("error in DAO impl");
+    assert!(matches!(dao.add_staff(&staff_member("waiter-1", Role::Manager)).await, Err(ApiError::StaffAlreadyExists)));
+
+    assert_eq!(dao.get_staff_member("chef-1").await.expect("error in DAO impl").role, Role::Kitchen);
+    assert!(matches!(dao.get_staff_member("unknown").await, Err(ApiError::StaffNotFound)));
+
+    assert!(!dao.deactivate_staff("waiter-1").await.expect("error in DAO impl").active);
+    assert!(!dao.get_staff_member("waiter-1").await.expect("error in DAO impl").active);
+    assert!(matches!(dao.deactivate_staff("unknown").await, Err(ApiError::StaffNotFound)));
+
+    let staff = dao.get_staff().await.expect("error in DAO impl");
+    assert_eq!(staff.iter().map(|staff| staff.staff_id.as_str()).collect::<Vec<_>>(), vec!["chef-1", "waiter-1"]);
+
+    // orders keep staff who created them
+    let mut table_orders = TableOrdersRequest::new(3);
+    table_orders.add_order_wihtout_note("kkk");
+    let mut orders = process_order_requests(table_orders);
+    orders[0].created_by = Some("waiter-1".to_string());
+    dao.add_table_orders(&orders, &[]).await.expect("error in DAO impl");
+    assert_eq!(dao.get_table_orders(3, None).await.expect("error in DAO impl")[0].created_by.as_deref(), Some("waiter-1"));
+}